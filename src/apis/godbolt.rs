@@ -5,11 +5,14 @@
 //! this service fetches the language/compiler catalog and formatter list once
 //! at startup so the rest of the bot can keep resolving targets offline.
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use godbolt::{
-    AsmDocumentation, ClientState, CompilationRequest, CompilationResult, Compiler, Executor,
-    ExecutorCompiler, Format, FormatRequest, FormatResult, Godbolt, GodboltError, Language,
-    Library, RequestOptions, Session, SessionCompiler,
+    AsmDocumentation, CompilationRequest, CompilationResult, Compiler, Executor, ExecutorCompiler,
+    Format, FormatRequest, FormatResult, Godbolt, GodboltError, Language, Library, RequestOptions,
+    SourceFile, Tool,
 };
+use serde::Serialize;
+use serde_json::{Map, Value};
 
 use crate::utls::constants::USER_AGENT;
 
@@ -22,6 +25,8 @@ pub struct GodboltCacheEntry {
 /// Compiler Explorer client plus the startup caches used for offline lookups
 pub struct GodboltService {
     client: Godbolt,
+    /// For the endpoints the crate has no binding for
+    http: reqwest::Client,
     /// Cache of godbolt languages and their associated compilers
     pub cache: Vec<GodboltCacheEntry>,
     /// Cache of all formatting tools
@@ -32,7 +37,7 @@ impl GodboltService {
     pub async fn new(http: reqwest::Client) -> Result<Self, GodboltError> {
         let client = Godbolt::builder()
             .user_agent(USER_AGENT)
-            .http_client(http)
+            .http_client(http.clone())
             .build();
         let catalog = client.catalog().await?;
         let formats = client.formats().await?;
@@ -56,6 +61,7 @@ impl GodboltService {
 
         Ok(GodboltService {
             client,
+            http,
             cache,
             formats,
         })
@@ -89,6 +95,11 @@ impl GodboltService {
             .find(|language| language.id.eq_ignore_ascii_case(language_id))
     }
 
+    /// Fetches the tools available for a language
+    pub async fn tools_for(&self, language_id: &str) -> Result<Vec<Tool>, GodboltError> {
+        self.client.tools_for(language_id).await
+    }
+
     /// Fetches the libraries available for a language
     pub async fn libraries_for(&self, language_id: &str) -> Result<Vec<Library>, GodboltError> {
         self.client.libraries_for(language_id).await
@@ -103,47 +114,149 @@ impl GodboltService {
         self.client.asm_doc(instruction_set, opcode).await
     }
 
-    /// Compiles `source` with the given compiler and options
+    /// Compiles `source` with the given compiler and options, along with the
+    /// other `files` of a multi-file project. If `cmake` is set, `source` is
+    /// the project's CMakeLists.txt and the project is built with CMake.
+    /// `extra_options` are merged into the compiler options for outputs the
+    /// crate doesn't model, like LLVM IR (`produceIr`).
     pub async fn compile(
         &self,
         compiler: &Compiler,
         source: &str,
+        files: &[SourceFile],
+        cmake: bool,
         options: RequestOptions,
+        extra_options: Map<String, Value>,
     ) -> Result<CompilationResult, GodboltError> {
         let mut request = CompilationRequest::new(source);
         request.options = options;
-        self.client.compile(&compiler.id, &request).await
+        request.files = files.to_vec();
+        let binary = request.options.filters.binary == Some(true);
+        if !cmake && extra_options.is_empty() && !binary {
+            return self.client.compile(&compiler.id, &request).await;
+        }
+
+        // The crate has no binding for CMake builds, which take the same
+        // request and give the same response as a plain compilation, nor for
+        // extra options, and drops the encodings of binary output, so those
+        // requests are sent directly
+        let mut body = serde_json::to_value(&request)?;
+        if let Some(compiler_options) = body
+            .pointer_mut("/options/compilerOptions")
+            .and_then(Value::as_object_mut)
+        {
+            compiler_options.extend(extra_options);
+        }
+
+        let response = self
+            .http
+            .post(format!(
+                "{}/api/compiler/{}/{}",
+                self.client.base_url(),
+                compiler.id,
+                if cmake { "cmake" } else { "compile" }
+            ))
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(GodboltError::Api {
+                status: status.as_u16(),
+                message: body,
+            });
+        }
+        let mut body: Value = serde_json::from_str(&body)?;
+        if binary {
+            inline_opcodes(&mut body);
+        }
+        Ok(serde_json::from_value(body)?)
     }
 
     /// Builds the base64-encoded client state used for godbolt.org
-    /// `/clientstate/` share links
+    /// `/clientstate/` share links. `files` holds every file of a multi-file
+    /// project, which opens in a tree (the site's project editor) with
+    /// `source` as the contents of the first one.
     pub fn get_base64(
         compiler: &Compiler,
         source: &str,
+        files: &[SourceFile],
+        cmake: bool,
         options: &RequestOptions,
     ) -> Result<String, GodboltError> {
-        let state = ClientState {
-            sessions: vec![Session {
-                id: 0,
-                language: compiler.lang.clone(),
-                source: source.to_string(),
-                compilers: vec![SessionCompiler {
-                    id: compiler.id.clone(),
-                    options: options.user_arguments.clone(),
-                }],
-                executors: vec![Executor {
-                    arguments: options.execute_parameters.args.join(" "),
-                    compiler: ExecutorCompiler {
-                        id: compiler.id.clone(),
-                        libs: Vec::new(),
-                        options: options.user_arguments.clone(),
-                    },
-                    stdin: options.execute_parameters.stdin.clone(),
+        let compiler_pane = LinkCompiler {
+            id: compiler.id.clone(),
+            options: options.user_arguments.clone(),
+            tools: options
+                .tools
+                .iter()
+                .map(|tool| LinkTool {
+                    id: tool.id.clone(),
+                    args: tool.args.clone(),
+                })
+                .collect(),
+        };
+        let executor = Executor {
+            arguments: options.execute_parameters.args.join(" "),
+            compiler: ExecutorCompiler {
+                id: compiler.id.clone(),
+                libs: Vec::new(),
+                options: options.user_arguments.clone(),
+            },
+            stdin: options.execute_parameters.stdin.clone(),
+        };
+
+        let state = match files.split_first() {
+            None => LinkState {
+                sessions: vec![LinkSession {
+                    id: 0,
+                    language: compiler.lang.clone(),
+                    source: source.to_string(),
+                    compilers: vec![compiler_pane],
+                    executors: vec![executor],
                 }],
-            }],
+                trees: Vec::new(),
+            },
+            Some((main, files)) => {
+                let files = std::iter::once(main)
+                    .chain(files)
+                    .enumerate()
+                    .map(|(index, file)| TreeFile {
+                        file_id: index as i32 + 1,
+                        is_included: true,
+                        is_open: false,
+                        is_main_source: index == 0,
+                        filename: file.filename.clone(),
+                        content: if index == 0 {
+                            source.to_string()
+                        } else {
+                            file.contents.clone()
+                        },
+                        editor_id: -1,
+                        lang_id: String::new(),
+                    })
+                    .collect::<Vec<_>>();
+                LinkState {
+                    sessions: Vec::new(),
+                    trees: vec![Tree {
+                        id: 1,
+                        cmake_args: String::new(),
+                        custom_output_filename: String::new(),
+                        is_cmake_project: cmake,
+                        compiler_language_id: compiler.lang.clone(),
+                        new_file_id: files.len() as i32 + 1,
+                        files,
+                        compilers: vec![compiler_pane],
+                        executors: vec![executor],
+                    }],
+                }
+            }
         };
 
-        state.to_base64()
+        Ok(STANDARD.encode(serde_json::to_string(&state)?))
     }
 
     /// Formats `source` with the given formatter; an empty `style` means the
@@ -166,3 +279,93 @@ impl GodboltService {
         self.client.format(formatter, &request).await
     }
 }
+
+/// The client state of a share link. The crate's `ClientState` can't
+/// express trees or tool panes.
+#[derive(Serialize)]
+struct LinkState {
+    sessions: Vec<LinkSession>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    trees: Vec<Tree>,
+}
+
+/// A single-file editor
+#[derive(Serialize)]
+struct LinkSession {
+    id: i32,
+    language: String,
+    source: String,
+    compilers: Vec<LinkCompiler>,
+    executors: Vec<Executor>,
+}
+
+/// A compiler pane, with any tool panes attached to it
+#[derive(Serialize)]
+struct LinkCompiler {
+    id: String,
+    options: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<LinkTool>,
+}
+
+#[derive(Serialize)]
+struct LinkTool {
+    id: String,
+    args: String,
+}
+
+/// A multi-file project on godbolt.org
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Tree {
+    id: i32,
+    cmake_args: String,
+    custom_output_filename: String,
+    #[serde(rename = "isCMakeProject")]
+    is_cmake_project: bool,
+    compiler_language_id: String,
+    files: Vec<TreeFile>,
+    new_file_id: i32,
+    compilers: Vec<LinkCompiler>,
+    executors: Vec<Executor>,
+}
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TreeFile {
+    file_id: i32,
+    is_included: bool,
+    is_open: bool,
+    is_main_source: bool,
+    filename: String,
+    content: String,
+    editor_id: i32,
+    lang_id: String,
+}
+
+/// Prefix each instruction of a binary-mode response with its encoding,
+/// aligned into a column, since the crate's `AsmLine` only keeps the text
+pub fn inline_opcodes(response: &mut Value) {
+    let Some(lines) = response.get_mut("asm").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    let encoding = |line: &Value| -> Option<String> {
+        let opcodes = line.get("opcodes")?.as_array()?;
+        let bytes: Vec<&str> = opcodes.iter().filter_map(Value::as_str).collect();
+        (!bytes.is_empty()).then(|| bytes.join(" "))
+    };
+    let width = lines
+        .iter()
+        .filter_map(encoding)
+        .map(|bytes| bytes.len())
+        .max()
+        .unwrap_or(0);
+
+    for line in lines.iter_mut() {
+        let Some(bytes) = encoding(line) else {
+            continue;
+        };
+        let text = line.get("text").and_then(Value::as_str).unwrap_or_default();
+        line["text"] = Value::String(format!("{:<width$}  {}", bytes, text.trim_start()));
+    }
+}
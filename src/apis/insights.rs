@@ -1,8 +1,16 @@
+use std::sync::Arc;
+use std::time::Instant;
+
 use reqwest::StatusCode;
 use serde::*;
 
+use crate::managers::health::HealthManager;
+
 pub const POST_URL: &str = "https://cppinsights.io/api/v1/transform";
 
+/// Name used for cppinsights.io in the health tracker
+const SERVICE: &str = "cppinsights.io";
+
 #[derive(Serialize)]
 pub struct InsightsRequest {
     pub code: String,
@@ -19,16 +27,35 @@ pub struct InsightsResponse {
 }
 pub struct InsightsAPI {
     client: reqwest::Client,
+    health: Arc<HealthManager>,
 }
 
 impl InsightsAPI {
-    pub fn new() -> Self {
+    pub fn new(health: Arc<HealthManager>) -> Self {
+        health.register(SERVICE);
         InsightsAPI {
             client: crate::apis::HTTP_CLIENT.clone(),
+            health,
         }
     }
 
-    pub async fn get_insights(&self, req: InsightsRequest) -> Option<InsightsResponse> {
+    /// Transform the request's code, or return a message suitable for display
+    pub async fn get_insights(&self, req: InsightsRequest) -> Result<InsightsResponse, String> {
+        self.health.check(SERVICE)?;
+
+        let start = Instant::now();
+        let result = self.request(req).await;
+        self.health.record(
+            SERVICE,
+            start.elapsed(),
+            result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+        );
+        result.map_err(|_| {
+            String::from("Unable to retrieve insights at this time! Please try again later.")
+        })
+    }
+
+    async fn request(&self, req: InsightsRequest) -> Result<InsightsResponse, String> {
         let req_result = self
             .client
             .post(POST_URL)
@@ -38,20 +65,20 @@ impl InsightsAPI {
             .await;
         if let Err(e) = req_result {
             warn!("Insights request failure: {}", e);
-            return None;
+            return Err(e.to_string());
         }
         let req = req_result.unwrap();
         if req.status() != StatusCode::OK {
             warn!("Received non-ok status code.");
-            return None;
+            return Err(format!("status {}", req.status()));
         }
 
         let resp = req.json::<InsightsResponse>().await;
         if let Err(e) = resp {
             warn!("Unable to get source insights: {}", e);
-            None
+            Err(e.to_string())
         } else {
-            Some(resp.unwrap())
+            Ok(resp.unwrap())
         }
     }
 }
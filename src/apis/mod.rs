@@ -4,6 +4,7 @@ use std::time::Duration;
 pub mod dbl;
 pub mod godbolt;
 pub mod insights;
+pub mod piston;
 pub mod quick_link;
 pub mod sourcepawn;
 pub mod wandbox;
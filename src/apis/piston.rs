@@ -0,0 +1,162 @@
+//! Client for self-hosted Piston-compatible execution engines.
+//!
+//! The service is enabled by setting PISTON_API_URL to the engine's base url
+//! (e.g. `http://localhost:2000`). The runtime catalog is fetched once at
+//! startup so targets can be resolved offline, like the other services.
+
+use serde::{Deserialize, Serialize};
+
+/// An installed language runtime, as listed by `/api/v2/runtimes`
+#[derive(Deserialize, Clone, Debug)]
+pub struct PistonRuntime {
+    pub language: String,
+    pub version: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Distinguishes engines for the same language (e.g. "node" vs "deno")
+    #[serde(default)]
+    pub runtime: Option<String>,
+}
+
+impl PistonRuntime {
+    /// Compiler id used by the bot, e.g. "python-3.10.0" or "deno-1.16.2"
+    pub fn id(&self) -> String {
+        format!(
+            "{}-{}",
+            self.runtime.as_deref().unwrap_or(&self.language),
+            self.version
+        )
+    }
+
+    /// Whether the language name or one of its aliases matches
+    pub fn is_language(&self, language: &str) -> bool {
+        self.language == language || self.aliases.iter().any(|alias| alias == language)
+    }
+}
+
+#[derive(Serialize)]
+struct PistonFile<'a> {
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ExecuteRequest<'a> {
+    language: &'a str,
+    version: &'a str,
+    files: Vec<PistonFile<'a>>,
+    stdin: &'a str,
+    args: &'a [String],
+}
+
+#[derive(Deserialize, Default, Debug)]
+pub struct PistonStage {
+    /// stdout and stderr interleaved as the program wrote them
+    #[serde(default)]
+    pub output: String,
+    #[serde(default)]
+    pub stdout: String,
+    pub code: Option<i32>,
+    /// Signal name (e.g. "SIGKILL") if the stage was killed
+    pub signal: Option<String>,
+}
+
+impl PistonStage {
+    pub fn success(&self) -> bool {
+        self.code == Some(0) && self.signal.is_none()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PistonResponse {
+    pub run: PistonStage,
+    /// Only present for compiled languages
+    pub compile: Option<PistonStage>,
+}
+
+impl PistonResponse {
+    pub fn success(&self) -> bool {
+        self.compile.as_ref().map(|c| c.success()).unwrap_or(true) && self.run.success()
+    }
+}
+
+/// Piston client plus the startup runtime cache used for offline lookups
+pub struct PistonService {
+    http: reqwest::Client,
+    endpoint: String,
+    runtimes: Vec<PistonRuntime>,
+}
+
+impl PistonService {
+    pub async fn new(http: reqwest::Client, endpoint: &str) -> Result<Self, reqwest::Error> {
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        let runtimes = http
+            .get(format!("{}/api/v2/runtimes", endpoint))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(PistonService {
+            http,
+            endpoint,
+            runtimes,
+        })
+    }
+
+    /// Returns every cached runtime
+    pub fn get_runtimes(&self) -> &[PistonRuntime] {
+        &self.runtimes
+    }
+
+    /// Resolves a target to a runtime: first as a compiler id, then as a
+    /// language name or alias (yielding that language's newest version)
+    pub fn resolve(&self, target: &str) -> Option<&PistonRuntime> {
+        if let Some(runtime) = self.runtimes.iter().find(|rt| rt.id() == target) {
+            return Some(runtime);
+        }
+
+        self.runtimes
+            .iter()
+            .filter(|rt| rt.is_language(target))
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+
+    /// Compiles (if needed) and runs code with the given runtime
+    pub async fn execute(
+        &self,
+        runtime: &PistonRuntime,
+        code: &str,
+        stdin: &str,
+        args: &[String],
+    ) -> Result<PistonResponse, reqwest::Error> {
+        let request = ExecuteRequest {
+            language: &runtime.language,
+            version: &runtime.version,
+            files: vec![PistonFile { content: code }],
+            stdin,
+            args,
+        };
+
+        self.http
+            .post(format!("{}/api/v2/execute", self.endpoint))
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+/// Orders dotted versions numerically, so 3.10.0 sorts after 3.9.4
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
@@ -1,21 +1,48 @@
+use std::sync::Arc;
+use std::time::Instant;
+
 use reqwest::StatusCode;
 
+use crate::managers::health::HealthManager;
+
+/// Name used for the link shortener in the health tracker
+const SERVICE: &str = "quick link";
+
 pub struct LinkAPI {
     client: reqwest::Client,
     request_base: String,
     redirect_base: String,
+    health: Arc<HealthManager>,
 }
 
 impl LinkAPI {
-    pub fn new(request_base: &str, redirect_base: &str) -> Self {
+    pub fn new(request_base: &str, redirect_base: &str, health: Arc<HealthManager>) -> Self {
+        health.register(SERVICE);
         LinkAPI {
             client: crate::apis::HTTP_CLIENT.clone(),
             request_base: request_base.to_string(),
             redirect_base: redirect_base.to_string(),
+            health,
         }
     }
 
+    /// Shorten a url. Links are optional, so failures just leave them out.
     pub async fn get_link(&self, url: String) -> Option<String> {
+        if self.health.check(SERVICE).is_err() {
+            return None;
+        }
+
+        let start = Instant::now();
+        let result = self.request(url).await;
+        self.health.record(
+            SERVICE,
+            start.elapsed(),
+            result.as_ref().map(|_| ()).map_err(|e| e.clone()),
+        );
+        result.ok()
+    }
+
+    async fn request(&self, url: String) -> Result<String, String> {
         let trimmed = url.trim_end().to_string();
         let req_result = self
             .client
@@ -26,22 +53,22 @@ impl LinkAPI {
             .await;
         if let Err(e) = req_result {
             warn!("Quick link request failure: {}", e);
-            return None;
+            return Err(e.to_string());
         }
         let req = req_result.unwrap();
         if req.status() != StatusCode::OK {
             warn!("Received non-ok status code.");
-            return None;
+            return Err(format!("status {}", req.status()));
         }
 
         let body = req.text().await;
         if let Err(e) = body {
             warn!("Unable to get quick link: {}", e);
-            None
+            Err(e.to_string())
         } else {
             let url = format!("{}{}", self.redirect_base, body.unwrap());
             info!("Generated url: {}", &url);
-            Some(url)
+            Ok(url)
         }
     }
 }
@@ -0,0 +1,222 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use serenity::async_trait;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use crate::boilerplate::generator::boilerplate_factory;
+use crate::managers::compilation::CompilationResult;
+use crate::utls::constants::JAVA_PUBLIC_CLASS_REGEX;
+use crate::utls::parser::ParserResult;
+
+/// The language and compiler a backend would use for a target
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedTarget {
+    /// The backend's language id (e.g., "c++", "python")
+    pub language: String,
+    /// The backend's compiler id (e.g., "g132", "cpython-3.12.7")
+    pub compiler: String,
+}
+
+/// A service that can compile, run, or disassemble code on behalf of
+/// `CompilationManager`.
+///
+/// Backends are consulted in priority order; the first one that resolves a
+/// target handles the request. Implement this trait and register the backend
+/// with `CompilationManager::register` to add a new service.
+#[async_trait]
+pub trait CompilerBackend: Send + Sync {
+    /// Short identifier used for priority configuration (e.g., "godbolt")
+    fn id(&self) -> &'static str;
+
+    /// Name shown to users in embed footers (e.g., "godbolt.org")
+    fn display_name(&self) -> &'static str;
+
+    /// Resolves a target (language or compiler) to the compiler this
+    /// backend would use, or None if the target is unsupported
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget>;
+
+    /// Compile code without executing it
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError>;
+
+    /// Compile and execute code, optionally generating boilerplate first
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError>;
+
+    /// Compile code and return its assembly output
+    async fn assembly(
+        &self,
+        request: &ParserResult,
+        _author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        Err(CommandError::from(format!(
+            "Target '{}' does not produce assembly.",
+            request.target
+        )))
+    }
+
+    /// Whether `assembly` is supported by this backend
+    fn produces_assembly(&self) -> bool {
+        false
+    }
+
+    /// Whether multi-file projects (see `ParserResult::files`) can be built
+    fn supports_projects(&self) -> bool {
+        false
+    }
+
+    /// Whether executions report how long the program ran, which `;bench`
+    /// relies on
+    fn reports_exec_time(&self) -> bool {
+        false
+    }
+
+    /// Whether programs can be built and run with sanitizers like `--asan`
+    fn supports_sanitizers(&self) -> bool {
+        false
+    }
+
+    /// Every language this backend supports
+    fn languages(&self) -> Vec<String>;
+
+    /// Formatted list of compilers for a language, sorted by similarity to
+    /// the filter if one is supplied
+    fn list_compilers(
+        &self,
+        language: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError>;
+
+    /// Whether `list_libraries` is supported for the given language
+    fn supports_libraries(&self, _language: &str) -> bool {
+        false
+    }
+
+    /// Formatted list of libraries for a language, sorted by similarity to
+    /// the filter if one is supplied
+    async fn list_libraries(
+        &self,
+        language: &str,
+        _filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        Err(CommandError::from(format!(
+            "Libraries are not available for '{}'.",
+            language
+        )))
+    }
+
+    /// Whether the backend's catalog is loaded and it can take requests
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Re-fetch the backend's catalog. Backends without one have nothing to
+    /// do; see `CompilationManager::spawn_refresher`.
+    async fn refresh(&self) -> Result<(), CommandError> {
+        Ok(())
+    }
+
+    /// Allows `CompilationManager` to hand out the concrete backend
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// The backend itself failed (transport error, timeout or 5xx) rather than
+/// rejecting the request. `CompilationManager` retries these on another
+/// backend when one supports the language.
+#[derive(Debug)]
+pub struct BackendUnavailable(pub String);
+
+impl fmt::Display for BackendUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for BackendUnavailable {}
+
+/// Whether an error returned by a backend is a `BackendUnavailable`
+pub fn is_unavailable(error: &CommandError) -> bool {
+    error.downcast_ref::<BackendUnavailable>().is_some()
+}
+
+/// Holds a backend's service, which may be missing (e.g. the site was down at
+/// startup) or replaced by a refreshed one at any time. Callers take a cheap
+/// snapshot, so a swap never waits on or disturbs in-flight compilations.
+pub struct ServiceSlot<T> {
+    service: RwLock<Option<Arc<T>>>,
+}
+
+impl<T> Default for ServiceSlot<T> {
+    fn default() -> Self {
+        ServiceSlot {
+            service: RwLock::new(None),
+        }
+    }
+}
+
+impl<T> ServiceSlot<T> {
+    pub fn get(&self) -> Option<Arc<T>> {
+        self.service.read().unwrap().clone()
+    }
+
+    pub fn set(&self, service: T) {
+        *self.service.write().unwrap() = Some(Arc::new(service));
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.service.read().unwrap().is_some()
+    }
+}
+
+/// Prepare code by adding boilerplate and fixing common issues
+pub fn boilerplate_generation(language: &str, code: &str) -> String {
+    let generator = boilerplate_factory(language, code);
+    let code = if generator.needs_boilerplate() {
+        generator.generate()
+    } else {
+        code.to_string()
+    };
+
+    fix_common_problems(language, code)
+}
+
+/// Fix common language-specific issues in user code
+fn fix_common_problems(language: &str, code: String) -> String {
+    match language {
+        "java" => {
+            // Remove 'public' from class declarations (Godbolt doesn't like public classes)
+            let mut fixed = code.clone();
+            for m in JAVA_PUBLIC_CLASS_REGEX.captures_iter(&code) {
+                if let Some(pub_keyword) = m.name("public") {
+                    fixed.replace_range(pub_keyword.range(), "");
+                }
+            }
+            fixed
+        }
+        _ => code,
+    }
+}
+
+/// Check if a compiler matches the filter string
+pub fn matches_filter(id: &str, name: &str, filter: &str) -> bool {
+    filter
+        .split_whitespace()
+        .any(|part| id.contains(part) || name.contains(part))
+}
+
+/// Compute similarity score for sorting filtered results
+pub fn compute_similarity(id: &str, name: &str, filter: &str) -> f64 {
+    let id_sim = similar_string::compare_similarity(filter, id);
+    let name_sim = similar_string::compare_similarity(filter, name);
+    f64::max(id_sim, name_sim)
+}
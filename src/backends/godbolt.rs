@@ -0,0 +1,1319 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use serenity::async_trait;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use crate::apis::godbolt::GodboltService;
+use crate::backends::backend::{
+    boilerplate_generation, compute_similarity, matches_filter, BackendUnavailable,
+    CompilerBackend, ResolvedTarget, ServiceSlot,
+};
+use crate::managers::compilation::{CompilationDetails, CompilationResult};
+use crate::utls::cfg;
+use crate::utls::diagnostics;
+use crate::utls::discordhelpers::embeds::{self, EmbedOptions, ExtraOutput, SourceMap};
+use crate::utls::parser::ParserResult;
+use crate::utls::triage;
+use godbolt::{
+    CompilationFilters, CompilerOptions, GodboltError, LibrarySelection, OutputLine, PpOptions,
+    RequestOptions, SourceFile, Tool, ToolInvocation,
+};
+use serde::Deserialize;
+
+/// Names Compiler Explorer reports the main file by: `<source>`, or the
+/// `example.cpp` (`example.rs`, …) it saves it as when output isn't masked
+const GODBOLT_SOURCE_NAMES: &[&str] = &["<source>", "example"];
+
+/// What kind of request to send to Compiler Explorer
+#[derive(Clone, Copy, PartialEq)]
+enum GodboltMode {
+    Execute,
+    Check,
+    Assembly,
+    Graph,
+    Dump(DumpKind),
+}
+
+/// Compiler output other than assembly that Compiler Explorer can produce,
+/// used by ;ir, ;ast, ;remarks and ;gccdump
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DumpKind {
+    LlvmIr,
+    ClangAst,
+    OptRemarks,
+    GccDump,
+}
+
+impl DumpKind {
+    /// Embed field title and code fence language
+    fn display(&self) -> (&'static str, &'static str) {
+        match self {
+            DumpKind::LlvmIr => ("LLVM IR", "llvm"),
+            DumpKind::ClangAst => ("AST", ""),
+            DumpKind::OptRemarks => ("Optimization Remarks", ""),
+            DumpKind::GccDump => ("GCC Dump", ""),
+        }
+    }
+
+    /// Name of the attachment holding output too long for the embed
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            DumpKind::LlvmIr => "output.ll",
+            DumpKind::ClangAst => "ast.txt",
+            DumpKind::OptRemarks => "remarks.txt",
+            DumpKind::GccDump => "dump.txt",
+        }
+    }
+
+    /// What to call the output in errors
+    fn description(&self) -> &'static str {
+        match self {
+            DumpKind::LlvmIr => "LLVM IR",
+            DumpKind::ClangAst => "a Clang AST",
+            DumpKind::OptRemarks => "optimization remarks",
+            DumpKind::GccDump => "GCC tree/RTL dumps",
+        }
+    }
+}
+
+/// Compiler Explorer (godbolt.org) backend. The catalog is empty until the
+/// first successful `refresh`.
+#[derive(Default)]
+pub struct GodboltBackend {
+    service: ServiceSlot<GodboltService>,
+}
+
+impl GodboltBackend {
+    /// Snapshot of the current service, if the catalog has been loaded
+    pub fn service(&self) -> Option<Arc<GodboltService>> {
+        self.service.get()
+    }
+
+    fn require_service(&self) -> Result<Arc<GodboltService>, CommandError> {
+        self.service.get().ok_or_else(|| {
+            CommandError::from(BackendUnavailable(String::from(
+                "Compiler Explorer is unavailable. This may be due to an outage. Please try again later.",
+            )))
+        })
+    }
+
+    /// Compile using Compiler Explorer (godbolt.org).
+    /// Used by `compile` (check mode), `execute` (execute mode), `assembly`
+    /// (asm mode), `control_flow_graph` (graph mode) and `dump` (dump modes).
+    /// Also returns a file to attach: the control-flow graph, or the full
+    /// output of a dump that was too long for the embed.
+    async fn compile_with_mode(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        mode: GodboltMode,
+        boilerplate: bool,
+    ) -> Result<(CompilationResult, Option<String>), CommandError> {
+        let godbolt = &self.require_service()?;
+
+        // Resolve target to a specific compiler
+        let asm_mode = matches!(
+            mode,
+            GodboltMode::Assembly | GodboltMode::Graph | GodboltMode::Dump(_)
+        );
+        let target = normalize_target(&request.target);
+        let compiler = godbolt.resolve(target).ok_or_else(|| {
+            if asm_mode {
+                CommandError::from(format!(
+                    "Target '{}' either does not produce assembly or is not supported on godbolt.org",
+                    target
+                ))
+            } else {
+                CommandError::from(format!(
+                    "Unable to find compiler for target '{}'.",
+                    target
+                ))
+            }
+        })?;
+
+        // Split any `-lib` requests out of the compiler options
+        let mut request = request.clone();
+        let library_specs = take_library_specs(&mut request.options)?;
+        let gcc_pass = take_gcc_pass(&mut request.options);
+        let asm_flags = take_asm_flags(&mut request.options);
+        let source_map = take_source_map(&mut request.options)?;
+        let request = &request;
+
+        let code = if boilerplate && !request.is_project() {
+            boilerplate_generation(&compiler.lang, &request.code)
+        } else {
+            request.code.to_owned()
+        };
+        let files = project_files(request);
+
+        // Build request options
+        let mut options = match mode {
+            GodboltMode::Execute => build_execute_options(request),
+            GodboltMode::Check => build_check_options(request),
+            GodboltMode::Assembly | GodboltMode::Graph => build_asm_options(request),
+            GodboltMode::Dump(kind) => build_dump_options(request, kind),
+        };
+        if asm_mode {
+            apply_asm_flags(&mut options.filters, &asm_flags);
+        }
+        options.libraries = resolve_libraries(godbolt, &compiler.lang, &library_specs).await?;
+        let preprocessor = options.compiler_options.produce_pp.is_some();
+        let extra_options = match mode {
+            GodboltMode::Dump(kind) => build_dump_extra_options(kind, None),
+            GodboltMode::Graph => {
+                let mut extra = serde_json::Map::new();
+                extra.insert(
+                    String::from("produceCfg"),
+                    serde_json::json!({ "asm": true, "ir": false }),
+                );
+                extra
+            }
+            _ => Default::default(),
+        };
+
+        // Get shareable link
+        let godbolt_base64 =
+            GodboltService::get_base64(&compiler, &code, &files, request.is_cmake(), &options).ok();
+
+        // Send compilation request
+        let send = |options: RequestOptions, extra_options| {
+            godbolt.compile(
+                &compiler,
+                &code,
+                files.get(1..).unwrap_or_default(),
+                request.is_cmake(),
+                options,
+                extra_options,
+            )
+        };
+        let mut response = send(options.clone(), extra_options)
+            .await
+            .map_err(classify_error)?;
+
+        // A pass's dump is only produced once it's selected from the passes
+        // the first response lists
+        if let (GodboltMode::Dump(DumpKind::GccDump), Some(name)) = (mode, &gcc_pass) {
+            let pass = find_gcc_pass(&response, name)?;
+            response = send(
+                options,
+                build_dump_extra_options(DumpKind::GccDump, Some(pass)),
+            )
+            .await
+            .map_err(classify_error)?;
+        }
+
+        let mut details = CompilationDetails {
+            language: compiler.lang.clone(),
+            compiler: compiler.name.clone(),
+            godbolt_base64,
+            success: response.code == 0,
+            executed: mode == GodboltMode::Execute,
+            exec_time: response.exec_time.filter(|_| mode == GodboltMode::Execute),
+            assembly: asm_mode.then(|| {
+                response
+                    .asm
+                    .iter()
+                    .flatten()
+                    .filter_map(|line| line.text.as_deref())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }),
+            stdout: (mode == GodboltMode::Execute).then(|| program_stdout(&response)),
+            signal: (mode == GodboltMode::Execute)
+                .then(|| crash_signal(&response))
+                .flatten(),
+            compiler_log: diagnostics::full_log(&compiler_log(&response)),
+            counts: None,
+            service: Some(self.display_name()),
+        };
+
+        let mut embed_options = EmbedOptions::new(asm_mode, preprocessor);
+        embed_options.sources = diagnostics::sources(request, &code, GODBOLT_SOURCE_NAMES);
+        embed_options.output = request.output;
+        if asm_mode {
+            embed_options.source_map = source_map.map(|lines| SourceMap {
+                code: code.clone(),
+                lines,
+            });
+        }
+        let mut attachment = None;
+        if let GodboltMode::Dump(kind) = mode {
+            if response.code == 0 {
+                let text = dump_output(&response, kind, gcc_pass.is_some()).ok_or_else(|| {
+                    CommandError::from(format!(
+                        "{} can't produce {}.",
+                        compiler.name,
+                        kind.description()
+                    ))
+                })?;
+                if !embeds::fits_in_embed(&text) {
+                    attachment = Some(text.clone());
+                }
+                let (title, fence) = kind.display();
+                embed_options.extra_output = Some(ExtraOutput { title, fence, text });
+            }
+        }
+        if mode == GodboltMode::Graph && response.code == 0 {
+            let graph = response.extra.get("cfg").and_then(cfg::to_dot);
+            attachment = Some(graph.ok_or_else(|| {
+                CommandError::from(format!(
+                    "{} can't produce a control-flow graph.",
+                    compiler.name
+                ))
+            })?);
+        }
+        let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
+
+        Ok((CompilationResult { details, embed }, attachment))
+    }
+
+    /// Compile to assembly along with the control-flow graph of each function
+    /// as Graphviz DOT
+    pub async fn control_flow_graph(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<(CompilationResult, Option<String>), CommandError> {
+        self.compile_with_mode(request, author, GodboltMode::Graph, false)
+            .await
+    }
+
+    /// Compile and show one of the outputs in `DumpKind` instead of assembly,
+    /// along with the full output when it's too long for the embed
+    pub async fn dump(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        kind: DumpKind,
+    ) -> Result<(CompilationResult, Option<String>), CommandError> {
+        self.compile_with_mode(request, author, GodboltMode::Dump(kind), false)
+            .await
+    }
+
+    /// Directly compile using Compiler Explorer and return raw response.
+    /// Used by the ;cpp command which needs access to the raw response.
+    pub async fn compile_raw(
+        &self,
+        request: &ParserResult,
+    ) -> Result<(CompilationDetails, godbolt::CompilationResult), CommandError> {
+        let godbolt = &self.require_service()?;
+
+        let target = normalize_target(&request.target);
+        let compiler = godbolt.resolve(target).ok_or_else(|| {
+            CommandError::from(format!("Unable to find compiler for target '{}'.", target))
+        })?;
+
+        // Split any `-lib` requests out of the compiler options
+        let mut request = request.clone();
+        let library_specs = take_library_specs(&mut request.options)?;
+        let request = &request;
+
+        let code = if request.is_project() {
+            request.code.to_owned()
+        } else {
+            boilerplate_generation(&compiler.lang, &request.code)
+        };
+        let files = project_files(request);
+        let mut options = build_execute_options(request);
+        options.libraries = resolve_libraries(godbolt, &compiler.lang, &library_specs).await?;
+        let godbolt_base64 =
+            GodboltService::get_base64(&compiler, &code, &files, request.is_cmake(), &options).ok();
+        let response = godbolt
+            .compile(
+                &compiler,
+                &code,
+                files.get(1..).unwrap_or_default(),
+                request.is_cmake(),
+                options,
+                Default::default(),
+            )
+            .await
+            .map_err(classify_error)?;
+
+        let details = CompilationDetails {
+            language: compiler.lang.clone(),
+            compiler: compiler.name.clone(),
+            godbolt_base64,
+            success: response.code == 0,
+            executed: true,
+            exec_time: response.exec_time,
+            assembly: None,
+            stdout: Some(program_stdout(&response)),
+            signal: crash_signal(&response),
+            compiler_log: None,
+            counts: None,
+            service: Some(self.display_name()),
+        };
+
+        Ok((details, response))
+    }
+
+    /// Compile with one of Compiler Explorer's tools (clang-tidy, pahole,
+    /// llvm-mca, ...) running alongside, adding the tool's output to the
+    /// embed. Used by the ;tool command.
+    pub async fn run_tool(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        tool_id: &str,
+        tool_args: &str,
+    ) -> Result<CompilationResult, CommandError> {
+        let godbolt = &self.require_service()?;
+
+        let target = normalize_target(&request.target);
+        let compiler = godbolt.resolve(target).ok_or_else(|| {
+            CommandError::from(format!(
+                "Target '{}' is not supported on godbolt.org",
+                target
+            ))
+        })?;
+        let tool = resolve_tool(godbolt, &compiler.lang, tool_id).await?;
+
+        let mut request = request.clone();
+        let library_specs = take_library_specs(&mut request.options)?;
+        let request = &request;
+
+        // Post-compilation tools work on the assembly or binary, so ask for
+        // both even though neither is shown
+        let files = project_files(request);
+        let mut options = build_asm_options(request);
+        options.compiler_options.produce_pp = None;
+        options.libraries = resolve_libraries(godbolt, &compiler.lang, &library_specs).await?;
+        options.tools.push(ToolInvocation {
+            id: tool.id.clone(),
+            args: tool_args.to_string(),
+            stdin: (tool.allow_stdin && !request.stdin.is_empty()).then(|| request.stdin.clone()),
+        });
+
+        let godbolt_base64 = GodboltService::get_base64(
+            &compiler,
+            &request.code,
+            &files,
+            request.is_cmake(),
+            &options,
+        )
+        .ok();
+        let response = godbolt
+            .compile(
+                &compiler,
+                &request.code,
+                files.get(1..).unwrap_or_default(),
+                request.is_cmake(),
+                options,
+                Default::default(),
+            )
+            .await
+            .map_err(classify_error)?;
+        let output = tool_output(&response, &tool.id);
+
+        let mut details = CompilationDetails {
+            language: compiler.lang.clone(),
+            compiler: compiler.name.clone(),
+            godbolt_base64,
+            success: response.code == 0 && output.as_ref().is_some_and(|o| o.code == 0),
+            executed: false,
+            exec_time: None,
+            assembly: None,
+            stdout: None,
+            signal: None,
+            compiler_log: None,
+            counts: None,
+            service: Some(self.display_name()),
+        };
+
+        let mut embed_options = EmbedOptions::new(false, false);
+        embed_options.sources = diagnostics::sources(request, &request.code, GODBOLT_SOURCE_NAMES);
+        embed_options.output = request.output;
+        let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
+        let embed = embeds::add_tool_output(embed, &tool.name, output.map(|o| o.text()));
+
+        Ok(CompilationResult { details, embed })
+    }
+
+    /// The tools available for a target's language, for ;tool list
+    pub async fn list_tools(&self, target: &str) -> Result<Vec<String>, CommandError> {
+        let godbolt = self.require_service()?;
+        let target = normalize_target(target);
+        let compiler = godbolt.resolve(target).ok_or_else(|| {
+            CommandError::from(format!(
+                "Target '{}' is not supported on godbolt.org",
+                target
+            ))
+        })?;
+
+        let tools = godbolt.tools_for(&compiler.lang).await.map_err(|e| {
+            CommandError::from(format!(
+                "Unable to fetch tools for '{}': {}",
+                compiler.lang, e
+            ))
+        })?;
+        Ok(tools
+            .into_iter()
+            .map(|tool| format!("{} -> **{}**", tool.name, tool.id))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl CompilerBackend for GodboltBackend {
+    fn id(&self) -> &'static str {
+        "godbolt"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "godbolt.org"
+    }
+
+    fn reports_exec_time(&self) -> bool {
+        true
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        self.service
+            .get()?
+            .resolve(normalize_target(target))
+            .map(|compiler| ResolvedTarget {
+                language: compiler.lang,
+                compiler: compiler.id,
+            })
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.compile_with_mode(request, author, GodboltMode::Check, false)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        self.compile_with_mode(request, author, GodboltMode::Execute, boilerplate)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    async fn assembly(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.compile_with_mode(request, author, GodboltMode::Assembly, false)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    fn produces_assembly(&self) -> bool {
+        true
+    }
+
+    fn supports_sanitizers(&self) -> bool {
+        true
+    }
+
+    fn supports_projects(&self) -> bool {
+        true
+    }
+
+    fn languages(&self) -> Vec<String> {
+        self.service
+            .get()
+            .map(|godbolt| {
+                godbolt
+                    .cache
+                    .iter()
+                    .map(|entry| entry.language.id.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn list_compilers(
+        &self,
+        language: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        let godbolt = self.require_service()?;
+        let mut results: Vec<(f64, String)> = Vec::new();
+
+        for cache_entry in &godbolt.cache {
+            if cache_entry.language.id != language {
+                continue;
+            }
+
+            for compiler in &cache_entry.compilers {
+                let display = format!("{} -> **{}**", &compiler.name, &compiler.id);
+
+                if let Some(filter_str) = filter {
+                    if !matches_filter(&compiler.id, &compiler.name, filter_str) {
+                        continue;
+                    }
+                    let similarity = compute_similarity(&compiler.id, &compiler.name, filter_str);
+                    results.push((similarity, display));
+                } else {
+                    results.push((0.0, display));
+                }
+            }
+        }
+
+        if filter.is_some() {
+            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        }
+
+        Ok(results.into_iter().map(|(_, s)| s).collect())
+    }
+
+    fn supports_libraries(&self, language: &str) -> bool {
+        self.service.get().is_some_and(|godbolt| {
+            godbolt
+                .cache
+                .iter()
+                .any(|entry| entry.language.id == language)
+        })
+    }
+
+    async fn list_libraries(
+        &self,
+        language: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        let godbolt = self.require_service()?;
+        let libraries = godbolt.libraries_for(language).await.map_err(|e| {
+            CommandError::from(format!(
+                "Unable to fetch libraries for '{}': {}",
+                language, e
+            ))
+        })?;
+
+        let mut results: Vec<(f64, String)> = Vec::new();
+
+        for library in libraries {
+            let versions = match (library.versions.first(), library.versions.last()) {
+                (Some(first), Some(last)) if library.versions.len() > 1 => {
+                    format!("{} … {}", first.version, last.version)
+                }
+                (Some(only), _) => only.version.clone(),
+                _ => String::from("none"),
+            };
+            let display = format!("{} -> **{}** ({})", library.name, library.id, versions);
+
+            if let Some(filter_str) = filter {
+                if !matches_filter(&library.id, &library.name, filter_str) {
+                    continue;
+                }
+                let similarity = compute_similarity(&library.id, &library.name, filter_str);
+                results.push((similarity, display));
+            } else {
+                results.push((0.0, display));
+            }
+        }
+
+        if filter.is_some() {
+            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        }
+
+        Ok(results.into_iter().map(|(_, s)| s).collect())
+    }
+
+    fn is_available(&self) -> bool {
+        self.service.is_set()
+    }
+
+    async fn refresh(&self) -> Result<(), CommandError> {
+        let godbolt = GodboltService::new(crate::apis::HTTP_CLIENT.clone())
+            .await
+            .map_err(|e| CommandError::from(e.to_string()))?;
+        info!(
+            "Compiler Explorer catalog loaded ({} languages)",
+            godbolt.cache.len()
+        );
+        self.service.set(godbolt);
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// ============================================================================
+// Helper functions
+// ============================================================================
+
+/// Transport failures and server errors mean Compiler Explorer is having
+/// trouble, anything else is a problem with the request
+fn classify_error(error: GodboltError) -> CommandError {
+    match error {
+        GodboltError::Http(_) | GodboltError::Api { status: 500.., .. } => {
+            CommandError::from(BackendUnavailable(format!(
+                "Compiler Explorer is unavailable. This may be due to an outage. Please try again later. ({})",
+                error
+            )))
+        }
+        _ => CommandError::from(error),
+    }
+}
+
+/// The files of a multi-file request in the crate's form. The main file
+/// stays first so share links can name it.
+fn project_files(request: &ParserResult) -> Vec<SourceFile> {
+    request
+        .files
+        .iter()
+        .map(|file| SourceFile {
+            filename: file.name.clone(),
+            contents: file.code.clone(),
+        })
+        .collect()
+}
+
+/// Normalize target names (e.g., "haskell" -> "ghc901")
+fn normalize_target(target: &str) -> &str {
+    match target {
+        "haskell" => "ghc901",
+        other => other,
+    }
+}
+
+/// Remove `-lib` flags (`-lib fmt:trunk`, `-lib fmt`, `-libfmt`) from the
+/// compiler options, returning the extracted library specs
+fn take_library_specs(options: &mut Vec<String>) -> Result<Vec<String>, CommandError> {
+    let mut specs = Vec::new();
+    let mut remaining = Vec::new();
+
+    let mut iter = std::mem::take(options).into_iter();
+    while let Some(opt) = iter.next() {
+        if opt == "-lib" {
+            let spec = iter.next().ok_or_else(|| {
+                CommandError::from("The `-lib` flag requires a library, e.g. `-lib fmt:trunk`")
+            })?;
+            specs.push(spec);
+        } else if let Some(spec) = opt.strip_prefix("-lib") {
+            specs.push(spec.to_string());
+        } else {
+            remaining.push(opt);
+        }
+    }
+
+    *options = remaining;
+    Ok(specs)
+}
+
+/// Resolve `-lib` specs (`<library>:<version>`) against Compiler Explorer's
+/// library list for the given language
+async fn resolve_libraries(
+    godbolt: &GodboltService,
+    language_id: &str,
+    specs: &[String],
+) -> Result<Vec<LibrarySelection>, CommandError> {
+    if specs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let libraries = godbolt.libraries_for(language_id).await.map_err(|e| {
+        CommandError::from(format!(
+            "Unable to fetch libraries for '{}': {}",
+            language_id, e
+        ))
+    })?;
+
+    let mut selections = Vec::new();
+    for spec in specs {
+        let (name, version_str) = spec.split_once(':').unwrap_or((spec.as_str(), ""));
+
+        let library = libraries
+            .iter()
+            .find(|lib| lib.id.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                CommandError::from(format!(
+                    "Unknown library '{}' for language '{}'. See `;libraries {}` to browse.\n\nAvailable libraries: {}",
+                    name,
+                    language_id,
+                    language_id,
+                    summarize_names(libraries.iter().map(|lib| lib.id.as_str()))
+                ))
+            })?;
+
+        let version = if version_str.is_empty() {
+            library.versions.last().ok_or_else(|| {
+                CommandError::from(format!(
+                    "Library '{}' has no versions available.",
+                    library.id
+                ))
+            })?
+        } else {
+            library
+                .versions
+                .iter()
+                .find(|ver| {
+                    ver.id.eq_ignore_ascii_case(version_str)
+                        || ver.version.eq_ignore_ascii_case(version_str)
+                        || ver
+                            .alias
+                            .iter()
+                            .any(|alias| alias.eq_ignore_ascii_case(version_str))
+                })
+                .ok_or_else(|| {
+                    CommandError::from(format!(
+                        "Unknown version '{}' for library '{}'.\n\nAvailable versions: {}",
+                        version_str,
+                        library.id,
+                        summarize_names(library.versions.iter().map(|ver| ver.version.as_str()))
+                    ))
+                })?
+        };
+
+        selections.push(LibrarySelection {
+            id: library.id.clone(),
+            version: version.id.clone(),
+        });
+    }
+
+    Ok(selections)
+}
+
+/// Join names with commas, truncating once the list grows too long for an
+/// error message
+fn summarize_names<'a>(names: impl Iterator<Item = &'a str>) -> String {
+    let mut summary = String::new();
+    for name in names {
+        if !summary.is_empty() {
+            summary.push_str(", ");
+        }
+        if summary.len() + name.len() > 800 {
+            summary.push('…');
+            break;
+        }
+        summary.push_str(name);
+    }
+    summary
+}
+
+/// Find a tool by id among those Compiler Explorer offers for a language
+async fn resolve_tool(
+    godbolt: &GodboltService,
+    language_id: &str,
+    tool_id: &str,
+) -> Result<Tool, CommandError> {
+    let tools = godbolt.tools_for(language_id).await.map_err(|e| {
+        CommandError::from(format!(
+            "Unable to fetch tools for '{}': {}",
+            language_id, e
+        ))
+    })?;
+
+    let found = tools
+        .iter()
+        .find(|tool| tool.id.eq_ignore_ascii_case(tool_id))
+        .cloned();
+    found.ok_or_else(|| {
+        CommandError::from(format!(
+            "Unknown tool '{}' for language '{}'. See `;tool list {}` to browse.\n\nAvailable tools: {}",
+            tool_id,
+            language_id,
+            language_id,
+            summarize_names(tools.iter().map(|tool| tool.id.as_str()))
+        ))
+    })
+}
+
+/// What a tool run alongside a compilation printed. The crate leaves the
+/// response's `tools` field unmodelled.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct ToolOutput {
+    pub id: String,
+    pub code: i32,
+    pub stdout: Vec<OutputLine>,
+    pub stderr: Vec<OutputLine>,
+}
+
+impl ToolOutput {
+    /// Stdout followed by stderr
+    pub fn text(&self) -> String {
+        self.stdout
+            .iter()
+            .chain(&self.stderr)
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// The output of the given tool, if it ran
+pub fn tool_output(response: &godbolt::CompilationResult, tool_id: &str) -> Option<ToolOutput> {
+    let tools = response.extra.get("tools")?.clone();
+    serde_json::from_value::<Vec<ToolOutput>>(tools)
+        .ok()?
+        .into_iter()
+        .find(|tool| tool.id == tool_id)
+}
+
+/// Everything the compiler wrote, which is kept apart from the program's
+/// stderr when it ran
+fn compiler_log(response: &godbolt::CompilationResult) -> String {
+    let lines = match &response.build_result {
+        Some(build_result) => &build_result.stderr,
+        None => &response.stderr,
+    };
+    lines
+        .iter()
+        .map(|line| format!("{}\n", line.text))
+        .collect()
+}
+
+/// Everything the program wrote to stdout
+fn program_stdout(response: &godbolt::CompilationResult) -> String {
+    response
+        .stdout
+        .iter()
+        .map(|line| format!("{}\n", line.text))
+        .collect()
+}
+
+/// Everything the program wrote to stderr
+pub fn program_stderr(response: &godbolt::CompilationResult) -> String {
+    response
+        .stderr
+        .iter()
+        .map(|line| format!("{}\n", line.text))
+        .collect()
+}
+
+/// The signal that killed the program, if it ran and crashed
+fn crash_signal(response: &godbolt::CompilationResult) -> Option<&'static str> {
+    if response.did_execute == Some(false) {
+        return None;
+    }
+    let output = format!("{}{}", program_stdout(response), program_stderr(response));
+    triage::exit_signal(response.code, &output)
+}
+
+/// Remove `--map` and `--lines=<first>-<last>`, which group the assembly
+/// under its source lines. Returns None without either, otherwise the lines
+/// to keep if only some were asked for.
+pub fn take_source_map(
+    options: &mut Vec<String>,
+) -> Result<Option<Option<(i32, i32)>>, CommandError> {
+    let mut map = None;
+    let mut remaining = Vec::new();
+    for opt in std::mem::take(options) {
+        if opt == "--map" {
+            map = Some(map.flatten());
+        } else if let Some(range) = opt.strip_prefix("--lines=") {
+            map = Some(Some(parse_line_range(range).ok_or_else(|| {
+                CommandError::from(format!(
+                    "Invalid line range '{}', expected something like L12-L20",
+                    range
+                ))
+            })?));
+        } else {
+            remaining.push(opt);
+        }
+    }
+
+    *options = remaining;
+    Ok(map)
+}
+
+/// Parse `L12-L20`, `12-20` or a single `L12`
+pub fn parse_line_range(range: &str) -> Option<(i32, i32)> {
+    let line = |line: &str| -> Option<i32> {
+        let line = line.strip_prefix(['L', 'l']).unwrap_or(line);
+        line.parse().ok().filter(|line| *line > 0)
+    };
+    let (first, last) = match range.split_once('-') {
+        Some((first, last)) => (line(first)?, line(last)?),
+        None => (line(range)?, line(range)?),
+    };
+    (first <= last).then_some((first, last))
+}
+
+/// Remove the flags that change how assembly is filtered and displayed
+/// (`--att`, `--binary`, ...) from the compiler options, returning them in
+/// the order given
+pub fn take_asm_flags(options: &mut Vec<String>) -> Vec<String> {
+    let (flags, remaining) = std::mem::take(options)
+        .into_iter()
+        .partition(|opt| is_asm_flag(opt));
+    *options = remaining;
+    flags
+}
+
+/// Apply assembly flags in order, so later ones override a guild's defaults
+pub fn apply_asm_flags(filters: &mut CompilationFilters, flags: &[String]) {
+    for flag in flags {
+        apply_asm_flag(filters, flag);
+    }
+}
+
+/// Whether `flag` changes how assembly is filtered and displayed
+pub fn is_asm_flag(flag: &str) -> bool {
+    apply_asm_flag(&mut CompilationFilters::default(), flag)
+}
+
+/// Apply one assembly flag, returning false if `flag` isn't one. Every flag
+/// has an opposite so that a guild's default can be turned back off.
+fn apply_asm_flag(filters: &mut CompilationFilters, flag: &str) -> bool {
+    let (filter, value) = match flag {
+        "--att" => (&mut filters.intel, false),
+        "--intel" => (&mut filters.intel, true),
+        "--no-demangle" => (&mut filters.demangle, false),
+        "--demangle" => (&mut filters.demangle, true),
+        "--keep-directives" => (&mut filters.directives, false),
+        "--filter-directives" => (&mut filters.directives, true),
+        "--keep-comments" => (&mut filters.comment_only, false),
+        "--filter-comments" => (&mut filters.comment_only, true),
+        "--keep-labels" => (&mut filters.labels, false),
+        "--filter-labels" => (&mut filters.labels, true),
+        "--binary" => (&mut filters.binary, true),
+        "--no-binary" => (&mut filters.binary, false),
+        "--lib-functions" => (&mut filters.library_code, false),
+        "--no-lib-functions" => (&mut filters.library_code, true),
+        _ => return false,
+    };
+    *filter = Some(value);
+    true
+}
+
+/// Remove a `--pass=<name>` option choosing the GCC dump pass, returning the
+/// pass name
+fn take_gcc_pass(options: &mut Vec<String>) -> Option<String> {
+    let index = options.iter().position(|opt| opt.starts_with("--pass="))?;
+    let option = options.remove(index);
+    Some(option["--pass=".len()..].to_string())
+}
+
+/// The pass with the given name among those a GCC dump response lists, in
+/// the form Compiler Explorer expects it back
+fn find_gcc_pass(
+    response: &godbolt::CompilationResult,
+    name: &str,
+) -> Result<serde_json::Value, CommandError> {
+    let passes = response
+        .extra
+        .get("gccDumpOutput")
+        .and_then(|dump| dump.get("all"))
+        .and_then(|all| all.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let pass_name = |pass: &serde_json::Value| {
+        pass.get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let found = passes.iter().find(|pass| {
+        let pass_name = pass_name(pass);
+        pass_name.eq_ignore_ascii_case(name)
+            || pass_name
+                .split_whitespace()
+                .next()
+                .is_some_and(|short| short.eq_ignore_ascii_case(name))
+    });
+    found.cloned().ok_or_else(|| {
+        let names: Vec<String> = passes.iter().map(pass_name).collect();
+        CommandError::from(format!(
+            "Unknown GCC pass '{}'.\n\nAvailable passes: {}",
+            name,
+            summarize_names(names.iter().map(String::as_str))
+        ))
+    })
+}
+
+/// Compiler options the crate doesn't model that a dump needs
+fn build_dump_extra_options(
+    kind: DumpKind,
+    pass: Option<serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut extra = serde_json::Map::new();
+    match kind {
+        DumpKind::LlvmIr => {
+            extra.insert(
+                String::from("produceIr"),
+                serde_json::json!({
+                    "filterDebugInfo": true,
+                    "filterIRMetadata": true,
+                    "filterAttributes": true,
+                    "filterComments": true,
+                    "noDiscardValueNames": true,
+                    "demangle": true,
+                }),
+            );
+        }
+        DumpKind::GccDump => {
+            let mut dump = serde_json::json!({
+                "opened": true,
+                "treeDump": true,
+                "rtlDump": true,
+                "ipaDump": true,
+            });
+            if let Some(pass) = pass {
+                dump["pass"] = pass;
+            }
+            extra.insert(String::from("produceGccDump"), dump);
+        }
+        DumpKind::ClangAst | DumpKind::OptRemarks => {}
+    }
+    extra
+}
+
+/// The requested output from a response, or None if the compiler doesn't
+/// produce it. Without a pass, a GCC dump lists the passes to choose from.
+pub fn dump_output(
+    response: &godbolt::CompilationResult,
+    kind: DumpKind,
+    pass_selected: bool,
+) -> Option<String> {
+    let lines = |value: &serde_json::Value| -> Option<String> {
+        match value {
+            serde_json::Value::String(text) => Some(text.clone()),
+            serde_json::Value::Array(lines) => Some(
+                lines
+                    .iter()
+                    .filter_map(|line| line.get("text").and_then(|text| text.as_str()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            _ => None,
+        }
+    };
+
+    match kind {
+        DumpKind::LlvmIr => lines(response.extra.get("irOutput")?.get("asm")?),
+        DumpKind::ClangAst => lines(response.ast_output.as_ref()?),
+        DumpKind::OptRemarks => {
+            let remarks = response.opt_output.as_ref()?.as_array()?;
+            Some(
+                remarks
+                    .iter()
+                    .map(format_remark)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        }
+        DumpKind::GccDump => {
+            let dump = response.extra.get("gccDumpOutput")?;
+            if pass_selected {
+                return dump
+                    .get("currentPassOutput")
+                    .and_then(|output| output.as_str())
+                    .map(str::to_string);
+            }
+            let passes = dump.get("all")?.as_array()?;
+            let names: Vec<&str> = passes
+                .iter()
+                .filter_map(|pass| pass.get("name").and_then(|name| name.as_str()))
+                .collect();
+            Some(format!(
+                "Choose a pass with --pass=<name>:\n{}",
+                names.join("\n")
+            ))
+        }
+    }
+}
+
+/// One optimization remark as `line:column Kind Pass: message`
+fn format_remark(remark: &serde_json::Value) -> String {
+    let field = |name: &str| {
+        remark
+            .get(name)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+    };
+    let location = remark.get("DebugLoc");
+    let position = |name: &str| {
+        location
+            .and_then(|loc| loc.get(name))
+            .and_then(|value| value.as_i64())
+            .unwrap_or(0)
+    };
+
+    // Older responses lack the display string, which is the arguments joined
+    let message = match remark.get("displayString").and_then(|s| s.as_str()) {
+        Some(message) => message.to_string(),
+        None => remark
+            .get("Args")
+            .and_then(|args| args.as_array())
+            .map(|args| {
+                args.iter()
+                    .filter_map(|arg| arg.as_object()?.values().next()?.as_str())
+                    .collect::<String>()
+            })
+            .unwrap_or_default(),
+    };
+
+    format!(
+        "{}:{} {} {}: {}",
+        position("Line"),
+        position("Column"),
+        field("optType"),
+        field("Pass"),
+        message
+    )
+}
+
+/// Build request options for the outputs in `DumpKind`. Assembly is still
+/// produced since the dumps come out of the same compilation.
+fn build_dump_options(request: &ParserResult, kind: DumpKind) -> RequestOptions {
+    let mut options = build_asm_options(request);
+    options.compiler_options.produce_pp = None;
+    options.compiler_options.produce_ast = (kind == DumpKind::ClangAst).then_some(true);
+    options.compiler_options.produce_opt_info = (kind == DumpKind::OptRemarks).then_some(true);
+    options
+}
+
+/// Build request options for code execution
+fn build_execute_options(request: &ParserResult) -> RequestOptions {
+    RequestOptions {
+        user_arguments: request.options.join(" "),
+        compiler_options: CompilerOptions {
+            skip_asm: true,
+            executor_request: true,
+            ..Default::default()
+        },
+        execute_parameters: godbolt::ExecuteParameters {
+            args: request.args.clone(),
+            stdin: request.stdin.clone(),
+        },
+        filters: CompilationFilters {
+            comment_only: Some(true),
+            demangle: Some(true),
+            directives: Some(true),
+            execute: Some(true),
+            intel: Some(true),
+            labels: Some(true),
+            trim: Some(true),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Build request options for a compile-only check
+fn build_check_options(request: &ParserResult) -> RequestOptions {
+    RequestOptions {
+        user_arguments: request.options.join(" "),
+        compiler_options: CompilerOptions {
+            skip_asm: true,
+            executor_request: false,
+            ..Default::default()
+        },
+        execute_parameters: Default::default(),
+        filters: CompilationFilters {
+            comment_only: Some(true),
+            demangle: Some(true),
+            directives: Some(true),
+            execute: Some(false),
+            intel: Some(true),
+            labels: Some(true),
+            trim: Some(true),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Build request options for assembly output
+fn build_asm_options(request: &ParserResult) -> RequestOptions {
+    // Only request preprocessor output when the user explicitly asks for it via
+    // `-E`. When present, we show the cleaner (header-filtered, formatted)
+    // preprocessor source instead of the assembly.
+    let produce_pp = request
+        .options
+        .iter()
+        .any(|opt| opt == "-E")
+        .then_some(PpOptions {
+            filter_headers: true,
+            clang_format: true,
+        });
+
+    RequestOptions {
+        user_arguments: request.options.join(" "),
+        compiler_options: CompilerOptions {
+            skip_asm: false,
+            executor_request: false,
+            produce_pp,
+            ..Default::default()
+        },
+        execute_parameters: Default::default(),
+        filters: CompilationFilters {
+            comment_only: Some(true),
+            demangle: Some(true),
+            directives: Some(true),
+            execute: Some(false),
+            intel: Some(true),
+            labels: Some(true),
+            trim: Some(true),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::take_library_specs;
+
+    #[test]
+    fn takes_library_specs_out_of_options() {
+        let mut options = vec![
+            String::from("-O2"),
+            String::from("-lib"),
+            String::from("fmt:trunk"),
+            String::from("-Wall"),
+            String::from("-lib"),
+            String::from("ctre:dev"),
+        ];
+
+        let specs = take_library_specs(&mut options).unwrap();
+        assert_eq!(specs, vec!["fmt:trunk", "ctre:dev"]);
+        assert_eq!(options, vec!["-O2", "-Wall"]);
+    }
+
+    #[test]
+    fn takes_concatenated_library_specs() {
+        let mut options = vec![
+            String::from("-libboost"),
+            String::from("-O2"),
+            String::from("-libfmt:trunk"),
+        ];
+
+        let specs = take_library_specs(&mut options).unwrap();
+        assert_eq!(specs, vec!["boost", "fmt:trunk"]);
+        assert_eq!(options, vec!["-O2"]);
+    }
+
+    #[test]
+    fn takes_library_specs_without_versions() {
+        let mut options = vec![String::from("-lib"), String::from("boost")];
+
+        let specs = take_library_specs(&mut options).unwrap();
+        assert_eq!(specs, vec!["boost"]);
+        assert!(options.is_empty());
+    }
+
+    #[test]
+    fn leaves_options_without_libraries_alone() {
+        let mut options = vec![String::from("-O2"), String::from("-Wall")];
+
+        let specs = take_library_specs(&mut options).unwrap();
+        assert!(specs.is_empty());
+        assert_eq!(options, vec!["-O2", "-Wall"]);
+    }
+
+    #[test]
+    fn rejects_dangling_lib_flag() {
+        let mut options = vec![String::from("-O2"), String::from("-lib")];
+        assert!(take_library_specs(&mut options).is_err());
+    }
+}
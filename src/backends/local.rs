@@ -0,0 +1,362 @@
+use std::any::Any;
+use std::path::{Path, PathBuf};
+
+use serenity::async_trait;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use crate::backends::backend::{
+    boilerplate_generation, matches_filter, CompilerBackend, ResolvedTarget,
+};
+use crate::managers::compilation::{CompilationDetails, CompilationResult};
+use crate::utls::diagnostics;
+use crate::utls::discordhelpers::embeds::{self, EmbedOptions};
+use crate::utls::parser::ParserResult;
+use crate::utls::sandbox::{self, ProcessOutput, SandboxLimits, SandboxUsers};
+use crate::utls::triage;
+
+/// A toolchain the local backend knows how to drive
+struct Toolchain {
+    language: &'static str,
+    /// Executable looked up on PATH, doubles as the compiler id
+    compiler: &'static str,
+    source: &'static str,
+    /// Arguments placed before the user's compiler options
+    prefix: &'static [&'static str],
+    /// Arguments placed after the user's compiler options to produce `./prog`,
+    /// or None for interpreters that run the source directly
+    build: Option<&'static [&'static str]>,
+}
+
+const TOOLCHAINS: &[Toolchain] = &[
+    Toolchain {
+        language: "c++",
+        compiler: "g++",
+        source: "main.cpp",
+        prefix: &[],
+        build: Some(&["main.cpp", "-o", "prog"]),
+    },
+    Toolchain {
+        language: "c++",
+        compiler: "clang++",
+        source: "main.cpp",
+        prefix: &[],
+        build: Some(&["main.cpp", "-o", "prog"]),
+    },
+    Toolchain {
+        language: "c",
+        compiler: "gcc",
+        source: "main.c",
+        prefix: &[],
+        build: Some(&["main.c", "-o", "prog"]),
+    },
+    Toolchain {
+        language: "c",
+        compiler: "clang",
+        source: "main.c",
+        prefix: &[],
+        build: Some(&["main.c", "-o", "prog"]),
+    },
+    Toolchain {
+        language: "rust",
+        compiler: "rustc",
+        source: "main.rs",
+        prefix: &[],
+        build: Some(&["main.rs", "-o", "prog"]),
+    },
+    Toolchain {
+        language: "go",
+        compiler: "go",
+        source: "main.go",
+        prefix: &["build"],
+        build: Some(&["-o", "prog", "main.go"]),
+    },
+    Toolchain {
+        language: "python",
+        compiler: "python3",
+        source: "main.py",
+        prefix: &[],
+        build: None,
+    },
+    Toolchain {
+        language: "javascript",
+        compiler: "node",
+        source: "main.js",
+        prefix: &[],
+        build: None,
+    },
+    Toolchain {
+        language: "ruby",
+        compiler: "ruby",
+        source: "main.rb",
+        prefix: &[],
+        build: None,
+    },
+    Toolchain {
+        language: "bash script",
+        compiler: "bash",
+        source: "main.sh",
+        prefix: &[],
+        build: None,
+    },
+];
+
+/// Outcome of a local run, ready to be turned into an embed
+pub struct LocalResponse {
+    /// Build step, absent for interpreted languages
+    pub compile: Option<ProcessOutput>,
+    /// Program run, absent for compile-only requests or failed builds
+    pub run: Option<ProcessOutput>,
+}
+
+impl LocalResponse {
+    pub fn success(&self) -> bool {
+        self.compile.as_ref().map(|c| c.success()).unwrap_or(true)
+            && self.run.as_ref().map(|r| r.success()).unwrap_or(true)
+    }
+}
+
+/// Runs toolchains found on the bot host's PATH inside the sandbox from
+/// `utls::sandbox`. Enabled by setting LOCAL_SANDBOX=1 along with the
+/// LOCAL_SANDBOX_UIDS to run programs as; only toolchains that are installed
+/// when the bot starts are offered, and they must live under one of the
+/// sandbox's `mounts` to run.
+pub struct LocalBackend {
+    toolchains: Vec<&'static Toolchain>,
+    limits: SandboxLimits,
+    users: SandboxUsers,
+    mounts: Vec<PathBuf>,
+}
+
+impl LocalBackend {
+    pub fn new(limits: SandboxLimits, users: SandboxUsers, mounts: Vec<PathBuf>) -> Self {
+        let toolchains = TOOLCHAINS
+            .iter()
+            .filter(|toolchain| find_on_path(toolchain.compiler))
+            .collect();
+
+        LocalBackend {
+            toolchains,
+            limits,
+            users,
+            mounts,
+        }
+    }
+
+    pub fn compiler_names(&self) -> Vec<&'static str> {
+        self.toolchains.iter().map(|t| t.compiler).collect()
+    }
+
+    fn find_toolchain(&self, target: &str) -> Option<&'static Toolchain> {
+        self.toolchains
+            .iter()
+            .find(|t| t.compiler == target)
+            .or_else(|| self.toolchains.iter().find(|t| t.language == target))
+            .copied()
+    }
+
+    /// Build (if needed) and optionally run the request in a fresh temporary
+    /// directory that is removed once we're done.
+    async fn run_stages(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        execute: bool,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        let toolchain = self.find_toolchain(&request.target).ok_or_else(|| {
+            CommandError::from(format!(
+                "Unable to find local toolchain for target '{}'.",
+                request.target
+            ))
+        })?;
+
+        let code = if boilerplate {
+            boilerplate_generation(toolchain.language, &request.code)
+        } else {
+            request.code.to_owned()
+        };
+
+        let user = self.users.acquire().await;
+        let workdir = tempfile::tempdir()
+            .map_err(|e| CommandError::from(format!("Unable to create sandbox: {}", e)))?;
+        tokio::fs::write(workdir.path().join(toolchain.source), &code)
+            .await
+            .and_then(|_| sandbox::hand_over(workdir.path(), user.uid))
+            .map_err(|e| CommandError::from(format!("Unable to create sandbox: {}", e)))?;
+
+        let response = match toolchain.build {
+            Some(build) => {
+                let args = toolchain_args(toolchain.prefix, &request.options, build);
+                let compile = self
+                    .spawn(toolchain.compiler, &args, workdir.path(), "", user.uid)
+                    .await?;
+
+                let run = if execute && compile.success() {
+                    Some(
+                        self.spawn(
+                            "./prog",
+                            &request.args,
+                            workdir.path(),
+                            &request.stdin,
+                            user.uid,
+                        )
+                        .await?,
+                    )
+                } else {
+                    None
+                };
+
+                LocalResponse {
+                    compile: Some(compile),
+                    run,
+                }
+            }
+            // Interpreters have no separate build step to stop after
+            None => {
+                let mut args = toolchain_args(toolchain.prefix, &request.options, &[]);
+                args.push(toolchain.source.to_string());
+                args.extend(request.args.iter().cloned());
+                let run = self
+                    .spawn(
+                        toolchain.compiler,
+                        &args,
+                        workdir.path(),
+                        &request.stdin,
+                        user.uid,
+                    )
+                    .await?;
+
+                LocalResponse {
+                    compile: None,
+                    run: Some(run),
+                }
+            }
+        };
+
+        let mut details = CompilationDetails {
+            language: toolchain.language.to_string(),
+            compiler: toolchain.compiler.to_string(),
+            godbolt_base64: None,
+            success: response.success(),
+            executed: response.run.is_some(),
+            exec_time: None,
+            assembly: None,
+            stdout: response.run.as_ref().map(|run| run.stdout.clone()),
+            signal: response
+                .run
+                .as_ref()
+                .and_then(|run| run.signal)
+                .and_then(triage::signal_number),
+            compiler_log: response.compile.as_ref().and_then(|compile| {
+                diagnostics::full_log(&format!("{}\n{}", compile.stdout, compile.stderr))
+            }),
+            counts: None,
+            service: Some(self.display_name()),
+        };
+
+        let mut embed_options = EmbedOptions::new(false, false);
+        embed_options.sources = diagnostics::sources(request, &code, &[toolchain.source]);
+        embed_options.output = request.output;
+        let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
+
+        Ok(CompilationResult { details, embed })
+    }
+
+    async fn spawn(
+        &self,
+        program: &str,
+        args: &[String],
+        dir: &Path,
+        stdin: &str,
+        uid: u32,
+    ) -> Result<ProcessOutput, CommandError> {
+        sandbox::run(program, args, dir, stdin, &self.limits, uid, &self.mounts)
+            .await
+            .map_err(|e| CommandError::from(format!("Local sandbox failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl CompilerBackend for LocalBackend {
+    fn id(&self) -> &'static str {
+        "local"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "local"
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        self.find_toolchain(target).map(|toolchain| ResolvedTarget {
+            language: toolchain.language.to_string(),
+            compiler: toolchain.compiler.to_string(),
+        })
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.run_stages(request, author, false, false).await
+    }
+
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        self.run_stages(request, author, true, boilerplate).await
+    }
+
+    fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = Vec::new();
+        for toolchain in &self.toolchains {
+            if !languages.iter().any(|lang| lang == toolchain.language) {
+                languages.push(toolchain.language.to_string());
+            }
+        }
+        languages
+    }
+
+    fn list_compilers(
+        &self,
+        language: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        Ok(self
+            .toolchains
+            .iter()
+            .filter(|t| t.language == language)
+            .filter(|t| filter.is_none_or(|f| matches_filter(t.compiler, t.compiler, f)))
+            .map(|t| format!("{} -> **{}**", t.compiler, t.compiler))
+            .collect())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn toolchain_args(prefix: &[&str], options: &[String], suffix: &[&str]) -> Vec<String> {
+    prefix
+        .iter()
+        .map(|arg| arg.to_string())
+        .chain(options.iter().cloned())
+        .chain(suffix.iter().map(|arg| arg.to_string()))
+        .collect()
+}
+
+/// Whether an executable file with this name exists in a PATH directory
+fn find_on_path(program: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path).any(|dir| {
+        std::fs::metadata(dir.join(program))
+            .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    })
+}
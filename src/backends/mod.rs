@@ -0,0 +1,7 @@
+pub mod backend;
+pub mod godbolt;
+pub mod local;
+pub mod piston;
+pub mod routing;
+pub mod sourcepawn;
+pub mod wandbox;
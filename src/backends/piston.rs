@@ -0,0 +1,171 @@
+use std::any::Any;
+
+use serenity::async_trait;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use crate::apis::piston::PistonService;
+use crate::backends::backend::{
+    boilerplate_generation, compute_similarity, matches_filter, BackendUnavailable,
+    CompilerBackend, ResolvedTarget,
+};
+use crate::managers::compilation::{CompilationDetails, CompilationResult};
+use crate::utls::diagnostics;
+use crate::utls::discordhelpers::embeds::{self, EmbedOptions};
+use crate::utls::parser::ParserResult;
+use crate::utls::triage;
+
+/// Self-hosted Piston backend, enabled by PISTON_API_URL. Like WandBox,
+/// Piston always runs the program, so compile-only requests execute as well.
+pub struct PistonBackend {
+    pub service: PistonService,
+}
+
+impl PistonBackend {
+    pub fn new(service: PistonService) -> Self {
+        PistonBackend { service }
+    }
+}
+
+#[async_trait]
+impl CompilerBackend for PistonBackend {
+    fn id(&self) -> &'static str {
+        "piston"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "piston"
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        self.service.resolve(target).map(|runtime| ResolvedTarget {
+            language: runtime.language.clone(),
+            compiler: runtime.id(),
+        })
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.execute(request, author, false).await
+    }
+
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        let runtime = self.service.resolve(&request.target).ok_or_else(|| {
+            CommandError::from(format!(
+                "Unable to find language or compiler for target '{}'.",
+                request.target
+            ))
+        })?;
+
+        if !request.options.is_empty() {
+            return Err(CommandError::from(
+                "Compiler options are not supported for Piston targets.",
+            ));
+        }
+
+        let code = if boilerplate {
+            boilerplate_generation(&runtime.language, &request.code)
+        } else {
+            request.code.to_owned()
+        };
+
+        let response = self
+            .service
+            .execute(runtime, &code, &request.stdin, &request.args)
+            .await
+            .map_err(|e| {
+                let message = format!("Piston request failed: {}", e);
+                if e.is_connect()
+                    || e.is_timeout()
+                    || e.status().is_some_and(|s| s.is_server_error())
+                {
+                    CommandError::from(BackendUnavailable(message))
+                } else {
+                    CommandError::from(message)
+                }
+            })?;
+
+        let mut details = CompilationDetails {
+            language: runtime.language.clone(),
+            compiler: runtime.id(),
+            godbolt_base64: None,
+            success: response.success(),
+            executed: true,
+            exec_time: None,
+            assembly: None,
+            stdout: Some(response.run.stdout.clone()),
+            signal: response.run.signal.as_deref().and_then(triage::signal_name),
+            compiler_log: response
+                .compile
+                .as_ref()
+                .and_then(|compile| diagnostics::full_log(&compile.output)),
+            counts: None,
+            service: Some(self.display_name()),
+        };
+
+        let mut embed_options = EmbedOptions::new(false, false);
+        // Piston saves unnamed files as file0.code, which some languages' run
+        // scripts give an extension
+        embed_options.sources = diagnostics::sources(request, &code, &["file0"]);
+        embed_options.output = request.output;
+        let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
+
+        Ok(CompilationResult { details, embed })
+    }
+
+    fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = Vec::new();
+        for runtime in self.service.get_runtimes() {
+            if !languages.contains(&runtime.language) {
+                languages.push(runtime.language.clone());
+            }
+        }
+        languages
+    }
+
+    fn list_compilers(
+        &self,
+        language: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        let mut results: Vec<(f64, String)> = Vec::new();
+
+        for runtime in self.service.get_runtimes() {
+            if !runtime.is_language(language) {
+                continue;
+            }
+
+            let id = runtime.id();
+            let name = format!("{} {}", runtime.language, runtime.version);
+            let display = format!("{} -> **{}**", name, id);
+
+            if let Some(filter_str) = filter {
+                if !matches_filter(&id, &name, filter_str) {
+                    continue;
+                }
+                let similarity = compute_similarity(&id, &name, filter_str);
+                results.push((similarity, display));
+            } else {
+                results.push((0.0, display));
+            }
+        }
+
+        if filter.is_some() {
+            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        }
+
+        Ok(results.into_iter().map(|(_, s)| s).collect())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,131 @@
+//! Operator-editable routing rules for `CompilationManager`.
+//!
+//! Rules are read from the JSON file named by ROUTING_CONFIG (see
+//! routing.example.json) and can be reloaded at runtime with `;routing reload`,
+//! so upstream breakage can be worked around without a new build. Without a
+//! file the built-in defaults below are used.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RoutingConfig {
+    /// Backend id to try first for a language, e.g. "scala" -> "wandbox"
+    pub preferred_backends: HashMap<String, String>,
+    /// Compiler ids or names each backend must never use, keyed by backend id
+    pub denied_compilers: HashMap<String, Vec<String>>,
+    /// Languages hidden from a backend entirely, keyed by backend id
+    pub denied_languages: HashMap<String, Vec<String>>,
+    /// Targets rewritten before resolution, e.g. "c++23" -> "g132"
+    pub aliases: HashMap<String, String>,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        let preferred_backends = ["scala", "nim", "typescript", "javascript"]
+            .iter()
+            .map(|lang| (lang.to_string(), String::from("wandbox")))
+            .collect();
+
+        // WandBox's "cpp" is the C preprocessor, not C++
+        let mut denied_compilers = HashMap::new();
+        denied_compilers.insert(
+            String::from("wandbox"),
+            vec![String::from("ghc-head"), String::from("go-head")],
+        );
+        let mut denied_languages = HashMap::new();
+        denied_languages.insert(String::from("wandbox"), vec![String::from("cpp")]);
+
+        RoutingConfig {
+            preferred_backends,
+            denied_compilers,
+            denied_languages,
+            aliases: HashMap::new(),
+        }
+    }
+}
+
+impl RoutingConfig {
+    /// The backend a target should be routed to first, if any
+    pub fn preferred_backend(&self, target: &str) -> Option<&str> {
+        self.preferred_backends.get(target).map(|id| id.as_str())
+    }
+
+    pub fn is_compiler_denied(&self, backend: &str, compiler: &str) -> bool {
+        self.denied_compilers
+            .get(backend)
+            .is_some_and(|denied| denied.iter().any(|c| c == compiler))
+    }
+
+    pub fn is_language_denied(&self, backend: &str, language: &str) -> bool {
+        self.denied_languages
+            .get(backend)
+            .is_some_and(|denied| denied.iter().any(|l| l == language))
+    }
+
+    /// The target to resolve in place of `target`
+    pub fn alias<'a>(&'a self, target: &'a str) -> &'a str {
+        self.aliases
+            .get(target)
+            .map_or(target, |alias| alias.as_str())
+    }
+}
+
+/// The active routing rules, shared by the manager and any backend that
+/// filters its catalog with them
+pub struct Routing {
+    path: Option<PathBuf>,
+    config: RwLock<Arc<RoutingConfig>>,
+}
+
+impl Routing {
+    /// Loads the file named by ROUTING_CONFIG, falling back to the defaults
+    /// if it is unset or unreadable
+    pub fn from_env() -> Self {
+        let path = std::env::var("ROUTING_CONFIG").ok().map(PathBuf::from);
+        let routing = Routing {
+            path,
+            config: RwLock::new(Arc::new(RoutingConfig::default())),
+        };
+
+        if routing.path.is_some() {
+            if let Err(e) = routing.reload() {
+                error!("Unable to load routing config, using defaults: {}", e);
+            }
+        }
+        routing
+    }
+
+    /// Rules with the given config and no file to reload from
+    #[cfg(test)]
+    pub fn with_config(config: RoutingConfig) -> Self {
+        Routing {
+            path: None,
+            config: RwLock::new(Arc::new(config)),
+        }
+    }
+
+    pub fn get(&self) -> Arc<RoutingConfig> {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Re-read the config file. The current rules stay active on failure.
+    pub fn reload(&self) -> Result<(), String> {
+        let path = self
+            .path
+            .as_ref()
+            .ok_or_else(|| String::from("ROUTING_CONFIG is not set"))?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read '{}': {}", path.display(), e))?;
+        let config: RoutingConfig = serde_json::from_str(&contents)
+            .map_err(|e| format!("Unable to parse '{}': {}", path.display(), e))?;
+
+        *self.config.write().unwrap() = Arc::new(config);
+        info!("Routing config loaded from '{}'", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::any::Any;
+
+use serenity::async_trait;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use crate::apis::sourcepawn::SourcePawnService;
+use crate::backends::backend::{boilerplate_generation, CompilerBackend, ResolvedTarget};
+use crate::managers::compilation::{CompilationDetails, CompilationResult};
+use crate::utls::diagnostics;
+use crate::utls::discordhelpers::embeds::{self, EmbedOptions};
+use crate::utls::parser::ParserResult;
+
+/// Self-hosted SourcePawn backend, enabled by SOURCEPAWN_API_URL
+pub struct SourcePawnBackend {
+    pub service: SourcePawnService,
+}
+
+impl SourcePawnBackend {
+    pub fn new(service: SourcePawnService) -> Self {
+        SourcePawnBackend { service }
+    }
+
+    /// Compile using the self-hosted SourcePawn service, optionally running
+    /// the program or disassembling it to pcode.
+    async fn compile_with_stages(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        execute: bool,
+        asm: bool,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        let code = if boilerplate {
+            boilerplate_generation("sourcepawn", &request.code)
+        } else {
+            request.code.to_owned()
+        };
+
+        let response = self
+            .service
+            .compile(&code, execute, asm)
+            .await
+            .map_err(|e| CommandError::from(format!("SourcePawn request failed: {}", e)))?;
+
+        let success = response.compile.success
+            && response.run.as_ref().map(|run| run.success).unwrap_or(true);
+        let mut details = CompilationDetails {
+            language: String::from("sourcepawn"),
+            compiler: self.service.compiler_name(),
+            godbolt_base64: None,
+            success,
+            executed: execute,
+            exec_time: None,
+            assembly: response
+                .asm
+                .as_ref()
+                .filter(|_| asm)
+                .map(|disassembly| disassembly.stdout.clone()),
+            stdout: response.run.as_ref().map(|run| run.stdout.clone()),
+            signal: None,
+            compiler_log: diagnostics::full_log(&format!(
+                "{}\n{}",
+                response.compile.stdout, response.compile.stderr
+            )),
+            counts: None,
+            service: None,
+        };
+
+        let mut embed_options = EmbedOptions::new(asm, false);
+        embed_options.sources = diagnostics::sources(request, &code, &[]);
+        embed_options.output = request.output;
+        let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
+
+        Ok(CompilationResult { details, embed })
+    }
+}
+
+#[async_trait]
+impl CompilerBackend for SourcePawnBackend {
+    fn id(&self) -> &'static str {
+        "sourcepawn"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "sourcepawn"
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        (target == "sourcepawn").then(|| ResolvedTarget {
+            language: String::from("sourcepawn"),
+            compiler: self.service.compiler_name(),
+        })
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.compile_with_stages(request, author, false, false, false)
+            .await
+    }
+
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        self.compile_with_stages(request, author, true, false, boilerplate)
+            .await
+    }
+
+    async fn assembly(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.compile_with_stages(request, author, false, true, false)
+            .await
+    }
+
+    fn produces_assembly(&self) -> bool {
+        true
+    }
+
+    fn languages(&self) -> Vec<String> {
+        vec![String::from("sourcepawn")]
+    }
+
+    fn list_compilers(
+        &self,
+        _language: &str,
+        _filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        Ok(vec![format!(
+            "{} -> **sourcepawn**",
+            self.service.compiler_name()
+        )])
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,253 @@
+use std::any::Any;
+use std::sync::Arc;
+
+use serenity::async_trait;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use wandbox::WandboxError;
+
+use crate::apis::wandbox::{WandboxLanguage, WandboxService};
+use crate::backends::backend::{
+    boilerplate_generation, matches_filter, BackendUnavailable, CompilerBackend, ResolvedTarget,
+    ServiceSlot,
+};
+use crate::backends::routing::{Routing, RoutingConfig};
+use crate::managers::compilation::{CompilationDetails, CompilationResult};
+use crate::utls::diagnostics;
+use crate::utls::discordhelpers::embeds::{self, EmbedOptions};
+use crate::utls::parser::{shortname_to_qualified, ParserResult};
+use crate::utls::triage;
+
+/// WandBox (wandbox.org) backend. WandBox cannot skip the run step, so
+/// compile-only requests are executed as well. The catalog is empty until
+/// the first successful `refresh`.
+pub struct WandboxBackend {
+    service: ServiceSlot<WandboxService>,
+    /// Denied languages and compilers are hidden from the catalog
+    routing: Arc<Routing>,
+}
+
+impl WandboxBackend {
+    pub fn new(routing: Arc<Routing>) -> Self {
+        WandboxBackend {
+            service: ServiceSlot::default(),
+            routing,
+        }
+    }
+
+    /// Cached languages, minus any the routing config denies. Borrows from
+    /// the catalog, which is looked at several times per request.
+    fn visible_languages<'a>(
+        &'a self,
+        wandbox: &'a WandboxService,
+        routing: &'a RoutingConfig,
+    ) -> impl Iterator<Item = &'a WandboxLanguage> {
+        wandbox
+            .get_languages()
+            .iter()
+            .filter(move |lang| !routing.is_language_denied(self.id(), &lang.name))
+    }
+
+    /// Names of a language's compilers, minus any the routing config denies
+    fn visible_compilers<'a>(
+        &'a self,
+        language: &'a WandboxLanguage,
+        routing: &'a RoutingConfig,
+    ) -> impl Iterator<Item = &'a str> {
+        language
+            .compilers
+            .iter()
+            .map(|compiler| compiler.name.as_str())
+            .filter(move |name| !routing.is_compiler_denied(self.id(), name))
+    }
+}
+
+#[async_trait]
+impl CompilerBackend for WandboxBackend {
+    fn id(&self) -> &'static str {
+        "wandbox"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "wandbox.org"
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        let wandbox = self.service.get()?;
+        let routing = self.routing.get();
+
+        for language in self.visible_languages(&wandbox, &routing) {
+            let mut compilers = self.visible_compilers(language, &routing);
+            // A language name resolves to its newest compiler
+            let compiler = if target == language.name {
+                Some(compilers.next().unwrap_or_default())
+            } else {
+                compilers.find(|compiler| *compiler == target)
+            };
+
+            if let Some(compiler) = compiler {
+                return Some(ResolvedTarget {
+                    language: language.name.clone(),
+                    compiler: compiler.to_string(),
+                });
+            }
+        }
+
+        None
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.execute(request, author, false).await
+    }
+
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        // Resolve target to language and compiler
+        let ResolvedTarget { language, compiler } =
+            self.resolve(&request.target).ok_or_else(|| {
+                CommandError::from(format!(
+                    "Unable to find language or compiler for target '{}'.",
+                    request.target
+                ))
+            })?;
+
+        let code = if boilerplate {
+            boilerplate_generation(&language, &request.code)
+        } else {
+            request.code.to_owned()
+        };
+
+        // Build and send compilation request
+        let compilation_request = wandbox::CompilationRequest {
+            compiler: compiler.clone(),
+            code: code.trim().to_string(),
+            stdin: request.stdin.trim().to_string(),
+            compiler_option_raw: request.options.join("\n"),
+            runtime_option_raw: request.args.join("\n"),
+            save: false,
+            ..Default::default()
+        };
+
+        let wandbox = self.service.get().ok_or_else(|| {
+            CommandError::from(BackendUnavailable(String::from(
+                "WandBox is unavailable. This may be due to an outage. Please try again later.",
+            )))
+        })?;
+        let response = wandbox
+            .compile(&compilation_request)
+            .await
+            .map_err(|e| match e {
+                // Transport failures and server errors are WandBox's problem
+                WandboxError::Http(_) | WandboxError::Api { status: 500.., .. } => {
+                    CommandError::from(BackendUnavailable(format!(
+                        "WandBox is unavailable. This may be due to an outage. Please try again later. ({})",
+                        e
+                    )))
+                }
+                _ => CommandError::from(e),
+            })?;
+
+        let mut details = CompilationDetails {
+            language,
+            compiler,
+            godbolt_base64: None,
+            success: response.status == Some(0),
+            executed: true,
+            exec_time: None,
+            assembly: None,
+            stdout: Some(response.program_output.clone()),
+            signal: triage::signal_name(&response.signal),
+            compiler_log: diagnostics::full_log(&response.compiler_message),
+            counts: None,
+            service: Some(self.display_name()),
+        };
+
+        let mut embed_options = EmbedOptions::new(false, false);
+        // WandBox names the file after the language, e.g. prog.cc or prog.rs
+        embed_options.sources = diagnostics::sources(request, code.trim(), &["prog"]);
+        embed_options.output = request.output;
+        let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
+
+        Ok(CompilationResult { details, embed })
+    }
+
+    fn languages(&self) -> Vec<String> {
+        let Some(wandbox) = self.service.get() else {
+            return Vec::new();
+        };
+        let routing = self.routing.get();
+
+        self.visible_languages(&wandbox, &routing)
+            .map(|language| language.name.clone())
+            .collect()
+    }
+
+    fn list_compilers(
+        &self,
+        language: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        let language = shortname_to_qualified(language);
+        let not_found = || {
+            CommandError::from(format!(
+                "Unable to find compilers for target '{}'.",
+                language
+            ))
+        };
+        let wandbox = self.service.get().ok_or_else(not_found)?;
+        let routing = self.routing.get();
+        let language = self
+            .visible_languages(&wandbox, &routing)
+            .find(|lang| lang.name == language)
+            .ok_or_else(not_found)?;
+
+        let mut results: Vec<(f64, String)> = Vec::new();
+
+        for compiler in self.visible_compilers(language, &routing) {
+            if let Some(filter_str) = filter {
+                if !matches_filter(compiler, compiler, filter_str) {
+                    continue;
+                }
+                let similarity = similar_string::compare_similarity(filter_str, compiler);
+                results.push((similarity, compiler.to_string()));
+            } else {
+                results.push((0.0, compiler.to_string()));
+            }
+        }
+
+        if filter.is_some() {
+            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        }
+
+        Ok(results.into_iter().map(|(_, s)| s).collect())
+    }
+
+    fn is_available(&self) -> bool {
+        self.service.is_set()
+    }
+
+    async fn refresh(&self) -> Result<(), CommandError> {
+        let wandbox = WandboxService::new(crate::apis::HTTP_CLIENT.clone())
+            .await
+            .map_err(|e| CommandError::from(e.to_string()))?;
+        info!(
+            "WandBox catalog loaded ({} languages)",
+            wandbox.get_languages().len()
+        );
+        self.service.set(wandbox);
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -16,6 +16,8 @@ use crate::apis::insights::InsightsAPI;
 use crate::apis::quick_link::LinkAPI;
 use crate::managers::command::CommandManager;
 use crate::managers::compilation::CompilationManager;
+use crate::managers::health::HealthManager;
+use crate::managers::settings::SettingsManager;
 use lru_cache::LruCache;
 use serenity::all::{ApplicationId, CommandInteraction, ShardManager};
 use serenity::model::channel::Message;
@@ -34,6 +36,12 @@ impl TypeMapKey for CompilerCache {
     type Value = Arc<RwLock<CompilationManager>>;
 }
 
+/// Health of every upstream service, shared with the CompilationManager
+pub struct HealthCache;
+impl TypeMapKey for HealthCache {
+    type Value = Arc<HealthManager>;
+}
+
 /// Contains our top.gg api client for server count updates
 pub struct DblCache;
 impl TypeMapKey for DblCache {
@@ -52,6 +60,12 @@ impl TypeMapKey for BlocklistCache {
     type Value = Arc<RwLock<Blocklist>>;
 }
 
+/// Per-guild and per-user preferences, changed with /settings
+pub struct SettingsCache;
+impl TypeMapKey for SettingsCache {
+    type Value = Arc<RwLock<SettingsManager>>;
+}
+
 /// Contains the shard manager - used to send global presence updates
 pub struct ShardManagerCache;
 impl TypeMapKey for ShardManagerCache {
@@ -182,8 +196,16 @@ pub async fn fill(
     // Message delete cache
     data.insert::<MessageCache>(Arc::new(Mutex::new(LruCache::new(25))));
 
+    // Stats tracking (server/shard counts, backend failovers)
+    let stats = Arc::new(Mutex::new(StatsManager::new()));
+    data.insert::<StatsManagerCache>(stats.clone());
+
     // Compiler manager
-    data.insert::<CompilerCache>(Arc::new(RwLock::new(CompilationManager::new().await?)));
+    let mut compilation = CompilationManager::new().await?;
+    compilation.attach_stats(stats);
+    let health = compilation.health().clone();
+    data.insert::<HealthCache>(health.clone());
+    data.insert::<CompilerCache>(Arc::new(RwLock::new(compilation)));
     info!("Compilation manager loaded");
 
     // DBL
@@ -196,23 +218,24 @@ pub async fn fill(
     if let Ok(redirect_base) = env::var("QUICK_LINK_URL") {
         if let Ok(request_base) = env::var("QUICK_LINK_POST") {
             info!("Registered quick link api");
-            let link_man = LinkAPI::new(&request_base, &redirect_base);
+            let link_man = LinkAPI::new(&request_base, &redirect_base, health.clone());
             data.insert::<LinkAPICache>(Arc::new(RwLock::new(link_man)));
         }
     }
 
     // Cpp insights
-    let insights = InsightsAPI::new();
+    let insights = InsightsAPI::new(health);
     data.insert::<InsightsAPICache>(Arc::new(insights));
 
-    // Stats tracking (server/shard counts)
-    let stats = StatsManager::new();
-    data.insert::<StatsManagerCache>(Arc::new(Mutex::new(stats)));
-
     // Blocklist
     let blocklist = Blocklist::new();
     data.insert::<BlocklistCache>(Arc::new(RwLock::new(blocklist)));
 
+    // Settings
+    let settings = SettingsManager::from_env()?;
+    data.insert::<SettingsCache>(Arc::new(RwLock::new(settings)));
+    info!("Settings loaded");
+
     // Commands
     let commands = CommandManager::new();
     data.insert::<CommandCache>(Arc::new(RwLock::new(commands)));
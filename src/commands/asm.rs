@@ -1,6 +1,6 @@
 use std::fmt::Write as _;
 
-use serenity::all::{CreateActionRow, CreateButton, CreateMessage};
+use serenity::all::{CreateActionRow, CreateAttachment, CreateButton, CreateMessage};
 use serenity::builder::CreateEmbed;
 use serenity::client::Context;
 use serenity::framework::standard::CommandError;
@@ -8,8 +8,13 @@ use serenity::framework::standard::{macros::command, Args, CommandResult};
 use serenity::model::channel::{Message, ReactionType};
 use serenity::model::user::User;
 
-use crate::cache::{CompilerCache, ConfigCache, LinkAPICache, MessageCache, MessageCacheEntry};
+use crate::backends::godbolt::parse_line_range;
+use crate::cache::{
+    CompilerCache, ConfigCache, LinkAPICache, MessageCache, MessageCacheEntry, SettingsCache,
+};
+use crate::commands::compile::parse_request;
 use crate::managers::compilation::{CompilationDetails, CompilationResult};
+use crate::utls::discordhelpers::LoadingStatus;
 use crate::utls::{discordhelpers, parser};
 
 #[command]
@@ -21,6 +26,10 @@ pub async fn asm(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
 
     // Build message with optional godbolt link button
     let mut new_msg = discordhelpers::reply_to(msg, CreateMessage::new().embed(result.embed));
+    new_msg = discordhelpers::attach_compiler_log(new_msg, &result.details);
+    if let Some(graph) = result.graph {
+        new_msg = new_msg.add_file(CreateAttachment::bytes(graph.into_bytes(), "cfg.dot"));
+    }
     if let Some(b64) = &result.details.godbolt_base64 {
         if let Some(link_cache) = data.get::<LinkAPICache>() {
             let long_url = format!("https://godbolt.org/clientstate/{}", b64);
@@ -49,6 +58,8 @@ pub async fn asm(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
 pub struct HandleRequestResult {
     pub embed: CreateEmbed,
     pub details: CompilationDetails,
+    /// Control-flow graph as Graphviz DOT, when asked for with `--cfg`
+    pub graph: Option<String>,
 }
 
 /// Parse message, compile to assembly, and return result ready for display.
@@ -68,44 +79,88 @@ pub async fn handle_request(
 
     // Handle file attachments (may do HTTP request)
     let content = append_attachment_code(content, &msg.attachments).await?;
+    let content = split_line_range(&content);
 
     // Parse the compilation request
-    let parse_result = parser::get_components(
-        &content,
-        author,
-        Some(&compilation_manager),
-        &msg.referenced_message,
-        false,
-    )
-    .await?;
+    let mut parse_result = parse_request(ctx, msg, author, &content).await?;
 
-    // Show loading indicator
-    if msg
-        .react(&ctx.http, loading_reaction.clone())
+    // The guild's default flags go first so the user's own can override them
+    let settings = ctx
+        .data
+        .read()
         .await
-        .is_err()
-    {
-        return Err(CommandError::from(
-            "Unable to react to message. Am I missing permissions to react or use external emoji?",
-        ));
-    }
+        .get::<SettingsCache>()
+        .unwrap()
+        .clone();
+    let defaults = settings
+        .read()
+        .await
+        .asm_flags(msg.guild_id.map(|guild| guild.get()));
+    parse_result.options.splice(0..0, defaults);
+    let wants_graph = take_cfg_flag(&mut parse_result.options);
+
+    // Show loading indicator
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
 
     // Compile to assembly - this is the slow part, no locks held
     let result = {
         let compilation_manager_lock = compilation_manager.read().await;
-        compilation_manager_lock
-            .assembly(&parse_result, author)
-            .await
+        if wants_graph {
+            compilation_manager_lock
+                .control_flow_graph(&parse_result, author, status.updates())
+                .await
+        } else {
+            compilation_manager_lock
+                .assembly(&parse_result, author, status.updates())
+                .await
+                .map(|result| (result, None))
+        }
     };
 
     // Remove loading indicator
-    discordhelpers::delete_bot_reacts(ctx, msg, loading_reaction).await?;
+    status.finish().await;
 
     // Handle compilation errors
-    let CompilationResult { details, embed } =
+    let (CompilationResult { details, embed }, graph) =
         result.map_err(|e| CommandError::from(format!("Godbolt request failed!\n\n{}", e)))?;
 
-    Ok(HandleRequestResult { embed, details })
+    Ok(HandleRequestResult {
+        embed,
+        details,
+        graph,
+    })
+}
+
+/// Move a source line range given first (`;asm L12-L20 c++`) to the end of
+/// the options as `--lines=L12-L20`, so the target can still follow it
+pub fn split_line_range(content: &str) -> String {
+    let content = parser::strip_mention(content);
+    let end_point = content.find(['\n', '`']).unwrap_or(content.len());
+    let mut params = content[..end_point].split_whitespace();
+    let command = params.next().unwrap_or_default();
+
+    let mut params = params.peekable();
+    let range = match params.peek() {
+        Some(range) if range.starts_with(['L', 'l']) && parse_line_range(range).is_some() => {
+            params.next().unwrap()
+        }
+        _ => return content.to_string(),
+    };
+
+    let lines = format!("--lines={}", range);
+    let first_line: Vec<&str> = std::iter::once(command)
+        .chain(params)
+        .chain(std::iter::once(lines.as_str()))
+        .collect();
+    format!("{}{}", first_line.join(" "), &content[end_point..])
+}
+
+/// Remove the `--cfg` option, which is for the bot rather than the compiler,
+/// returning whether it was given
+pub fn take_cfg_flag(options: &mut Vec<String>) -> bool {
+    let before = options.len();
+    options.retain(|opt| opt != "--cfg");
+    options.len() != before
 }
 
 /// Get the configured loading reaction or default hourglass
@@ -0,0 +1,125 @@
+use futures_util::future::join;
+use serenity::all::{CreateAttachment, CreateMessage};
+use serenity::client::Context;
+use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::cache::CompilerCache;
+use crate::commands::compile::{get_loading_reaction, parse_request};
+use crate::utls::discordhelpers::{embeds, LoadingStatus};
+use crate::utls::{discordhelpers, parser};
+
+/// Longest diff shown inline, anything longer is attached
+const MAX_INLINE_DIFF: usize = 3900;
+
+#[command]
+#[bucket = "nospam"]
+pub async fn asmdiff(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let (first, second) = split_sides(&msg.content)?;
+    let mut requests = Vec::new();
+    for content in [first, second] {
+        let request = parse_request(ctx, msg, &msg.author, &content).await?;
+        requests.push(request);
+    }
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    let results = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        join(
+            compilation_manager_lock.assembly(&requests[0], &msg.author, status.updates()),
+            compilation_manager_lock.assembly(&requests[1], &msg.author, status.updates()),
+        )
+        .await
+    };
+
+    status.finish().await;
+    let (first, second) = (results.0?, results.1?);
+
+    // A side that didn't compile has no assembly to compare, so show why
+    for result in [&first, &second] {
+        if !result.details.success {
+            embeds::reply_embed(&ctx.http, msg, result.embed.clone()).await?;
+            return Ok(());
+        }
+    }
+
+    let label = |index: usize| {
+        let request = &requests[index];
+        let details = [&first.details, &second.details][index];
+        std::iter::once(details.compiler.as_str())
+            .chain(request.options.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let (first_label, second_label) = (label(0), label(1));
+    let diff = diff_assembly(
+        first.details.assembly.as_deref().unwrap_or_default(),
+        second.details.assembly.as_deref().unwrap_or_default(),
+        &first_label,
+        &second_label,
+    );
+
+    let attach = diff.len() > MAX_INLINE_DIFF;
+    let emb = embeds::build_asmdiff_embed(&msg.author, &first_label, &second_label, &diff, attach);
+    let mut new_msg = discordhelpers::reply_to(msg, CreateMessage::new().embed(emb));
+    if attach {
+        new_msg = new_msg.add_file(CreateAttachment::bytes(diff.into_bytes(), "asm.diff"));
+    }
+    msg.channel_id.send_message(&ctx.http, new_msg).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// Split `;asmdiff <target-a> [flags] | <target-b> [flags]` into a command
+/// for each side, both followed by the rest of the message
+pub fn split_sides(content: &str) -> Result<(String, String), CommandError> {
+    let content = parser::strip_mention(content);
+    let end_point = content.find(['\n', '`']).unwrap_or(content.len());
+    let mut params = content[..end_point].split_whitespace();
+    let command = params.next().unwrap_or_default();
+
+    let params: Vec<&str> = params.collect();
+    let sides: Vec<&[&str]> = params.split(|param| *param == "|").collect();
+    match sides.as_slice() {
+        [first, second] if !first.is_empty() && !second.is_empty() => {
+            let side = |params: &[&str]| {
+                format!("{} {}{}", command, params.join(" "), &content[end_point..])
+            };
+            Ok((side(first), side(second)))
+        }
+        _ => Err(CommandError::from(
+            "Supply two compilers to compare, separated by `|`.\n\nUsage: `;asmdiff g132 -O2 | g132 -O3` followed by a code block",
+        )),
+    }
+}
+
+/// Reduce a listing to what matters when comparing: no blank lines, and
+/// whitespace collapsed so alignment changes don't show up as differences
+pub fn normalize_assembly(assembly: &str) -> String {
+    assembly
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Unified diff of two listings, empty if they're the same once normalized
+pub fn diff_assembly(first: &str, second: &str, first_label: &str, second_label: &str) -> String {
+    let (first, second) = (normalize_assembly(first), normalize_assembly(second));
+    similar::TextDiff::from_lines(&first, &second)
+        .unified_diff()
+        .context_radius(3)
+        .header(first_label, second_label)
+        .to_string()
+}
@@ -0,0 +1,85 @@
+use serenity::client::Context;
+use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::cache::CompilerCache;
+use crate::commands::compile::{get_loading_reaction, parse_request};
+use crate::utls::bench::BenchStats;
+use crate::utls::discordhelpers::{embeds, LoadingStatus};
+use crate::utls::parser;
+
+/// Runs when the count is left out, and the most a single ;bench may ask for
+const DEFAULT_BENCH_RUNS: usize = 5;
+const MAX_BENCH_RUNS: usize = 20;
+
+#[command]
+#[bucket = "nospam"]
+pub async fn bench(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let (runs, content) = split_runs(&msg.content)?;
+    let parse_result = parse_request(ctx, msg, &msg.author, &content).await?;
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    let results = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        compilation_manager_lock
+            .benchmark(&parse_result, &msg.author, runs, status.updates())
+            .await
+    };
+
+    status.finish().await;
+
+    let mut results = results?;
+    let last = results.pop().unwrap();
+    let times: Vec<i64> = results
+        .iter()
+        .chain(std::iter::once(&last))
+        .filter_map(|result| result.details.exec_time)
+        .collect();
+
+    // A failed run has nothing worth timing, so show why it failed instead
+    let emb = match BenchStats::from_times(&times) {
+        Some(stats) if last.details.success => {
+            embeds::build_bench_embed(&msg.author, &last.details, &stats, runs)
+        }
+        _ => last.embed,
+    };
+    embeds::reply_embed(&ctx.http, msg, emb).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// Take the run count, if any, off the front of the command, returning it
+/// with the rest of the message
+pub fn split_runs(content: &str) -> Result<(usize, String), CommandError> {
+    let content = parser::strip_mention(content);
+    let end_point = content.find(['\n', '`']).unwrap_or(content.len());
+    let mut params = content[..end_point].split_whitespace().skip(1).peekable();
+
+    let mut runs = DEFAULT_BENCH_RUNS;
+    if let Some(count) = params.peek().and_then(|p| p.parse::<usize>().ok()) {
+        if !(2..=MAX_BENCH_RUNS).contains(&count) {
+            return Err(CommandError::from(format!(
+                "Benchmarks take between 2 and {} runs.",
+                MAX_BENCH_RUNS
+            )));
+        }
+        runs = count;
+        params.next();
+    }
+
+    let rest: Vec<&str> = params.collect();
+    Ok((
+        runs,
+        format!(";bench {}{}", rest.join(" "), &content[end_point..]),
+    ))
+}
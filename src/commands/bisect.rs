@@ -0,0 +1,153 @@
+use godbolt::Compiler;
+use serenity::client::Context;
+use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::cache::CompilerCache;
+use crate::commands::compile::{apply_settings, get_loading_reaction};
+use crate::utls::bisect::{bisect_versions, Outcome};
+use crate::utls::discordhelpers::{embeds, LoadingStatus};
+use crate::utls::parser;
+
+/// Numeric components of a compiler version, e.g. `13.2.0` -> [13, 2, 0]
+pub fn parse_version(semver: &str) -> Option<Vec<u32>> {
+    let version = semver.trim().trim_start_matches('v');
+    let parts: Vec<u32> = version
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect();
+    (!parts.is_empty()).then_some(parts)
+}
+
+/// Released x86-64 compilers of one family (gcc, clang, ...) oldest first,
+/// one per version. Trunk builds and variants like `(assertions)` are left
+/// out since they don't sit anywhere on the version line.
+pub fn family_compilers(compilers: &[Compiler], family: &str) -> Vec<Compiler> {
+    let family = family.to_lowercase();
+    let mut versions: Vec<(Vec<u32>, Compiler)> = compilers
+        .iter()
+        .filter(|compiler| {
+            let name = compiler.name.to_lowercase();
+            name.split_whitespace().any(|word| word == family)
+                && !name.contains('(')
+                && compiler
+                    .instruction_set
+                    .as_deref()
+                    .is_none_or(|set| set == "amd64")
+        })
+        .filter_map(|compiler| {
+            let version = parse_version(compiler.semver.as_deref()?)?;
+            Some((version, compiler.clone()))
+        })
+        .collect();
+
+    versions.sort_by(|a, b| a.0.cmp(&b.0));
+    versions.dedup_by(|a, b| a.0 == b.0);
+    versions.into_iter().map(|(_, compiler)| compiler).collect()
+}
+
+#[command]
+#[bucket = "nospam"]
+pub async fn bisect(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let (family, content) = split_family(&msg.content)?;
+
+    // The language comes from the code block's syntax highlighting
+    let mut parse_result =
+        parser::get_components(&content, &msg.author, None, &msg.referenced_message, true).await?;
+    // Bisecting picks its own compilers, so only the requester's flags apply
+    let language = parse_result.target.clone();
+    apply_settings(ctx, msg, &msg.author, &mut parse_result).await;
+    parse_result.target = language;
+
+    let versions = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        let godbolt = compilation_manager_lock.godbolt().ok_or_else(|| {
+            CommandError::from(
+                "Compiler Explorer is unavailable. This may be due to an outage. Please try again later.",
+            )
+        })?;
+        let compilers = godbolt
+            .cache
+            .iter()
+            .find(|entry| entry.language.id == parse_result.target)
+            .map(|entry| entry.compilers.as_slice())
+            .ok_or_else(|| {
+                CommandError::from(format!(
+                    "Language '{}' is not supported on godbolt.org",
+                    parse_result.target
+                ))
+            })?;
+        family_compilers(compilers, &family)
+    };
+    if versions.len() < 2 {
+        return Err(CommandError::from(format!(
+            "Found {} released '{}' compilers for {}, at least two are needed to bisect.",
+            versions.len(),
+            family,
+            parse_result.target
+        )));
+    }
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    let bisection = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        bisect_versions(versions.len(), |index| {
+            let mut request = parse_result.clone();
+            request.target = versions[index].id.clone();
+            let compilation_manager = &compilation_manager_lock;
+            let name = &versions[index].name;
+            let updates = status.updates();
+            async move {
+                let (_, result) = compilation_manager
+                    .compile_godbolt_raw(&request, updates)
+                    .await
+                    .map_err(|e| CommandError::from(format!("{} failed: {}", name, e)))?;
+                Ok(Outcome::from_result(&result))
+            }
+        })
+        .await
+    };
+
+    status.finish().await;
+
+    let emb = embeds::build_bisect_embed(&msg.author, &family, &versions, &bisection?);
+    embeds::reply_embed(&ctx.http, msg, emb).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// Take the compiler family off the front of the command, returning it with
+/// the rest of the message
+pub fn split_family(content: &str) -> Result<(String, String), CommandError> {
+    let content = parser::strip_mention(content);
+    let end_point = content.find(['\n', '`']).unwrap_or(content.len());
+    let mut params = content[..end_point].split_whitespace().skip(1);
+
+    let family = params
+        .next()
+        .map(str::to_lowercase)
+        .ok_or_else(|| {
+            CommandError::from(
+                "Supply the compiler family to bisect.\n\nUsage: `;bisect gcc [options]` followed by a code block",
+            )
+        })?;
+
+    let rest: Vec<&str> = params.collect();
+    Ok((
+        family,
+        format!(";bisect {}{}", rest.join(" "), &content[end_point..]),
+    ))
+}
@@ -0,0 +1,141 @@
+use futures_util::future::join_all;
+use serenity::builder::CreateEmbed;
+use serenity::client::Context;
+use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::cache::CompilerCache;
+use crate::commands::compile::{apply_settings, get_loading_reaction};
+use crate::managers::compilation::CompilationManager;
+use crate::utls::discordhelpers::embeds::{self, CompareEntry};
+use crate::utls::discordhelpers::menu::Menu;
+use crate::utls::discordhelpers::LoadingStatus;
+use crate::utls::parser;
+
+/// Most compilers a single ;compare may run
+const MAX_COMPARE_TARGETS: usize = 6;
+
+#[command]
+#[bucket = "nospam"]
+pub async fn compare(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let (targets, assembly, content) = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        split_targets(&msg.content, &compilation_manager_lock)?
+    };
+
+    // The first compiler stands in as the target so the usual parser picks
+    // up options, stdin, arguments and code
+    let parse_result =
+        parser::get_components(&content, &msg.author, None, &msg.referenced_message, false).await?;
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    // Settings apply per compiler, since each may be for another language
+    let mut requests = Vec::new();
+    for target in targets {
+        let mut request = parse_result.clone();
+        request.target = target.clone();
+        apply_settings(ctx, msg, &msg.author, &mut request).await;
+        requests.push((target, request));
+    }
+
+    let results = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        let runs = requests.into_iter().map(|(target, request)| {
+            let compilation_manager = &compilation_manager_lock;
+            let updates = status.updates();
+            async move {
+                let result = if assembly {
+                    compilation_manager
+                        .assembly(&request, &msg.author, updates)
+                        .await
+                } else {
+                    compilation_manager
+                        .execute(&request, &msg.author, false, updates)
+                        .await
+                };
+                (target, result)
+            }
+        });
+        join_all(runs).await
+    };
+
+    status.finish().await;
+
+    let mut entries = Vec::new();
+    let mut pages: Vec<CreateEmbed> = Vec::new();
+    for (target, result) in results {
+        let result = match result {
+            Ok(result) => {
+                pages.push(result.embed);
+                Ok(result.details)
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        entries.push(CompareEntry { target, result });
+    }
+    pages.insert(0, embeds::build_compare_embed(&msg.author, &entries));
+
+    let mut menu = Menu::new(ctx, msg, &pages);
+    menu.run().await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// Take the compilers (and `--asm`) off the front of the command, returning
+/// them with the message rewritten as a request for the first compiler
+pub fn split_targets(
+    content: &str,
+    compilation_manager: &CompilationManager,
+) -> Result<(Vec<String>, bool, String), CommandError> {
+    let content = parser::strip_mention(content);
+    let end_point = content.find(['\n', '`']).unwrap_or(content.len());
+
+    let mut targets: Vec<String> = Vec::new();
+    let mut assembly = false;
+    let mut rest = content[..end_point].split_whitespace().skip(1).peekable();
+    while let Some(param) = rest.peek() {
+        let lower_param = param.to_lowercase();
+        let target = parser::shortname_to_qualified(&lower_param);
+        if lower_param == "--asm" {
+            assembly = true;
+        } else if compilation_manager.is_target_supported(target) {
+            if !targets.iter().any(|t| t == target) {
+                targets.push(target.to_string());
+            }
+        } else {
+            break;
+        }
+        rest.next();
+    }
+
+    if targets.len() < 2 {
+        return Err(CommandError::from(
+            "Supply at least two compilers or languages to compare.\n\nUsage: `;compare gcc-13 clang-17 [--asm] [options]` followed by a code block",
+        ));
+    }
+    if targets.len() > MAX_COMPARE_TARGETS {
+        return Err(CommandError::from(format!(
+            "Up to {} compilers can be compared at once.",
+            MAX_COMPARE_TARGETS
+        )));
+    }
+
+    let rest: Vec<&str> = rest.collect();
+    let content = format!(
+        ";compare {} {}{}",
+        targets[0],
+        rest.join(" "),
+        &content[end_point..]
+    );
+    Ok((targets, assembly, content))
+}
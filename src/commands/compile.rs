@@ -12,9 +12,11 @@ use serenity::framework::standard::{macros::command, Args, CommandResult};
 use serenity::model::channel::{Message, ReactionType};
 use serenity::model::user::User;
 
-use crate::cache::{CompilerCache, ConfigCache, LinkAPICache, MessageCache, MessageCacheEntry};
+use crate::cache::{
+    CompilerCache, ConfigCache, LinkAPICache, MessageCache, MessageCacheEntry, SettingsCache,
+};
 use crate::managers::compilation::{CompilationDetails, CompilationResult};
-use crate::utls::discordhelpers::embeds;
+use crate::utls::discordhelpers::{embeds, LoadingStatus};
 use crate::utls::parser::ParserResult;
 use crate::utls::{discordhelpers, parser};
 
@@ -34,6 +36,7 @@ pub async fn compile(ctx: &Context, msg: &Message, _args: Args) -> CommandResult
     }
 
     let mut new_msg = discordhelpers::reply_to(msg, CreateMessage::new().embed(result.embed));
+    new_msg = discordhelpers::attach_compiler_log(new_msg, &result.details);
     if !buttons.is_empty() {
         new_msg = new_msg.components(vec![CreateActionRow::Buttons(buttons)]);
     }
@@ -126,7 +129,7 @@ pub async fn await_execute_button(
         .await?;
 
     // mark as executed so edits re-execute rather than compile
-    let compilation_manager = {
+    let (loading_reaction, compilation_manager) = {
         let data = ctx.data.read().await;
         {
             let mut message_cache = data.get::<MessageCache>().unwrap().lock().await;
@@ -134,14 +137,17 @@ pub async fn await_execute_button(
                 entry.executed = true;
             }
         }
-        data.get::<CompilerCache>().unwrap().clone()
+        let reaction = get_loading_reaction(&data).await?;
+        (reaction, data.get::<CompilerCache>().unwrap().clone())
     };
+    let status = LoadingStatus::start(ctx, &sent, loading_reaction).await?;
     let result = {
         let compilation_manager_lock = compilation_manager.read().await;
         compilation_manager_lock
-            .execute(&parse_result, &request_msg.author, false)
+            .execute(&parse_result, &request_msg.author, false, status.updates())
             .await
     };
+    status.finish().await;
 
     match result {
         Ok(CompilationResult { details, embed }) => {
@@ -159,11 +165,9 @@ pub async fn await_execute_button(
             }
             let _ = discordhelpers::send_completion_react(ctx, &sent, details.success).await;
 
-            sent.edit(
-                &ctx.http,
-                EditMessage::new().embed(embed).components(components),
-            )
-            .await?;
+            let edit = EditMessage::new().embed(embed).components(components);
+            sent.edit(&ctx.http, discordhelpers::edit_compiler_log(edit, &details))
+                .await?;
         }
         Err(e) => {
             let embed = embeds::build_fail_embed(&request_msg.author, &e.to_string());
@@ -206,42 +210,28 @@ pub async fn handle_request(
     let content = append_attachment_code(content, &msg.attachments).await?;
 
     // Parse the compilation request
-    let parse_result = parser::get_components(
-        &content,
-        author,
-        Some(&compilation_manager),
-        &msg.referenced_message,
-        false,
-    )
-    .await?;
+    let parse_result = parse_request(ctx, msg, author, &content).await?;
 
-    // Show loading indicator
-    if msg
-        .react(&ctx.http, loading_reaction.clone())
-        .await
-        .is_err()
-    {
-        return Err(CommandError::from(
-            "Unable to react to message. Am I missing permissions to react or use external emoji?",
-        ));
-    }
+    // Show loading indicator, along with the queue position while waiting
+    // for a busy backend
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
 
     // Compile the code - this is the slow part, no locks held
     let result = {
         let compilation_manager_lock = compilation_manager.read().await;
         if execute {
             compilation_manager_lock
-                .execute(&parse_result, author, true)
+                .execute(&parse_result, author, true, status.updates())
                 .await
         } else {
             compilation_manager_lock
-                .compile(&parse_result, author)
+                .compile(&parse_result, author, status.updates())
                 .await
         }
     };
 
     // Remove loading indicator
-    let _ = discordhelpers::delete_bot_reacts(ctx, msg, loading_reaction).await;
+    status.finish().await;
 
     // Handle compilation errors
     let CompilationResult { details, embed } = result?;
@@ -256,8 +246,67 @@ pub async fn handle_request(
     })
 }
 
+/// Parse a request, falling back to the requester's default language, and
+/// apply the rest of their settings to it
+pub async fn parse_request(
+    ctx: &Context,
+    msg: &Message,
+    author: &User,
+    content: &str,
+) -> Result<ParserResult, CommandError> {
+    let (settings, compilation_manager) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<SettingsCache>().unwrap().clone(),
+            data.get::<CompilerCache>().unwrap().clone(),
+        )
+    };
+    let language = settings
+        .read()
+        .await
+        .effective(msg.guild_id.map(|guild| guild.get()), author.id.get())
+        .language;
+
+    let mut request = parser::get_components_with_language(
+        content,
+        author,
+        Some(&compilation_manager),
+        &msg.referenced_message,
+        false,
+        language.as_deref(),
+    )
+    .await?;
+    apply_settings(ctx, msg, author, &mut request).await;
+    Ok(request)
+}
+
+/// Apply the requester's saved preferences (see /settings) to their request
+pub async fn apply_settings(
+    ctx: &Context,
+    msg: &Message,
+    author: &User,
+    request: &mut ParserResult,
+) {
+    let (settings, compilation_manager) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<SettingsCache>().unwrap().clone(),
+            data.get::<CompilerCache>().unwrap().clone(),
+        )
+    };
+
+    let settings = settings.read().await;
+    let compilation_manager = compilation_manager.read().await;
+    settings.apply(
+        request,
+        msg.guild_id.map(|guild| guild.get()),
+        author.id.get(),
+        &compilation_manager,
+    );
+}
+
 /// Get the configured loading reaction or default hourglass
-async fn get_loading_reaction(
+pub async fn get_loading_reaction(
     data: &tokio::sync::RwLockReadGuard<'_, serenity::prelude::TypeMap>,
 ) -> Result<ReactionType, CommandError> {
     let config = data.get::<ConfigCache>().unwrap().read().await;
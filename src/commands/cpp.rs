@@ -9,9 +9,9 @@ use serenity::model::user::User;
 use crate::cache::{CompilerCache, ConfigCache, LinkAPICache, MessageCache, MessageCacheEntry};
 use crate::cppeval::eval::CppEval;
 use crate::managers::compilation::CompilationDetails;
-use crate::utls::discordhelpers;
-use crate::utls::discordhelpers::embeds::{EmbedOptions, ToEmbed};
-use crate::utls::parser::ParserResult;
+use crate::utls::discordhelpers::embeds::{self, EmbedOptions};
+use crate::utls::discordhelpers::{self, LoadingStatus};
+use crate::utls::parser::{self, ParserResult};
 
 #[command]
 #[aliases("c++")]
@@ -67,6 +67,7 @@ pub async fn handle_request(
     };
 
     // Parse the C++ expression
+    let content = parser::strip_mention(content);
     let start = content
         .find(' ')
         .ok_or_else(|| CommandError::from("Invalid usage. View `;help cpp`"))?;
@@ -77,15 +78,7 @@ pub async fn handle_request(
     let wrapped_code = eval.evaluate()?;
 
     // Show loading indicator
-    if msg
-        .react(&ctx.http, loading_reaction.clone())
-        .await
-        .is_err()
-    {
-        return Err(CommandError::from(
-            "Unable to react to message. Am I missing permissions to react or use external emoji?",
-        ));
-    }
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
 
     // Build a fake parse result for the compilation
     let parse_result = ParserResult {
@@ -94,24 +87,24 @@ pub async fn handle_request(
         target: "gsnapshot".to_string(),
         code: wrapped_code,
         options: vec![String::from("-O3"), String::from("-std=gnu++26")],
-        args: vec![],
+        ..Default::default()
     };
 
     let result = {
         let compilation_manager_lock = compilation_manager.read().await;
         compilation_manager_lock
-            .compile_godbolt_raw(&parse_result)
+            .compile_godbolt_raw(&parse_result, status.updates())
             .await
     };
 
     // Remove loading indicator
-    let _ = discordhelpers::delete_bot_reacts(ctx, msg, loading_reaction).await;
+    status.finish().await;
 
-    let (details, response) = result?;
+    let (mut details, response) = result?;
 
     // Build embed from response
-    let embed_options = EmbedOptions::new(false, false, details.clone());
-    let embed = response.to_embed(author, &embed_options);
+    let embed_options = EmbedOptions::new(false, false);
+    let embed = embeds::build_result_embed(response, author, &embed_options, &mut details);
 
     Ok(HandleRequestResult { embed, details })
 }
@@ -0,0 +1,74 @@
+use serenity::all::{CreateActionRow, CreateAttachment, CreateMessage};
+use serenity::client::Context;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::backends::godbolt::DumpKind;
+use crate::cache::CompilerCache;
+use crate::commands::compile::{build_link_button, get_loading_reaction, parse_request};
+use crate::utls::discordhelpers::{self, LoadingStatus};
+
+#[command]
+#[bucket = "nospam"]
+pub async fn ir(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    handle_dump(ctx, msg, DumpKind::LlvmIr).await
+}
+
+#[command]
+#[bucket = "nospam"]
+pub async fn ast(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    handle_dump(ctx, msg, DumpKind::ClangAst).await
+}
+
+#[command]
+#[bucket = "nospam"]
+pub async fn remarks(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    handle_dump(ctx, msg, DumpKind::OptRemarks).await
+}
+
+#[command]
+#[bucket = "nospam"]
+pub async fn gccdump(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    handle_dump(ctx, msg, DumpKind::GccDump).await
+}
+
+async fn handle_dump(ctx: &Context, msg: &Message, kind: DumpKind) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let parse_result = parse_request(ctx, msg, &msg.author, &msg.content).await?;
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    let result = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        compilation_manager_lock
+            .dump(&parse_result, &msg.author, kind, status.updates())
+            .await
+    };
+
+    status.finish().await;
+    let (result, full_output) = result?;
+
+    let mut new_msg = discordhelpers::reply_to(msg, CreateMessage::new().embed(result.embed));
+    if let Some(output) = full_output {
+        new_msg = new_msg.add_file(CreateAttachment::bytes(
+            output.into_bytes(),
+            kind.file_name(),
+        ));
+    }
+    let buttons = build_link_button(ctx, &result.details).await;
+    if !buttons.is_empty() {
+        new_msg = new_msg.components(vec![CreateActionRow::Buttons(buttons)]);
+    }
+
+    let sent = msg.channel_id.send_message(&ctx.http, new_msg).await?;
+    discordhelpers::send_completion_react(ctx, &sent, result.details.success).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
@@ -1,11 +1,21 @@
-use serenity::all::{CreateActionRow, CreateMessage};
+use std::time::Duration;
+
+use serenity::all::{
+    ButtonStyle, CreateActionRow, CreateAttachment, CreateButton, CreateInteractionResponse,
+    CreateMessage, EditMessage,
+};
 use serenity::client::Context;
 use serenity::framework::standard::{macros::command, Args, CommandResult};
 use serenity::model::channel::Message;
 
-use crate::cache::{MessageCache, MessageCacheEntry};
-use crate::commands::compile::{build_link_button, handle_request};
-use crate::utls::discordhelpers;
+use crate::backends::backend::boilerplate_generation;
+use crate::cache::{CompilerCache, MessageCache, MessageCacheEntry};
+use crate::commands::compile::{build_link_button, get_loading_reaction, handle_request};
+use crate::utls::discordhelpers::{embeds, LoadingStatus};
+use crate::utls::parser::ParserResult;
+use crate::utls::{discordhelpers, triage};
+
+const TRIAGE_BUTTON_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[command]
 #[aliases("exec", "exe")]
@@ -15,7 +25,12 @@ pub async fn execute(ctx: &Context, msg: &Message, _args: Args) -> CommandResult
 
     // Build message with optional godbolt link button
     let mut new_msg = discordhelpers::reply_to(msg, CreateMessage::new().embed(result.embed));
-    let buttons = build_link_button(ctx, &result.details).await;
+    new_msg = discordhelpers::attach_compiler_log(new_msg, &result.details);
+    let link_button = build_link_button(ctx, &result.details).await;
+    let mut buttons = link_button.clone();
+    if result.details.signal.is_some() {
+        buttons.push(triage_button(msg));
+    }
     if !buttons.is_empty() {
         new_msg = new_msg.components(vec![CreateActionRow::Buttons(buttons)]);
     }
@@ -26,12 +41,120 @@ pub async fn execute(ctx: &Context, msg: &Message, _args: Args) -> CommandResult
     discordhelpers::send_completion_react(ctx, &sent, result.details.success).await?;
 
     // Cache for edit tracking
-    let data = ctx.data.read().await;
-    let mut message_cache = data.get::<MessageCache>().unwrap().lock().await;
-    let mut entry = MessageCacheEntry::new(sent, msg.clone());
-    entry.executed = true;
-    message_cache.insert(msg.id.get(), entry);
+    {
+        let data = ctx.data.read().await;
+        let mut message_cache = data.get::<MessageCache>().unwrap().lock().await;
+        let mut entry = MessageCacheEntry::new(sent.clone(), msg.clone());
+        entry.executed = true;
+        message_cache.insert(msg.id.get(), entry);
+    }
+
+    if let Some(signal) = result.details.signal {
+        await_triage_button(ctx, msg, sent, signal, result.parse_result, link_button).await?;
+    }
 
     debug!("Command executed");
     Ok(())
 }
+
+fn triage_button(msg: &Message) -> CreateButton {
+    CreateButton::new(format!("triage:{}", msg.id.get()))
+        .label("Triage crash")
+        .style(ButtonStyle::Secondary)
+}
+
+/// Wait for the requester to press Triage crash, then rerun the program with
+/// sanitizers and reply with what they found. The button is removed once we
+/// stop waiting, and an edit to the request stops the wait early.
+async fn await_triage_button(
+    ctx: &Context,
+    request_msg: &Message,
+    mut sent: Message,
+    signal: &str,
+    parse_result: ParserResult,
+    link_button: Vec<CreateButton>,
+) -> CommandResult {
+    let interaction = sent
+        .await_component_interaction(&ctx.shard)
+        .author_id(request_msg.author.id)
+        .timeout(TRIAGE_BUTTON_TIMEOUT)
+        .await;
+
+    let (generation, loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let generation = data
+            .get::<MessageCache>()
+            .unwrap()
+            .lock()
+            .await
+            .get_mut(&request_msg.id.get())
+            .map(|entry| entry.button_generation);
+        let reaction = get_loading_reaction(&data).await?;
+        (
+            generation,
+            reaction,
+            data.get::<CompilerCache>().unwrap().clone(),
+        )
+    };
+    if generation != Some(0) {
+        return Ok(());
+    }
+
+    let components = if link_button.is_empty() {
+        Vec::new()
+    } else {
+        vec![CreateActionRow::Buttons(link_button)]
+    };
+    sent.edit(&ctx.http, EditMessage::new().components(components))
+        .await?;
+
+    let Some(mci) = interaction else {
+        return Ok(());
+    };
+    mci.create_response(&ctx.http, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let status = LoadingStatus::start(ctx, &sent, loading_reaction).await?;
+    let result = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        compilation_manager_lock
+            .triage(&parse_result, status.updates())
+            .await
+    };
+    status.finish().await;
+
+    let reply = match result {
+        Ok((details, report)) => {
+            // Lines are reported against the code that was sent, which may
+            // have had boilerplate added
+            let source = boilerplate_generation(&details.language, &parse_result.code);
+            let finding = triage::analyze(&report).map(|finding| triage::render(&finding, &source));
+            let attached = !report.trim().is_empty();
+            let embed = embeds::build_triage_embed(
+                &request_msg.author,
+                &details,
+                signal,
+                finding,
+                attached,
+            );
+
+            let mut reply = CreateMessage::new().embed(embed);
+            if attached {
+                reply = reply.add_file(CreateAttachment::bytes(
+                    report.into_bytes(),
+                    "sanitizer.txt",
+                ));
+            }
+            reply
+        }
+        Err(e) => CreateMessage::new().embed(embeds::build_fail_embed(
+            &request_msg.author,
+            &e.to_string(),
+        )),
+    };
+    sent.channel_id
+        .send_message(&ctx.http, reply.reference_message(&sent))
+        .await?;
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::cache::CompilerCache;
 use crate::utls::discordhelpers;
-use crate::utls::parser::{get_message_attachment, ParserResult};
+use crate::utls::parser::{self, get_message_attachment, ParserResult};
 use serenity::all::{CreateAttachment, CreateMessage};
 use serenity::framework::standard::{
     macros::command, Args, CommandError, CommandResult, Delimiter,
@@ -15,9 +15,10 @@ pub async fn format(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
     let mut style = String::from("webkit");
     if !args.is_empty() {
         // do not include ``` codeblocks into arg parsing.. lets just substr and replace args
-        let idx = msg.content.find('`');
+        let content = parser::strip_mention(&msg.content);
+        let idx = content.find('`');
         if let Some(idx) = idx {
-            let substr: String = msg.content.chars().take(idx).collect();
+            let substr: String = content.chars().take(idx).collect();
             args = Args::new(&substr, &[Delimiter::Single(' ')]);
             args.advance();
         }
@@ -7,12 +7,19 @@ use serenity::{
     prelude::*,
 };
 
+use crate::cache::SettingsCache;
 use crate::utls::constants::*;
 use crate::utls::discordhelpers::embeds;
 
 #[command]
 pub async fn help(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let prefix = env::var("BOT_PREFIX").expect("Bot prefix is not set!");
+    let prefix = {
+        let default = env::var("BOT_PREFIX").expect("Bot prefix is not set!");
+        let data = ctx.data.read().await;
+        let settings = data.get::<SettingsCache>().unwrap().read().await;
+        let mut prefixes = settings.prefixes(msg.guild_id.map(|id| id.get()), &default);
+        prefixes.swap_remove(0)
+    };
     if !args.is_empty() {
         let cmd = args.parse::<String>().unwrap();
         let mut emb = CreateEmbed::default()
@@ -30,6 +37,137 @@ pub async fn help(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
                 );
                 "Grabs the bot's invite link\n\n"
             }
+            "compare" => {
+                emb = emb.title("Compare command").field(
+                    "Example",
+                    format!(
+                        "{}compare g132 clang1710 --asm -O2\n\
+                          \\`\\`\\`cpp\n\
+                          int square(int x) {{ return x * x; }}\n\
+                          \\`\\`\\`\n",
+                        prefix
+                    ),
+                    false,
+                );
+                "Runs one code block through up to six compilers at once and shows \
+                 whether each succeeded and how long it took. Add `--asm` to compare \
+                 assembly instead of running the program. Options, stdin and arguments \
+                 apply to every compiler.\n\n"
+            }
+            "tool" | "tools" => {
+                emb = emb
+                    .title("Tool command")
+                    .field(
+                        "Example",
+                        format!(
+                            "{}tool clang-tidytrunk clang1701 -std=c++20\n\
+                              --checks=modernize-*\n\
+                              \\`\\`\\`cpp\n\
+                              int main() {{ int* p = 0; }}\n\
+                              \\`\\`\\`\n",
+                            prefix
+                        ),
+                        false,
+                    )
+                    .field("Listing tools", format!("{}tool list c++", prefix), false);
+                "Compiles a code block on godbolt.org with one of its tools (clang-tidy, \
+                 pahole, llvm-mca...) running alongside and shows the tool's output. Tool \
+                 arguments go on the line after the command.\n\n"
+            }
+            "ir" | "ast" | "remarks" | "gccdump" => {
+                emb = emb
+                    .title("Compiler output commands")
+                    .field(
+                        "Example",
+                        format!(
+                            "{}ir clang1701 -O2\n\
+                              \\`\\`\\`cpp\n\
+                              int square(int x) {{ return x * x; }}\n\
+                              \\`\\`\\`\n",
+                            prefix
+                        ),
+                        false,
+                    )
+                    .field(
+                        "GCC dumps",
+                        format!(
+                            "{0}gccdump g132 -O2 lists the passes, {0}gccdump g132 -O2 --pass=optimized \
+                             shows one",
+                            prefix
+                        ),
+                        false,
+                    );
+                "Compiles a code block on godbolt.org and shows something other than \
+                 assembly: LLVM IR (ir), the Clang AST (ast), optimization remarks (remarks) \
+                 or GCC's tree/RTL dumps (gccdump). Output too long to display is attached \
+                 as a file.\n\n"
+            }
+            "test" => {
+                emb = emb.title("Test command").field(
+                    "Example",
+                    format!(
+                        "{}test python --ignore-whitespace\n\
+                          \\`\\`\\`py\n\
+                          print(sum(map(int, input().split())))\n\
+                          \\`\\`\\`\n\
+                          \\`\\`\\`input\n\
+                          1 2\n\
+                          \\`\\`\\`\n\
+                          \\`\\`\\`expected\n\
+                          3\n\
+                          \\`\\`\\`\n",
+                        prefix
+                    ),
+                    false,
+                );
+                "Runs a program once for every input block, using it as stdin, and checks \
+                 the output against the expected block after it. Add --ignore-whitespace to \
+                 compare words only, or --tolerance=1e-6 to allow numbers to differ slightly.\n\n"
+            }
+            "bench" => {
+                emb = emb.title("Bench command").field(
+                    "Example",
+                    format!(
+                        "{}bench 10 g132 -O2\n\
+                          \\`\\`\\`cpp\n\
+                          int main() {{ /* work */ }}\n\
+                          \\`\\`\\`\n",
+                        prefix
+                    ),
+                    false,
+                );
+                "Executes a code block several times (5 by default, up to 20) and shows the \
+                 min, median, mean & standard deviation of its run time, warning when the \
+                 timings are too noisy to trust. Only compilers hosted on godbolt.org \
+                 report run times.\n\n"
+            }
+            "bisect" => {
+                emb = emb.title("Bisect command").field(
+                    "Example",
+                    format!(
+                        "{}bisect gcc -O2\n\
+                          \\`\\`\\`cpp\n\
+                          int main() {{ return sizeof(long double); }}\n\
+                          \\`\\`\\`\n",
+                        prefix
+                    ),
+                    false,
+                );
+                "Runs a code block against released versions of one compiler family on \
+                 godbolt.org and reports the first version where the output, exit code or \
+                 diagnostics changed. Versions are bisected, so only a handful are tried; \
+                 this assumes the behaviour changes only once. The language is taken from \
+                 the code block.\n\n"
+            }
+            "status" => {
+                emb = emb.title("Status command").field(
+                    "Example",
+                    format!("{}status", prefix),
+                    false,
+                );
+                "Displays recent success rates & latencies of each compiler backend and \
+                 service, and whether requests to it are paused due to an outage\n\n"
+            }
             "compile" => {
                 emb = emb
                     .title("Compile command")
@@ -56,27 +194,49 @@ pub async fn help(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
                             prefix
                         ),
                         false,
+                    )
+                    .field(
+                        "Multiple files",
+                        "Label each code block with its file name, i.e. \\`\\`\\`cpp main.cpp or \
+                         \\`\\`\\`h util.h, to compile them together on Compiler Explorer. A \
+                         CMakeLists.txt block builds the project with CMake",
+                        false,
                     );
                 "Sends a compilation request, reporting the compiler's diagnostics without \
                  running the program. Press the Execute button within 30 seconds to run it, \
                  or use the execute command to compile & run in one step\n\n"
             }
             "execute" | "exec" | "exe" => {
-                emb = emb.title("Execute command").field(
-                    "Example",
-                    format!(
-                        "{}execute c++\n\
+                emb = emb
+                    .title("Execute command")
+                    .field(
+                        "Example",
+                        format!(
+                            "{}execute c++\n\
                           \\`\\`\\`\n\
                           #include <iostream>\n\n\
                           int main() {{ \n\
                           \tstd::cout << \"Hello, world\";\n\
                           }}\n\
                           \\`\\`\\`\n",
-                        prefix
-                    ),
-                    false,
-                );
-                "Compiles and executes code, displaying the program's output\n\n"
+                            prefix
+                        ),
+                        false,
+                    )
+                    .field(
+                        "Presets",
+                        format!(
+                            "`--asan`, `--ubsan`, `--tsan` and `--msan` build with a sanitizer, \
+                         `--hardened` with fortify and stack protection, and `--debug` \
+                         unoptimized with assertions, in the right spelling for GCC, Clang \
+                         or MSVC, i.e. `{}execute c++ --asan --ubsan`",
+                            prefix
+                        ),
+                        false,
+                    );
+                "Compiles and executes code, displaying the program's output. When a C or \
+                 C++ program crashes, press Triage crash within a minute to run it again \
+                 with AddressSanitizer and UBSan and see the line that caused it\n\n"
             }
             "compilers" => {
                 emb = emb.title("Compilers command").field(
@@ -130,21 +290,44 @@ pub async fn help(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
                 "Lists all languages supported"
             }
             "asm" => {
-                emb = emb.title("Assembly command").field(
-                    "Example",
-                    format!(
-                        "{}asm c++\n\
+                emb = emb
+                    .title("Assembly command")
+                    .field(
+                        "Example",
+                        format!(
+                            "{}asm c++\n\
                               \\`\\`\\`\n\
                               #include <iostream>\n\n\
                               int main() {{ \n\
                               \tstd::cout << \"Hello, world\";\n\
                               }}\n\
                               \\`\\`\\`\n",
+                            prefix
+                        ),
+                        false,
+                    )
+                    .field(
+                        "Control-flow graph",
+                        format!("{}asm g132 -O2 --cfg", prefix),
+                        false,
+                    );
+                "Sends an assembly request, displaying the assembly output. With --cfg, \
+                 each function's control-flow graph is attached as a Graphviz .dot file\n\n"
+            }
+            "asmdiff" => {
+                emb = emb.title("Assembly diff command").field(
+                    "Example",
+                    format!(
+                        "{}asmdiff g132 -O2 | g132 -O3\n\
+                          \\`\\`\\`cpp\n\
+                          int sum(int* a, int n) {{ int s = 0; for (int i = 0; i < n; ++i) s += a[i]; return s; }}\n\
+                          \\`\\`\\`\n",
                         prefix
                     ),
                     false,
                 );
-                "Sends an assembly request, displaying the assembly output\n\n"
+                "Compiles a code block to assembly with two compilers or sets of flags, \
+                 separated by |, and shows a diff of the two listings\n\n"
             }
             "asmdoc" => {
                 emb = emb.title("Assembly documentation command").field(
@@ -195,7 +378,14 @@ pub async fn help(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         .field("invite", "``` Grabs the bot's invite link ```", false)
         .field("compile", "``` Compiles code ```", false)
         .field("execute", "``` Executes code ```", false)
+        .field("compare", "``` Runs code through several compilers side by side ```", false)
+        .field("tool", "``` Runs a Compiler Explorer tool such as clang-tidy on code ```", false)
+        .field("bench", "``` Times repeated runs of a program ```", false)
+        .field("test", "``` Checks a program's output against test cases ```", false)
+        .field("bisect", "``` Finds the compiler version where code started behaving differently ```", false)
         .field("asm",     "``` Displays assembly```", false)
+        .field("asmdiff", "``` Diffs the assembly of two compilers or flag sets ```", false)
+        .field("ir", format!("``` Displays LLVM IR, see {}help ir for ast, remarks & gccdump ```", prefix), false)
         .field("compilers", "``` Displays the compilers for the specified language ```", false)
         .field("languages", "``` Displays all supported languages ```", false)
         .field("libraries", "``` Displays the libraries for the specified language ```", false)
@@ -204,7 +394,8 @@ pub async fn help(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         .field("cpp", format!("``` Executes c++ code using geordi-like syntax\n See {}help cpp for more info ```", prefix), false)
         .field("format", "``` Formats code using a code formatter (i.e. clang-format or rustfmt) ```", false)
         .field("formats", "``` Displays all formatting options & styles ```", false)
-        .field("insights", "``` Sends a code block to cppinsights.io ```", false);
+        .field("insights", "``` Sends a code block to cppinsights.io ```", false)
+        .field("status", "``` Displays the health of the compilers & services used ```", false);
 
     embeds::reply_embed(&ctx.http, msg, embed).await?;
 
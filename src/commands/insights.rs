@@ -72,19 +72,21 @@ pub async fn handle_request(
     let resp = insights_api.get_insights(req).await;
     discordhelpers::delete_bot_reacts(&ctx, msg, loading_reaction).await?;
 
-    if let Some(resp_obj) = resp {
-        debug!("Insights response retval: {}", resp_obj.return_code);
-        let details = CompilationDetails {
-            language: String::new(),
-            compiler: String::new(),
-            godbolt_base64: None,
-            success: resp_obj.return_code == 0,
-            executed: true,
-        };
-        Ok((details, build_insights_response_embed(&author, resp_obj)))
-    } else {
-        Err(CommandError::from(
-            "Unable to retrieve insights at this time! Please try again later.",
-        ))
-    }
+    let resp_obj = resp.map_err(CommandError::from)?;
+    debug!("Insights response retval: {}", resp_obj.return_code);
+    let details = CompilationDetails {
+        language: String::new(),
+        compiler: String::new(),
+        godbolt_base64: None,
+        success: resp_obj.return_code == 0,
+        executed: true,
+        exec_time: None,
+        assembly: None,
+        stdout: None,
+        signal: None,
+        compiler_log: None,
+        counts: None,
+        service: None,
+    };
+    Ok((details, build_insights_response_embed(&author, resp_obj)))
 }
@@ -12,23 +12,19 @@ pub async fn languages(ctx: &Context, msg: &Message, _args: Args) -> CommandResu
     let compiler_cache = data_read.get::<CompilerCache>().unwrap();
     let compiler_manager = compiler_cache.read().await;
 
-    let mut items = Vec::new();
-    if let Some(godbolt) = compiler_manager.godbolt() {
-        for cache_entry in &godbolt.cache {
-            items.push(format!("{}*", cache_entry.language.id));
-        }
-    }
-    if let Some(wandbox) = compiler_manager.wandbox() {
-        let langs = wandbox.get_languages();
-        for lang in langs {
-            if !items.contains(&lang.name) && !items.contains(&format!("{}*", &lang.name)) {
-                items.push(lang.name.clone());
+    let mut items: Vec<String> = Vec::new();
+    for backend in compiler_manager.backends() {
+        for language in backend.languages() {
+            let display = if backend.produces_assembly() {
+                format!("{}*", language)
+            } else {
+                language.clone()
+            };
+            if !items.contains(&language) && !items.contains(&format!("{}*", &language)) {
+                items.push(display);
             }
         }
     }
-    if compiler_manager.sourcepawn().is_some() {
-        items.push(String::from("sourcepawn*"));
-    }
 
     let avatar = {
         let data_read = ctx.data.read().await;
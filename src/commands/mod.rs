@@ -1,10 +1,15 @@
 pub mod asm;
+pub mod asmdiff;
 pub mod asmdoc;
+pub mod bench;
+pub mod bisect;
 pub mod block;
 pub mod botinfo;
+pub mod compare;
 pub mod compile;
 pub mod compilers;
 pub mod cpp;
+pub mod dump;
 pub mod execute;
 pub mod format;
 pub mod formats;
@@ -14,4 +19,8 @@ pub mod invite;
 pub mod languages;
 pub mod libraries;
 pub mod ping;
+pub mod routing;
+pub mod status;
+pub mod test;
+pub mod tool;
 pub mod unblock;
@@ -0,0 +1,30 @@
+use serenity::framework::standard::{macros::command, Args, CommandError, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::cache::CompilerCache;
+
+#[command]
+#[owners_only]
+pub async fn routing(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let routing = {
+        let data = ctx.data.read().await;
+        let compiler_manager = data.get::<CompilerCache>().unwrap().read().await;
+        compiler_manager.routing().clone()
+    };
+
+    let reply = match args.current() {
+        None => {
+            let json = serde_json::to_string_pretty(routing.get().as_ref())?;
+            format!("```json\n{}\n```", json)
+        }
+        Some("reload") => {
+            routing.reload().map_err(CommandError::from)?;
+            String::from("Routing config reloaded")
+        }
+        Some(_) => return Err(CommandError::from("Usage: `;routing [reload]`")),
+    };
+
+    msg.channel_id.say(&ctx.http, reply).await?;
+    Ok(())
+}
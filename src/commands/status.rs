@@ -0,0 +1,20 @@
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+
+use crate::cache::HealthCache;
+use crate::utls::discordhelpers::embeds;
+
+#[command]
+pub async fn status(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let statuses = {
+        let data = ctx.data.read().await;
+        data.get::<HealthCache>().unwrap().statuses()
+    };
+
+    let emb = embeds::build_status_embed(&statuses);
+    embeds::reply_embed(&ctx.http, msg, emb).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
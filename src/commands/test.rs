@@ -0,0 +1,141 @@
+use futures_util::future::join_all;
+use serenity::client::Context;
+use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::cache::CompilerCache;
+use crate::commands::compile::{get_loading_reaction, parse_request};
+use crate::utls::constants::CODE_BLOCK_REGEX;
+use crate::utls::discordhelpers::{embeds, LoadingStatus};
+use crate::utls::test_cases::{diff_output, CaseOutcome, Comparison, TestCase};
+
+/// Most input/expected pairs a single ;test may run
+const MAX_TEST_CASES: usize = 10;
+
+#[command]
+#[bucket = "nospam"]
+pub async fn test(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let (cases, content) = split_cases(&msg.content)?;
+    let mut parse_result = parse_request(ctx, msg, &msg.author, &content).await?;
+    let comparison = Comparison::take(&mut parse_result.options)?;
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    let results = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        let runs = cases.iter().map(|case| {
+            let mut request = parse_result.clone();
+            request.stdin = case.input.clone();
+            let compilation_manager = &compilation_manager_lock;
+            let author = &msg.author;
+            let updates = status.updates();
+            async move {
+                compilation_manager
+                    .execute(&request, author, true, updates)
+                    .await
+            }
+        });
+        join_all(runs).await
+    };
+
+    status.finish().await;
+    let results = results.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+    // When nothing ran, it's most likely a build error, so show it
+    if results.iter().all(|result| !result.details.success) {
+        let first = results.into_iter().next().unwrap();
+        embeds::reply_embed(&ctx.http, msg, first.embed).await?;
+        return Ok(());
+    }
+
+    let outcomes: Vec<CaseOutcome> = cases
+        .iter()
+        .zip(&results)
+        .map(|(case, result)| {
+            let actual = result.details.stdout.as_deref().unwrap_or_default();
+            if !result.details.success {
+                CaseOutcome::Failed
+            } else if comparison.matches(&case.expected, actual) {
+                CaseOutcome::Passed
+            } else {
+                CaseOutcome::WrongAnswer {
+                    diff: diff_output(&case.expected, actual),
+                }
+            }
+        })
+        .collect();
+
+    let emb = embeds::build_test_embed(&msg.author, &results[0].details, &outcomes);
+    embeds::reply_embed(&ctx.http, msg, emb).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// Take the labelled ```input and ```expected blocks out of the message,
+/// pairing them up in order. Returns the cases and the message left over.
+pub fn split_cases(content: &str) -> Result<(Vec<TestCase>, String), CommandError> {
+    let mut cases = Vec::new();
+    let mut input: Option<String> = None;
+    let mut remaining = String::new();
+    let mut last = 0;
+
+    for cap in CODE_BLOCK_REGEX.captures_iter(content) {
+        let label = cap
+            .name("language")
+            .map(|lang| lang.as_str().to_lowercase());
+        let code = cap.name("code").unwrap().as_str().to_string();
+        match label.as_deref() {
+            Some("input") if input.is_none() => input = Some(code),
+            Some("expected") => {
+                let input = input.take().ok_or_else(|| {
+                    CommandError::from(
+                        "Every \\`\\`\\`expected block needs an \\`\\`\\`input block before it.",
+                    )
+                })?;
+                cases.push(TestCase {
+                    input,
+                    expected: code,
+                });
+            }
+            Some("input") => {
+                return Err(CommandError::from(
+                    "Every \\`\\`\\`input block needs an \\`\\`\\`expected block after it.",
+                ))
+            }
+            _ => continue,
+        }
+
+        let block = cap.get(0).unwrap();
+        remaining.push_str(&content[last..block.start()]);
+        last = block.end();
+    }
+    remaining.push_str(&content[last..]);
+
+    if input.is_some() {
+        return Err(CommandError::from(
+            "Every \\`\\`\\`input block needs an \\`\\`\\`expected block after it.",
+        ));
+    }
+    if cases.is_empty() {
+        return Err(CommandError::from(
+            "Supply at least one test case as an \\`\\`\\`input block followed by an \\`\\`\\`expected block.",
+        ));
+    }
+    if cases.len() > MAX_TEST_CASES {
+        return Err(CommandError::from(format!(
+            "A test run takes at most {} cases.",
+            MAX_TEST_CASES
+        )));
+    }
+
+    Ok((cases, remaining))
+}
@@ -0,0 +1,132 @@
+use serenity::all::{CreateActionRow, CreateMessage};
+use serenity::client::Context;
+use serenity::framework::standard::CommandError;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::channel::Message;
+
+use crate::cache::{CompilerCache, ConfigCache};
+use crate::commands::compile::{build_link_button, get_loading_reaction, parse_request};
+use crate::utls::discordhelpers::menu::Menu;
+use crate::utls::discordhelpers::LoadingStatus;
+use crate::utls::{discordhelpers, parser};
+
+#[command]
+#[aliases("tools")]
+#[bucket = "nospam"]
+pub async fn tool(ctx: &Context, msg: &Message, _args: Args) -> CommandResult {
+    let (loading_reaction, compilation_manager) = {
+        let data = ctx.data.read().await;
+        let reaction = get_loading_reaction(&data).await?;
+        let comp_mgr = data.get::<CompilerCache>().unwrap().clone();
+        (reaction, comp_mgr)
+    };
+
+    let (tool, content) = split_tool(&msg.content)?;
+    if tool == "list" {
+        return list_tools(ctx, msg, &content).await;
+    }
+
+    // Arguments for the tool go on the line after the command, where a
+    // program's arguments normally would
+    let parse_result = parse_request(ctx, msg, &msg.author, &content).await?;
+    let tool_args = parse_result.args.join(" ");
+
+    let status = LoadingStatus::start(ctx, msg, loading_reaction).await?;
+
+    let result = {
+        let compilation_manager_lock = compilation_manager.read().await;
+        compilation_manager_lock
+            .run_tool(
+                &parse_result,
+                &msg.author,
+                &tool,
+                &tool_args,
+                status.updates(),
+            )
+            .await
+    };
+
+    status.finish().await;
+    let result = result?;
+
+    let mut new_msg = discordhelpers::reply_to(msg, CreateMessage::new().embed(result.embed));
+    let buttons = build_link_button(ctx, &result.details).await;
+    if !buttons.is_empty() {
+        new_msg = new_msg.components(vec![CreateActionRow::Buttons(buttons)]);
+    }
+
+    let sent = msg.channel_id.send_message(&ctx.http, new_msg).await?;
+    discordhelpers::send_completion_react(ctx, &sent, result.details.success).await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// ;tool list <compiler|language>
+async fn list_tools(ctx: &Context, msg: &Message, content: &str) -> CommandResult {
+    let target = content.split_whitespace().nth(1).ok_or_else(|| {
+        CommandError::from(
+            "No compiler or language specified!\nPlease try giving me one to search, i.e. ;tool list c++",
+        )
+    })?;
+
+    let data_read = ctx.data.read().await;
+    let tool_list = {
+        let compiler_cache_lock = data_read
+            .get::<CompilerCache>()
+            .expect("Expected CompilerCache.")
+            .clone();
+        let cache = compiler_cache_lock.read().await;
+        cache.get_tool_list(target).await?
+    };
+
+    if tool_list.is_empty() {
+        return Err(CommandError::from(format!(
+            "No tools are available for '{}'.",
+            target
+        )));
+    }
+
+    let avatar = {
+        let botinfo_lock = data_read
+            .get::<ConfigCache>()
+            .expect("Expected BotInfo in global cache")
+            .clone();
+        let botinfo = botinfo_lock.read().await;
+        botinfo.get("BOT_AVATAR").unwrap().clone()
+    };
+
+    let pages = discordhelpers::build_menu_items(
+        tool_list,
+        15,
+        "Supported Tools",
+        &avatar,
+        &msg.author.name,
+        "*Run a tool with `;tool <tool> <compiler>`, putting its arguments on the next line*",
+    );
+    let mut menu = Menu::new(ctx, msg, &pages);
+    menu.run().await?;
+
+    debug!("Command executed");
+    Ok(())
+}
+
+/// Take the tool id off the front of the command, returning it with the
+/// rest of the message
+pub fn split_tool(content: &str) -> Result<(String, String), CommandError> {
+    let content = parser::strip_mention(content);
+    let end_point = content.find(['\n', '`']).unwrap_or(content.len());
+    let mut params = content[..end_point].split_whitespace().skip(1);
+
+    let tool = params.next().map(str::to_lowercase).ok_or_else(|| {
+        CommandError::from(
+            "Supply the tool to run.\n\nUsage: `;tool <tool> <compiler> [options]` followed by a code block, or `;tool list <compiler>` to see the available tools",
+        )
+    })?;
+
+    let rest: Vec<&str> = params.collect();
+    Ok((
+        tool,
+        format!(";tool {}{}", rest.join(" "), &content[end_point..]),
+    ))
+}
@@ -12,7 +12,7 @@ use serenity::{
 
 use crate::{
     cache::*,
-    managers::command::CommandManager,
+    managers::{command::CommandManager, settings},
     utls::{discordhelpers, discordhelpers::embeds, discordhelpers::interactions::send_error_msg},
 };
 
@@ -244,6 +244,30 @@ impl EventHandler for Handler {
     }
 }
 
+/// Finds which of the guild's command prefixes a message starts with. The
+/// framework only checks the one prefix returned, so a guild with several
+/// has to be matched here.
+#[hook]
+pub async fn dynamic_prefix(ctx: &Context, msg: &Message) -> Option<String> {
+    let data = ctx.data.read().await;
+    let default = data
+        .get::<ConfigCache>()
+        .unwrap()
+        .read()
+        .await
+        .get("BOT_PREFIX")
+        .unwrap()
+        .to_owned();
+    let prefixes = data
+        .get::<SettingsCache>()
+        .unwrap()
+        .read()
+        .await
+        .prefixes(msg.guild_id.map(|id| id.get()), &default);
+
+    settings::find_prefix(&prefixes, &msg.content).map(String::from)
+}
+
 #[hook]
 pub async fn before(ctx: &Context, msg: &Message, _: &str) -> bool {
     let guild_id = msg.guild_id.map(|id| id.get()).unwrap_or(0);
@@ -1,6 +1,7 @@
 #![type_length_limit = "1146253"]
 
 mod apis;
+mod backends;
 mod boilerplate;
 mod cache;
 mod commands;
@@ -16,7 +17,7 @@ use serenity::framework::{standard::macros::group, StandardFramework};
 
 use serenity::all::standard::{BucketBuilder, Configuration};
 use serenity::http::Http;
-use serenity::model::id::ApplicationId;
+use serenity::model::id::{ApplicationId, UserId};
 use serenity::prelude::GatewayIntents;
 use std::collections::HashSet;
 use std::{env, error::Error};
@@ -29,10 +30,12 @@ extern crate pretty_env_logger;
 
 /** Command Registration **/
 use crate::commands::{
-    asm::*, asmdoc::*, block::*, botinfo::*, compile::*, compilers::*, cpp::*, execute::*,
-    format::*, formats::*, help::*, insights::*, invite::*, languages::*, libraries::*, ping::*,
-    unblock::*,
+    asm::*, asmdiff::*, asmdoc::*, bench::*, bisect::*, block::*, botinfo::*, compare::*,
+    compile::*, compilers::*, cpp::*, dump::*, execute::*, format::*, formats::*, help::*,
+    insights::*, invite::*, languages::*, libraries::*, ping::*, routing::*, status::*, test::*,
+    tool::*, unblock::*,
 };
+use crate::managers::compilation::CompilationManager;
 use crate::managers::stats::StatsManager;
 use crate::utls::discordhelpers::embeds::panic_embed;
 use crate::utls::discordhelpers::manual_dispatch;
@@ -40,7 +43,8 @@ use crate::utls::discordhelpers::manual_dispatch;
 #[group]
 #[commands(
     botinfo, compile, execute, languages, compilers, libraries, ping, help, asm, asmdoc, block,
-    unblock, invite, cpp, formats, format, insights
+    unblock, invite, cpp, formats, format, insights, routing, status, compare, bisect, bench, tool,
+    ir, ast, remarks, gccdump, asmdiff, test
 )]
 struct General;
 
@@ -96,7 +100,13 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let app_id_str = env::var("APPLICATION_ID").expect("Expected application id in .env file");
     let application_id = ApplicationId::new(app_id_str.parse::<u64>().unwrap());
 
-    let configuration = Configuration::new().owners(owners).prefix(&prefix);
+    // BOT_PREFIX is resolved per guild by events::dynamic_prefix, so a
+    // guild's own prefixes can replace it
+    let configuration = Configuration::new()
+        .owners(owners)
+        .prefix("")
+        .on_mention(Some(UserId::new(bot_id.get())))
+        .dynamic_prefix(events::dynamic_prefix);
     let mut framework = StandardFramework::new()
         .group(&GENERAL_GROUP)
         .before(events::before)
@@ -132,6 +142,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
     .await?;
 
     StatsManager::spawn_flusher(client.data.clone());
+    CompilationManager::spawn_refresher(client.data.clone());
     if let Ok(plog) = env::var("PANIC_LOG") {
         let default_panic = std::panic::take_hook();
         let http = client.http.clone();
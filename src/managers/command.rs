@@ -1,6 +1,8 @@
 use crate::slashcmds;
 
-use serenity::all::{Command, CommandInteraction, CommandType, CreateCommand};
+use serenity::all::{
+    Command, CommandInteraction, CommandOptionType, CommandType, CreateCommand, CreateCommandOption,
+};
 use serenity::{client::Context, framework::standard::CommandResult, model::guild::Guild};
 
 pub struct CommandManager {
@@ -23,6 +25,8 @@ impl CommandManager {
             "ping" => slashcmds::ping::ping(ctx, command).await,
             "help" => slashcmds::help::help(ctx, command).await,
             "invite" => slashcmds::invite::invite(ctx, command).await,
+            "status" => slashcmds::status::status(ctx, command).await,
+            "settings" => slashcmds::settings::settings(ctx, command).await,
             "format" | "format [beta]" => slashcmds::format::format(ctx, command).await,
             "diff" | "diff [beta]" => slashcmds::diff_msg::diff_msg(ctx, command).await,
             e => {
@@ -97,6 +101,54 @@ impl CommandManager {
                 .kind(CommandType::ChatInput),
         );
 
+        cmds.push(
+            CreateCommand::new("status")
+                .description("Check the health of the compilers and services I use")
+                .kind(CommandType::ChatInput),
+        );
+
+        cmds.push(
+            CreateCommand::new("settings")
+                .description("View or change your defaults, or your server's")
+                .kind(CommandType::ChatInput)
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "view",
+                        "Show the current settings",
+                    )
+                    .add_sub_option(scope_option()),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "set",
+                        "Change a setting",
+                    )
+                    .add_sub_option(scope_option().required(true))
+                    .add_sub_option(setting_option().required(true))
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::String,
+                            "value",
+                            "Compiler id, flags, summary/raw for output, a language, prefixes or asm flags",
+                        )
+                        .required(true),
+                    )
+                    .add_sub_option(language_option()),
+                )
+                .add_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::SubCommand,
+                        "reset",
+                        "Go back to the defaults",
+                    )
+                    .add_sub_option(scope_option().required(true))
+                    .add_sub_option(setting_option())
+                    .add_sub_option(language_option()),
+                ),
+        );
+
         cmds.push(
             CreateCommand::new("ping")
                 .description("Test my ping to Discord's endpoint")
@@ -106,3 +158,35 @@ impl CommandManager {
         cmds
     }
 }
+
+fn scope_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "scope",
+        "Your own settings, or the server's (requires Manage Server)",
+    )
+    .add_string_choice("me", "me")
+    .add_string_choice("server", "server")
+}
+
+fn setting_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "setting",
+        "The setting to change",
+    )
+    .add_string_choice("compiler", "compiler")
+    .add_string_choice("flags", "flags")
+    .add_string_choice("output", "output")
+    .add_string_choice("language", "language")
+    .add_string_choice("prefix", "prefix")
+    .add_string_choice("asm", "asm")
+}
+
+fn language_option() -> CreateCommandOption {
+    CreateCommandOption::new(
+        CommandOptionType::String,
+        "language",
+        "Language a compiler or flags are for, e.g. c++",
+    )
+}
@@ -1,17 +1,39 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::error::Error;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use serenity::builder::CreateEmbed;
 use serenity::framework::standard::CommandError;
 use serenity::model::user::User;
+use serenity::prelude::TypeMap;
+use tokio::sync::{Mutex, RwLock, SemaphorePermit};
 
 use crate::apis::godbolt::GodboltService;
+use crate::apis::piston::PistonService;
 use crate::apis::sourcepawn::SourcePawnService;
-use crate::apis::wandbox::WandboxService;
-use crate::boilerplate::generator::boilerplate_factory;
-use crate::utls::constants::JAVA_PUBLIC_CLASS_REGEX;
-use crate::utls::discordhelpers::embeds::{EmbedOptions, ToEmbed};
+use crate::backends::backend::{
+    is_unavailable, BackendUnavailable, CompilerBackend, ResolvedTarget,
+};
+use crate::backends::godbolt::{program_stderr, DumpKind, GodboltBackend};
+use crate::backends::local::LocalBackend;
+use crate::backends::piston::PistonBackend;
+use crate::backends::routing::{Routing, RoutingConfig};
+use crate::backends::sourcepawn::SourcePawnBackend;
+use crate::backends::wandbox::WandboxBackend;
+use crate::cache::CompilerCache;
+use crate::managers::health::HealthManager;
+use crate::managers::stats::StatsManager;
+use crate::utls::backend_queue::{BackendQueue, QueueLimits, QueueUpdates};
+use crate::utls::discordhelpers::embeds;
 use crate::utls::parser::{shortname_to_qualified, ParserResult};
-use godbolt::{CompilationFilters, CompilerOptions, LibrarySelection, PpOptions, RequestOptions};
+use crate::utls::presets::{self, Preset, Toolchain};
+use crate::utls::result_cache::{CachedResult, Lookup, ResultCache};
+use crate::utls::sandbox::{self, SandboxLimits, SandboxUsers};
 
 /// Information about a compilation that callers may need
 #[derive(Default, Clone)]
@@ -26,6 +48,21 @@ pub struct CompilationDetails {
     pub success: bool,
     /// Whether this result includes program execution
     pub executed: bool,
+    /// How long the program ran in milliseconds, if the backend reports it
+    pub exec_time: Option<i64>,
+    /// The assembly listing, for assembly requests
+    pub assembly: Option<String>,
+    /// What the program wrote to stdout, for executions
+    pub stdout: Option<String>,
+    /// The signal that killed the program (e.g., "SIGSEGV"), if it crashed
+    pub signal: Option<&'static str>,
+    /// The compiler's full output, to attach when the embed only has room
+    /// for its first diagnostics
+    pub compiler_log: Option<String>,
+    /// Diagnostic counts shown in the footer (e.g., "1 error, 2 warnings")
+    pub counts: Option<String>,
+    /// The service named at the end of the footer (e.g., "godbolt.org")
+    pub service: Option<&'static str>,
 }
 
 /// The result of a compilation request, containing everything needed to display to the user
@@ -34,53 +71,96 @@ pub struct CompilationResult {
     pub embed: CreateEmbed,
 }
 
-/// Which backend service handles a given language
-enum Backend {
-    CompilerExplorer,
-    WandBox,
-    SourcePawn,
-}
+/// How often loaded catalogs are fetched again to pick up new compilers
+const CATALOG_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 
-/// What kind of request to send to Compiler Explorer
-#[derive(Clone, Copy, PartialEq)]
-enum GodboltMode {
-    Execute,
-    Check,
-    Assembly,
-}
+/// First retry delay for a catalog that failed to load; doubles on every
+/// failure up to CATALOG_RETRY_MAX
+const CATALOG_RETRY_MIN: Duration = Duration::from_secs(30);
+const CATALOG_RETRY_MAX: Duration = Duration::from_secs(30 * 60);
+
+/// How many finished compilations are kept, and for how long, so identical
+/// requests can be answered without another upstream call
+const RESULT_CACHE_SIZE: usize = 256;
+const RESULT_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
 
 /// Manages compilation requests across multiple backend services (Godbolt, WandBox).
 ///
-/// Resolution order:
-/// 1. sourcepawn goes to the self-hosted SourcePawn service, if configured
-/// 2. Some languages are hardcoded to WandBox (scala, nim, typescript, javascript)
-/// 3. If Compiler Explorer supports the target, use it
-/// 4. Fall back to WandBox
+/// Resolution order, after applying any alias from the routing config:
+/// 1. The target's preferred backend from the routing config, if registered
+/// 2. The first backend, in priority order, that resolves the target to a
+///    compiler and language the routing config doesn't deny it
+///
+/// Compile and execute requests fail over to the next backend supporting the
+/// same language when the chosen one is unavailable. Their results are cached
+/// for RESULT_CACHE_TTL, and identical requests that are still running are
+/// answered by the first one's upstream call.
+///
+/// Every backend has a bounded queue (see `utls::backend_queue`) limiting how
+/// many requests run against it at once, configured with BACKEND_CONCURRENCY
+/// and BACKEND_QUEUE. Requests arriving at a full queue are rejected.
+///
+/// Every backend request is recorded with the health tracker, and backends
+/// whose circuit is open are treated as unavailable without being contacted.
+///
+/// The default priority is sourcepawn (if configured), godbolt, wandbox, piston
+/// (if configured), local (if LOCAL_SANDBOX is set) and may be changed with
+/// BACKEND_PRIORITY (e.g. `local,piston,godbolt,wandbox`).
 pub struct CompilationManager {
-    wandbox: Option<WandboxService>,
-    godbolt: Option<GodboltService>,
-    sourcepawn: Option<SourcePawnService>,
+    backends: Vec<Arc<dyn CompilerBackend>>,
+    /// Operator-editable routing rules, see `backends::routing`
+    routing: Arc<Routing>,
+    /// Recent compile and execute results, see `utls::result_cache`
+    results: ResultCache,
+    limits: QueueLimits,
+    /// Concurrency limit and wait queue of each backend, keyed by id
+    queues: HashMap<&'static str, BackendQueue>,
+    /// Also tracks services outside the manager, see `cache::HealthCache`
+    health: Arc<HealthManager>,
+    /// Where failovers are recorded, if attached
+    stats: Option<Arc<Mutex<StatsManager>>>,
+}
+
+/// A request `CompilationManager::dispatch` can send to any backend
+#[derive(Clone, Copy, Hash)]
+enum Operation {
+    Compile,
+    Execute { boilerplate: bool },
 }
 
 impl CompilationManager {
     /// Check if a target (language or compiler) is supported by any backend
     pub fn is_target_supported(&self, target: &str) -> bool {
-        self.resolve_backend(target).is_some()
+        let routing = self.routing.get();
+        routing.preferred_backend(routing.alias(target)).is_some()
+            || self.resolve_backend(target).is_some()
     }
 
-    /// Get reference to Godbolt instance, if available
-    pub fn godbolt(&self) -> Option<&GodboltService> {
-        self.godbolt.as_ref()
+    /// Success rates, latencies and circuit state of every backend
+    pub fn health(&self) -> &Arc<HealthManager> {
+        &self.health
     }
 
-    /// Get reference to WandBox instance, if available
-    pub fn wandbox(&self) -> Option<&WandboxService> {
-        self.wandbox.as_ref()
+    /// The active routing rules
+    pub fn routing(&self) -> &Arc<Routing> {
+        &self.routing
     }
 
-    /// Get reference to the SourcePawn service, if configured
-    pub fn sourcepawn(&self) -> Option<&SourcePawnService> {
-        self.sourcepawn.as_ref()
+    /// Get the current Godbolt instance, if its catalog has been loaded
+    pub fn godbolt(&self) -> Option<Arc<GodboltService>> {
+        self.backend::<GodboltBackend>().and_then(|gb| gb.service())
+    }
+
+    /// Get reference to a registered backend by its concrete type
+    pub fn backend<T: CompilerBackend + 'static>(&self) -> Option<&T> {
+        self.backends
+            .iter()
+            .find_map(|backend| backend.as_any().downcast_ref::<T>())
+    }
+
+    /// Every registered backend, in priority order
+    pub fn backends(&self) -> impl Iterator<Item = &dyn CompilerBackend> {
+        self.backends.iter().map(|backend| backend.as_ref())
     }
 }
 
@@ -88,60 +168,201 @@ impl CompilationManager {
     pub async fn new() -> Result<Self, Box<dyn Error>> {
         let http = crate::apis::HTTP_CLIENT.clone();
 
-        let mut broken_compilers = std::collections::HashSet::new();
-        broken_compilers.insert(String::from("ghc-head"));
-        broken_compilers.insert(String::from("go-head"));
+        let routing = Arc::new(Routing::from_env());
 
-        let mut broken_languages = std::collections::HashSet::new();
-        broken_languages.insert(String::from("cpp"));
+        let mut manager = CompilationManager {
+            backends: Vec::new(),
+            routing: routing.clone(),
+            results: ResultCache::new(RESULT_CACHE_SIZE, RESULT_CACHE_TTL),
+            limits: QueueLimits::from_env(),
+            queues: HashMap::new(),
+            health: Arc::new(HealthManager::new()),
+            stats: None,
+        };
 
-        let wandbox =
-            match WandboxService::new(http.clone(), broken_compilers, broken_languages).await {
-                Ok(wb) => Some(wb),
+        if let Ok(url) = std::env::var("SOURCEPAWN_API_URL") {
+            match SourcePawnService::new(http.clone(), &url).await {
+                Ok(sp) => {
+                    info!("SourcePawn service loaded ({})", sp.compiler_name());
+                    manager.register(Box::new(SourcePawnBackend::new(sp)));
+                }
                 Err(e) => {
-                    error!("Unable to load WandBox: {}", e);
-                    None
+                    error!("Unable to load SourcePawn service at '{}': {}", url, e);
                 }
-            };
+            }
+        }
 
-        let godbolt = match GodboltService::new(http.clone()).await {
-            Ok(gb) => Some(gb),
-            Err(e) => {
-                error!("Unable to load Compiler Explorer: {}", e);
-                None
+        // Both stay registered if loading fails; spawn_refresher retries them
+        let godbolt = GodboltBackend::default();
+        if let Err(e) = godbolt.refresh().await {
+            error!("Unable to load Compiler Explorer: {}", e);
+        }
+        manager.register(Box::new(godbolt));
+
+        let wandbox = WandboxBackend::new(routing);
+        if let Err(e) = wandbox.refresh().await {
+            error!("Unable to load WandBox: {}", e);
+        }
+        manager.register(Box::new(wandbox));
+
+        if let Ok(url) = std::env::var("PISTON_API_URL") {
+            match PistonService::new(http, &url).await {
+                Ok(piston) => {
+                    info!(
+                        "Piston service loaded ({} runtimes)",
+                        piston.get_runtimes().len()
+                    );
+                    manager.register(Box::new(PistonBackend::new(piston)));
+                }
+                Err(e) => {
+                    error!("Unable to load Piston service at '{}': {}", url, e);
+                }
             }
-        };
+        }
 
-        let sourcepawn = match std::env::var("SOURCEPAWN_API_URL") {
-            Ok(url) => match SourcePawnService::new(http, &url).await {
-                Ok(sp) => {
-                    info!("SourcePawn service loaded ({})", sp.compiler_name());
-                    Some(sp)
+        if std::env::var("LOCAL_SANDBOX").is_ok_and(|v| v == "1" || v == "true") {
+            let users = sandbox::check_config_permissions().and_then(|_| SandboxUsers::from_env());
+            match users {
+                Ok(users) => {
+                    let limits = SandboxLimits::from_env();
+                    let local = LocalBackend::new(limits, users, sandbox::mounts_from_env());
+                    info!(
+                        "Local sandbox loaded ({})",
+                        local.compiler_names().join(", ")
+                    );
+                    manager.register(Box::new(local));
                 }
                 Err(e) => {
-                    error!("Unable to load SourcePawn service at '{}': {}", url, e);
-                    None
+                    error!("Unable to load local sandbox: {}", e);
                 }
-            },
-            Err(_) => None,
+            }
+        }
+
+        if let Ok(priority) = std::env::var("BACKEND_PRIORITY") {
+            let order: Vec<&str> = priority.split(',').map(|id| id.trim()).collect();
+            manager.set_priority(&order);
+        }
+
+        Ok(manager)
+    }
+
+    /// Manager with the given backends (in priority order) and nothing else
+    #[cfg(test)]
+    pub fn with_backends(backends: Vec<Box<dyn CompilerBackend>>) -> Self {
+        let mut manager = CompilationManager {
+            backends: Vec::new(),
+            routing: Arc::new(Routing::with_config(RoutingConfig::default())),
+            results: ResultCache::new(RESULT_CACHE_SIZE, RESULT_CACHE_TTL),
+            limits: QueueLimits::default(),
+            queues: HashMap::new(),
+            health: Arc::new(HealthManager::new()),
+            stats: None,
         };
+        for backend in backends {
+            manager.register(backend);
+        }
+        manager
+    }
 
-        Ok(CompilationManager {
-            wandbox,
-            godbolt,
-            sourcepawn,
-        })
+    /// Replace the concurrency limit and queue size of a test manager's backend
+    #[cfg(test)]
+    pub fn with_queue(mut self, id: &'static str, concurrency: usize, max_waiting: usize) -> Self {
+        self.queues
+            .insert(id, BackendQueue::new(concurrency, max_waiting));
+        self
+    }
+
+    /// Replace the routing rules of a test manager
+    #[cfg(test)]
+    pub fn with_routing(mut self, config: RoutingConfig) -> Self {
+        self.routing = Arc::new(Routing::with_config(config));
+        self
+    }
+
+    /// Record failovers with the given stats manager
+    pub fn attach_stats(&mut self, stats: Arc<Mutex<StatsManager>>) {
+        self.stats = Some(stats);
+    }
+
+    /// Add a backend with the lowest priority
+    pub fn register(&mut self, backend: Box<dyn CompilerBackend>) {
+        self.queues
+            .insert(backend.id(), self.limits.queue_for(backend.id()));
+        self.health.register(backend.display_name());
+        self.backends.push(Arc::from(backend));
+    }
+
+    /// Keeps every backend's catalog up to date in the background. Backends
+    /// that failed to load are retried with exponential backoff; loaded ones
+    /// are refreshed every CATALOG_REFRESH_INTERVAL. Refreshed services are
+    /// swapped in by the backends themselves, so the CompilerCache lock is
+    /// never held for writing.
+    pub fn spawn_refresher(data: Arc<RwLock<TypeMap>>) {
+        tokio::spawn(async move {
+            let backends = {
+                let read = data.read().await;
+                let manager = read.get::<CompilerCache>().unwrap().read().await;
+                manager.backends.clone()
+            };
+
+            for backend in backends {
+                tokio::spawn(CompilationManager::refresh_loop(backend));
+            }
+        });
+    }
+
+    async fn refresh_loop(backend: Arc<dyn CompilerBackend>) {
+        let mut retry = CATALOG_RETRY_MIN;
+        let mut failed = false;
+        loop {
+            let delay = if failed || !backend.is_available() {
+                retry
+            } else {
+                CATALOG_REFRESH_INTERVAL
+            };
+            tokio::time::sleep(delay).await;
+
+            match backend.refresh().await {
+                Ok(()) => {
+                    failed = false;
+                    retry = CATALOG_RETRY_MIN;
+                }
+                Err(e) => {
+                    warn!(
+                        "Unable to refresh {} catalog, retrying in {}s: {}",
+                        backend.id(),
+                        retry.as_secs(),
+                        e
+                    );
+                    failed = true;
+                    retry = std::cmp::min(retry * 2, CATALOG_RETRY_MAX);
+                }
+            }
+        }
+    }
+
+    /// Reorder backends by id. Backends missing from `order` keep their
+    /// relative order after the listed ones.
+    pub fn set_priority(&mut self, order: &[&str]) {
+        self.backends.sort_by_key(|backend| {
+            order
+                .iter()
+                .position(|id| *id == backend.id())
+                .unwrap_or(order.len())
+        });
     }
 
     /// Compile code without executing it and return a result ready for display.
     /// WandBox targets fall back to full execution since WandBox cannot skip
-    /// the run step.
+    /// the run step. The request's queue position is reported to `updates`
+    /// while it waits for a busy backend.
     pub async fn compile(
         &self,
         request: &ParserResult,
         author: &User,
+        updates: Option<&QueueUpdates>,
     ) -> Result<CompilationResult, CommandError> {
-        self.dispatch(request, author, GodboltMode::Check, false)
+        self.dispatch(request, author, Operation::Compile, updates)
             .await
     }
 
@@ -153,322 +374,530 @@ impl CompilationManager {
         request: &ParserResult,
         author: &User,
         boilerplate: bool,
+        updates: Option<&QueueUpdates>,
     ) -> Result<CompilationResult, CommandError> {
-        self.dispatch(request, author, GodboltMode::Execute, boilerplate)
-            .await
+        let operation = Operation::Execute { boilerplate };
+        self.dispatch(request, author, operation, updates).await
     }
 
-    async fn dispatch(
+    /// Execute code `runs` times in a row for ;bench. Every run goes to the
+    /// backend resolving the target, skipping the result cache and failover
+    /// so that all timings come from the same compiler. Stops at the first
+    /// run that fails.
+    pub async fn benchmark(
         &self,
         request: &ParserResult,
         author: &User,
-        mode: GodboltMode,
-        boilerplate: bool,
-    ) -> Result<CompilationResult, CommandError> {
-        match self.resolve_backend(&request.target) {
-            Some(Backend::CompilerExplorer) => {
-                self.compile_with_godbolt(request, author, mode, boilerplate)
-                    .await
-            }
-            Some(Backend::WandBox) => {
-                self.compile_with_wandbox(request, author, boilerplate)
-                    .await
-            }
-            Some(Backend::SourcePawn) => {
-                self.compile_with_sourcepawn(request, author, mode, boilerplate)
-                    .await
-            }
-            None => {
-                let target = if request.target.starts_with('@') {
-                    format!("\\{}", request.target)
-                } else {
-                    request.target.clone()
-                };
-                Err(CommandError::from(format!(
-                    "Unable to find compiler or language for target '{}'.",
-                    target
-                )))
+        runs: usize,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<Vec<CompilationResult>, CommandError> {
+        let request = self.apply_alias(request);
+        let backend = self.require_backend(&request.target)?;
+        if !backend.reports_exec_time() {
+            return Err(CommandError::from(format!(
+                "{} doesn't report execution times, so it can't be used for benchmarks. \
+                 Try a compiler hosted on godbolt.org instead.",
+                backend.display_name()
+            )));
+        }
+
+        let operation = Operation::Execute { boilerplate: true };
+        let mut results = Vec::with_capacity(runs);
+        for _ in 0..runs {
+            let result = self
+                .run(backend, &request, author, operation, updates)
+                .await?;
+            let success = result.details.success;
+            results.push(result);
+            if !success {
+                break;
             }
         }
+        Ok(results)
     }
 
-    /// Compile code and return assembly output (Godbolt, or pcode disassembly
-    /// for SourcePawn targets).
-    pub async fn assembly(
+    /// Answer a request from the result cache, or send it to the backend
+    /// resolving its target and cache the result. Results produced by a
+    /// failover backend are passed to coalesced requests but not stored.
+    async fn dispatch(
         &self,
         request: &ParserResult,
         author: &User,
+        operation: Operation,
+        updates: Option<&QueueUpdates>,
     ) -> Result<CompilationResult, CommandError> {
-        if let Some(Backend::SourcePawn) = self.resolve_backend(&request.target) {
-            return self
-                .compile_with_sourcepawn(request, author, GodboltMode::Assembly, false)
-                .await;
-        }
-        self.compile_with_godbolt(request, author, GodboltMode::Assembly, false)
+        let request = self.apply_alias(request);
+        let primary = self.require_backend(&request.target)?;
+
+        let flight = match self
+            .results
+            .lookup(Self::cache_key(primary, &request, operation))
+            .await
+        {
+            Lookup::Hit(cached) => return Ok(Self::from_cache(*cached, author)),
+            Lookup::Failed(message) => return Err(CommandError::from(message)),
+            Lookup::Lead(flight) => flight,
+        };
+
+        match self
+            .failover(primary, &request, author, operation, updates)
             .await
+        {
+            Ok((result, failed_over)) => {
+                let cached = CachedResult {
+                    details: result.details.clone(),
+                    embed: result.embed.clone(),
+                };
+                flight.finish(Ok(cached), !failed_over);
+                Ok(result)
+            }
+            Err(e) => {
+                flight.finish(Err(e.to_string()), false);
+                Err(e)
+            }
+        }
     }
 
-    /// Compile using the self-hosted SourcePawn service.
-    async fn compile_with_sourcepawn(
+    /// Identifies requests that must produce the same result
+    fn cache_key(
+        backend: &dyn CompilerBackend,
+        request: &ParserResult,
+        operation: Operation,
+    ) -> u64 {
+        let compiler = backend
+            .resolve(&request.target)
+            .map(|resolved| resolved.compiler)
+            .unwrap_or_else(|| request.target.clone());
+
+        let mut hasher = DefaultHasher::new();
+        backend.id().hash(&mut hasher);
+        compiler.hash(&mut hasher);
+        request.code.hash(&mut hasher);
+        request.options.hash(&mut hasher);
+        request.presets.hash(&mut hasher);
+        request.output.hash(&mut hasher);
+        request.args.hash(&mut hasher);
+        request.stdin.hash(&mut hasher);
+        request.files.hash(&mut hasher);
+        operation.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A cached result with its footer made out to the new requester
+    fn from_cache(cached: CachedResult, author: &User) -> CompilationResult {
+        let footer = embeds::build_cached_footer(author, &cached);
+        CompilationResult {
+            embed: cached.embed.footer(footer),
+            details: cached.details,
+        }
+    }
+
+    /// Send a request to `primary`. If that backend is unavailable (transport
+    /// error, timeout or 5xx), the request is retried on the next backend
+    /// supporting the same language, using that backend's default compiler
+    /// for it. The flag is set if the result came from such a backend.
+    async fn failover(
         &self,
+        primary: &dyn CompilerBackend,
         request: &ParserResult,
         author: &User,
-        mode: GodboltMode,
-        boilerplate: bool,
-    ) -> Result<CompilationResult, CommandError> {
-        let sourcepawn = self.sourcepawn.as_ref().ok_or_else(|| {
-            CommandError::from(
-                "The SourcePawn service is unavailable. This may be due to an outage. Please try again later.",
-            )
-        })?;
-
-        let code = if boilerplate {
-            boilerplate_generation("sourcepawn", &request.code)
-        } else {
-            request.code.to_owned()
+        operation: Operation,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<(CompilationResult, bool), CommandError> {
+        let error = match self.run(primary, request, author, operation, updates).await {
+            Err(e) if is_unavailable(&e) => e,
+            result => return result.map(|result| (result, false)),
         };
 
-        let asm_mode = mode == GodboltMode::Assembly;
-        let response = sourcepawn
-            .compile(&code, mode == GodboltMode::Execute, asm_mode)
-            .await
-            .map_err(|e| CommandError::from(format!("SourcePawn request failed: {}", e)))?;
-
-        let success = response.compile.success
-            && response.run.as_ref().map(|run| run.success).unwrap_or(true);
-        let details = CompilationDetails {
-            language: String::from("sourcepawn"),
-            compiler: sourcepawn.compiler_name(),
-            godbolt_base64: None,
-            success,
-            executed: mode == GodboltMode::Execute,
+        let language = match primary.resolve(&request.target) {
+            Some(resolved) => resolved.language,
+            None => return Err(error),
         };
 
-        let embed_options = EmbedOptions::new(asm_mode, false, details.clone());
-        let embed = response.to_embed(author, &embed_options);
+        let routing = self.routing.get();
+        for fallback in self.backends().filter(|b| b.id() != primary.id()) {
+            let resolved = match Self::resolve_allowed(&routing, fallback, &request.target)
+                .or_else(|| Self::resolve_allowed(&routing, fallback, &language))
+            {
+                Some(resolved) => resolved,
+                None => continue,
+            };
+
+            warn!(
+                "{} is unavailable, retrying '{}' on {} with {}",
+                primary.id(),
+                request.target,
+                fallback.id(),
+                resolved.compiler
+            );
+
+            let mut request = request.clone();
+            request.target = resolved.compiler;
+            match self
+                .run(fallback, &request, author, operation, updates)
+                .await
+            {
+                Ok(mut result) => {
+                    if let Some(stats) = &self.stats {
+                        stats
+                            .lock()
+                            .await
+                            .record_failover(primary.id(), fallback.id());
+                    }
+
+                    let footer = embeds::build_failover_footer(
+                        author,
+                        &result.details,
+                        primary.display_name(),
+                    );
+                    result.embed = result.embed.footer(footer);
+                    return Ok((result, true));
+                }
+                Err(e) if is_unavailable(&e) => continue,
+                Err(e) => return Err(e),
+            }
+        }
 
-        Ok(CompilationResult { details, embed })
+        Err(error)
     }
 
-    /// Compile using Compiler Explorer (godbolt.org).
-    /// Used by `compile` (check mode), `execute` (execute mode), and
-    /// `assembly` (asm mode).
-    async fn compile_with_godbolt(
+    async fn run(
         &self,
+        backend: &dyn CompilerBackend,
         request: &ParserResult,
         author: &User,
-        mode: GodboltMode,
-        boilerplate: bool,
+        operation: Operation,
+        updates: Option<&QueueUpdates>,
     ) -> Result<CompilationResult, CommandError> {
-        let godbolt = self.godbolt.as_ref().ok_or_else(|| {
-            CommandError::from(
-                "Compiler Explorer is unavailable. This may be due to an outage. Please try again later.",
-            )
-        })?;
+        if request.is_project() && !backend.supports_projects() {
+            return Err(CommandError::from(format!(
+                "{} can't build multi-file projects. Try a compiler hosted on godbolt.org instead.",
+                backend.display_name()
+            )));
+        }
 
-        // Resolve target to a specific compiler
-        let asm_mode = mode == GodboltMode::Assembly;
-        let target = normalize_target(&request.target);
-        let compiler = godbolt.resolve(target).ok_or_else(|| {
-            if asm_mode {
-                CommandError::from(format!(
-                    "Target '{}' either does not produce assembly or is not supported on godbolt.org",
-                    target
-                ))
-            } else {
-                CommandError::from(format!(
-                    "Unable to find compiler for target '{}'.",
-                    target
-                ))
+        let request = &*Self::expand_presets(backend, request)?;
+        let request = async move {
+            match operation {
+                Operation::Compile => backend.compile(request, author).await,
+                Operation::Execute { boilerplate } => {
+                    backend.execute(request, author, boilerplate).await
+                }
             }
-        })?;
-
-        // Split any `-lib` requests out of the compiler options
-        let mut request = request.clone();
-        let library_specs = take_library_specs(&mut request.options)?;
-        let request = &request;
+        };
+        self.tracked(backend, updates, request).await
+    }
 
-        let code = if boilerplate {
-            boilerplate_generation(&compiler.lang, &request.code)
-        } else {
-            request.code.to_owned()
+    /// Send a request to a backend once its circuit allows it and it has a
+    /// free slot, recording the outcome with the health tracker
+    async fn tracked<T>(
+        &self,
+        backend: &dyn CompilerBackend,
+        updates: Option<&QueueUpdates>,
+        request: impl Future<Output = Result<T, CommandError>>,
+    ) -> Result<T, CommandError> {
+        let service = backend.display_name();
+        self.health
+            .check(service)
+            .map_err(|message| CommandError::from(BackendUnavailable(message)))?;
+        let _permit = match self.acquire(backend, updates).await {
+            Ok(permit) => permit,
+            Err(e) => {
+                self.health.abandon(service);
+                return Err(e);
+            }
         };
 
-        // Build request options
-        let mut options = match mode {
-            GodboltMode::Execute => build_execute_options(request),
-            GodboltMode::Check => build_check_options(request),
-            GodboltMode::Assembly => build_asm_options(request),
+        let start = Instant::now();
+        let result = request.await;
+        let outcome = match &result {
+            Err(e) if is_unavailable(e) => Err(e.to_string()),
+            _ => Ok(()),
         };
-        options.libraries = resolve_libraries(godbolt, &compiler.lang, &library_specs).await?;
-        let preprocessor = options.compiler_options.produce_pp.is_some();
+        self.health.record(service, start.elapsed(), outcome);
+        result
+    }
 
-        // Get shareable link
-        let godbolt_base64 = GodboltService::get_base64(&compiler, &code, &options).ok();
+    /// Wait for a slot on the backend's queue. A full queue is reported as
+    /// the backend being unavailable, so requests fail over where possible.
+    async fn acquire(
+        &self,
+        backend: &dyn CompilerBackend,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<Option<SemaphorePermit<'_>>, CommandError> {
+        let queue = match self.queues.get(backend.id()) {
+            Some(queue) => queue,
+            None => return Ok(None),
+        };
 
-        // Send compilation request
-        let response = godbolt.compile(&compiler, &code, options).await?;
+        match queue.acquire(updates).await {
+            Some(permit) => Ok(Some(permit)),
+            None => Err(CommandError::from(BackendUnavailable(format!(
+                "{} is busy right now ({} requests are already waiting). Please try again in a moment.",
+                backend.display_name(),
+                queue.max_waiting()
+            )))),
+        }
+    }
 
-        let details = CompilationDetails {
-            language: compiler.lang.clone(),
-            compiler: compiler.name.clone(),
-            godbolt_base64,
-            success: response.code == 0,
-            executed: mode == GodboltMode::Execute,
+    /// Compile code and return assembly output (Godbolt, or pcode disassembly
+    /// for SourcePawn targets).
+    pub async fn assembly(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<CompilationResult, CommandError> {
+        let request = self.apply_alias(request);
+        let backend = match self.require_backend(&request.target)? {
+            backend if backend.produces_assembly() => backend,
+            // Another backend may also know the target and produce assembly
+            // for it. If none does, the resolved backend explains why not.
+            resolved => {
+                let routing = self.routing.get();
+                self.backends()
+                    .filter(|backend| backend.produces_assembly())
+                    .find(|backend| {
+                        Self::resolve_allowed(&routing, *backend, &request.target).is_some()
+                    })
+                    .unwrap_or(resolved)
+            }
         };
+        let request = Self::expand_presets(backend, &request)?;
+        self.tracked(backend, updates, backend.assembly(&request, author))
+            .await
+    }
 
-        let embed_options = EmbedOptions::new(asm_mode, preprocessor, details.clone());
-        let embed = response.to_embed(author, &embed_options);
-
-        Ok(CompilationResult { details, embed })
+    /// Directly compile using Compiler Explorer and return raw response.
+    /// Used by the ;cpp command which needs access to the raw response.
+    pub async fn compile_godbolt_raw(
+        &self,
+        request: &ParserResult,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<(CompilationDetails, godbolt::CompilationResult), CommandError> {
+        let godbolt = self.require_godbolt()?;
+        let request = self.apply_alias(request);
+        let request = Self::expand_presets(godbolt, &request)?;
+        self.tracked(godbolt, updates, godbolt.compile_raw(&request))
+            .await
     }
 
-    /// Compile using WandBox.
-    async fn compile_with_wandbox(
+    /// Compile on Compiler Explorer with one of its tools running alongside.
+    /// Used by the ;tool command.
+    pub async fn run_tool(
         &self,
         request: &ParserResult,
         author: &User,
-        boilerplate: bool,
+        tool: &str,
+        tool_args: &str,
+        updates: Option<&QueueUpdates>,
     ) -> Result<CompilationResult, CommandError> {
-        let wandbox = self.wandbox.as_ref().ok_or_else(|| {
-            CommandError::from(
-                "WandBox is unavailable. This may be due to an outage. Please try again later.",
-            )
-        })?;
-
-        // Resolve target to language and compiler
-        let (language, compiler_name) = self.resolve_wandbox_target(wandbox, &request.target)?;
+        let godbolt = self.require_godbolt()?;
+        let request = self.apply_alias(request);
+        let request = Self::expand_presets(godbolt, &request)?;
+        self.tracked(
+            godbolt,
+            updates,
+            godbolt.run_tool(&request, author, tool, tool_args),
+        )
+        .await
+    }
 
-        let code = if boilerplate {
-            boilerplate_generation(&language, &request.code)
-        } else {
-            request.code.to_owned()
-        };
+    /// Compile to assembly on Compiler Explorer along with each function's
+    /// control-flow graph as Graphviz DOT. Used by `;asm --cfg`.
+    pub async fn control_flow_graph(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<(CompilationResult, Option<String>), CommandError> {
+        let godbolt = self.require_godbolt()?;
+        let request = self.apply_alias(request);
+        let request = Self::expand_presets(godbolt, &request)?;
+        self.tracked(
+            godbolt,
+            updates,
+            godbolt.control_flow_graph(&request, author),
+        )
+        .await
+    }
 
-        // Build and send compilation request
-        let compilation_request = wandbox::CompilationRequest {
-            compiler: compiler_name.clone(),
-            code: code.trim().to_string(),
-            stdin: request.stdin.trim().to_string(),
-            compiler_option_raw: request.options.join("\n"),
-            runtime_option_raw: request.args.join("\n"),
-            save: false,
-            ..Default::default()
-        };
+    /// Compile on Compiler Explorer and show LLVM IR, a Clang AST,
+    /// optimization remarks or GCC dumps instead of assembly, along with the
+    /// full output when it's too long for the embed.
+    /// Used by the ;ir, ;ast, ;remarks and ;gccdump commands.
+    pub async fn dump(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        kind: DumpKind,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<(CompilationResult, Option<String>), CommandError> {
+        let godbolt = self.require_godbolt()?;
+        let request = self.apply_alias(request);
+        let request = Self::expand_presets(godbolt, &request)?;
+        self.tracked(godbolt, updates, godbolt.dump(&request, author, kind))
+            .await
+    }
 
-        let response = wandbox.compile(&compilation_request).await?;
+    /// Run a crashed C or C++ program again with AddressSanitizer and UBSan
+    /// on Compiler Explorer, keeping its compiler when that one supports
+    /// them. Returns the rerun along with everything it wrote to stderr.
+    /// Used by the Triage button on crashed executions.
+    pub async fn triage(
+        &self,
+        request: &ParserResult,
+        updates: Option<&QueueUpdates>,
+    ) -> Result<(CompilationDetails, String), CommandError> {
+        let godbolt = self.require_godbolt()?;
+        let mut request = self.apply_alias(request).into_owned();
 
-        let details = CompilationDetails {
-            language,
-            compiler: compiler_name,
-            godbolt_base64: None,
-            success: response.status == Some(0),
-            executed: true,
+        let resolved = match godbolt.resolve(&request.target) {
+            Some(resolved) => resolved,
+            None => {
+                let language = self
+                    .require_backend(&request.target)?
+                    .resolve(&request.target)
+                    .map(|resolved| resolved.language)
+                    .unwrap_or_default();
+                godbolt.resolve(&language).ok_or_else(|| {
+                    CommandError::from("Crash triage only works for C and C++ programs.")
+                })?
+            }
         };
+        if resolved.language != "c" && resolved.language != "c++" {
+            return Err(CommandError::from(
+                "Crash triage only works for C and C++ programs.",
+            ));
+        }
 
-        let embed_options = EmbedOptions::new(false, false, details.clone());
-        let embed = response.to_embed(author, &embed_options);
+        let sanitizes = |compiler: &str| {
+            matches!(
+                Toolchain::detect(compiler),
+                Some(Toolchain::Gcc | Toolchain::Clang)
+            )
+        };
+        request.target = if sanitizes(&resolved.compiler) {
+            resolved.compiler
+        } else {
+            godbolt
+                .resolve(&resolved.language)
+                .map(|fallback| fallback.compiler)
+                .filter(|compiler| sanitizes(compiler))
+                .ok_or_else(|| {
+                    CommandError::from(format!(
+                        "Compiler Explorer has no {} compiler with sanitizers to rerun this on.",
+                        resolved.language
+                    ))
+                })?
+        };
+        request.presets = vec![Preset::Asan, Preset::Ubsan];
 
-        Ok(CompilationResult { details, embed })
+        let request = Self::expand_presets(godbolt, &request)?;
+        let (details, response) = self
+            .tracked(godbolt, updates, godbolt.compile_raw(&request))
+            .await?;
+        Ok((details, program_stderr(&response)))
     }
 
-    /// Directly compile using Compiler Explorer and return raw response.
-    /// Used by the ;cpp command which needs access to the raw response.
-    pub async fn compile_godbolt_raw(
-        &self,
-        request: &ParserResult,
-    ) -> Result<(CompilationDetails, godbolt::CompilationResult), CommandError> {
-        let godbolt = self.godbolt.as_ref().ok_or_else(|| {
+    fn require_godbolt(&self) -> Result<&GodboltBackend, CommandError> {
+        self.backend::<GodboltBackend>().ok_or_else(|| {
             CommandError::from(
                 "Compiler Explorer is unavailable. This may be due to an outage. Please try again later.",
             )
-        })?;
-
-        let target = normalize_target(&request.target);
-        let compiler = godbolt.resolve(target).ok_or_else(|| {
-            CommandError::from(format!("Unable to find compiler for target '{}'.", target))
-        })?;
-
-        // Split any `-lib` requests out of the compiler options
-        let mut request = request.clone();
-        let library_specs = take_library_specs(&mut request.options)?;
-        let request = &request;
-
-        let code = boilerplate_generation(&compiler.lang, &request.code);
-        let mut options = build_execute_options(request);
-        options.libraries = resolve_libraries(godbolt, &compiler.lang, &library_specs).await?;
-        let godbolt_base64 = GodboltService::get_base64(&compiler, &code, &options).ok();
-        let response = godbolt.compile(&compiler, &code, options).await?;
-
-        let details = CompilationDetails {
-            language: compiler.lang.clone(),
-            compiler: compiler.name.clone(),
-            godbolt_base64,
-            success: response.code == 0,
-            executed: true,
-        };
+        })
+    }
 
-        Ok((details, response))
+    /// The language a target resolves to, if any backend supports it
+    pub fn language_of(&self, target: &str) -> Option<String> {
+        let routing = self.routing.get();
+        let target = routing.alias(target);
+        self.resolve_backend(target)?
+            .resolve(target)
+            .map(|resolved| resolved.language)
     }
 
     /// Determine which backend should handle the given target.
-    fn resolve_backend(&self, target: &str) -> Option<Backend> {
-        if target == "sourcepawn" && self.sourcepawn.is_some() {
-            return Some(Backend::SourcePawn);
-        }
-
-        // These languages are only available on WandBox
-        const WANDBOX_ONLY: &[&str] = &["scala", "nim", "typescript", "javascript"];
-        if WANDBOX_ONLY.contains(&target) {
-            return Some(Backend::WandBox);
-        }
+    fn resolve_backend(&self, target: &str) -> Option<&dyn CompilerBackend> {
+        let routing = self.routing.get();
+        let target = routing.alias(target);
 
-        // Try Compiler Explorer first
-        if let Some(ref godbolt) = self.godbolt {
-            if godbolt.resolve(target).is_some() {
-                return Some(Backend::CompilerExplorer);
+        if let Some(id) = routing.preferred_backend(target) {
+            if let Some(backend) = self.backends().find(|backend| backend.id() == id) {
+                return Some(backend);
             }
         }
 
-        // Fall back to WandBox
-        if let Some(ref wandbox) = self.wandbox {
-            if wandbox.is_valid_language(target) || wandbox.is_valid_compiler_str(target) {
-                return Some(Backend::WandBox);
-            }
-        }
-
-        None
+        self.backends()
+            .find(|backend| Self::resolve_allowed(&routing, *backend, target).is_some())
     }
 
-    /// Resolve a WandBox target to (language, compiler_name).
-    fn resolve_wandbox_target(
-        &self,
-        wandbox: &WandboxService,
+    /// Resolve a target on one backend, unless the routing config denies the
+    /// result there
+    fn resolve_allowed(
+        routing: &RoutingConfig,
+        backend: &dyn CompilerBackend,
         target: &str,
-    ) -> Result<(String, String), CommandError> {
-        for lang in wandbox.get_languages() {
-            // Check if target matches language name
-            if target == lang.name {
-                let compiler_name = lang
-                    .compilers
-                    .first()
-                    .map(|c| c.name.clone())
-                    .unwrap_or_default();
-                return Ok((lang.name.clone(), compiler_name));
-            }
+    ) -> Option<ResolvedTarget> {
+        backend.resolve(target).filter(|resolved| {
+            !routing.is_compiler_denied(backend.id(), &resolved.compiler)
+                && !routing.is_language_denied(backend.id(), &resolved.language)
+        })
+    }
 
-            // Check if target matches a compiler name
-            for compiler in &lang.compilers {
-                if target == compiler.name {
-                    return Ok((lang.name.clone(), compiler.name.clone()));
-                }
+    /// The request with its target replaced by its alias, if it has one
+    fn apply_alias<'a>(&self, request: &'a ParserResult) -> Cow<'a, ParserResult> {
+        let routing = self.routing.get();
+        match routing.aliases.get(&request.target) {
+            Some(alias) => {
+                let mut request = request.clone();
+                request.target = alias.clone();
+                Cow::Owned(request)
             }
+            None => Cow::Borrowed(request),
+        }
+    }
+
+    /// The request with its presets (`--asan`, `--hardened`, ...) replaced by
+    /// the options the backend's compiler spells them with
+    fn expand_presets<'a>(
+        backend: &dyn CompilerBackend,
+        request: &'a ParserResult,
+    ) -> Result<Cow<'a, ParserResult>, CommandError> {
+        if request.presets.is_empty() {
+            return Ok(Cow::Borrowed(request));
         }
 
-        Err(CommandError::from(format!(
-            "Unable to find language or compiler for target '{}'.",
-            target
-        )))
+        let compiler = backend
+            .resolve(&request.target)
+            .map(|resolved| resolved.compiler)
+            .unwrap_or_else(|| request.target.clone());
+        let options = presets::expand(
+            &request.presets,
+            &compiler,
+            backend.display_name(),
+            backend.supports_sanitizers(),
+        )?;
+
+        let mut request = request.clone();
+        request.options.splice(0..0, options);
+        request.presets.clear();
+        Ok(Cow::Owned(request))
+    }
+
+    /// Like `resolve_backend`, but with an error suitable for display
+    fn require_backend(&self, target: &str) -> Result<&dyn CompilerBackend, CommandError> {
+        self.resolve_backend(target).ok_or_else(|| {
+            let target = if target.starts_with('@') {
+                format!("\\{}", target)
+            } else {
+                target.to_string()
+            };
+            CommandError::from(format!(
+                "Unable to find compiler or language for target '{}'.",
+                target
+            ))
+        })
     }
 
     /// Get list of available compilers for a language.
@@ -481,15 +910,7 @@ impl CompilationManager {
         let language = shortname_to_qualified(&lower_lang);
 
         match self.resolve_backend(language) {
-            Some(Backend::CompilerExplorer) => self.list_godbolt_compilers(language, filter),
-            Some(Backend::WandBox) => self.list_wandbox_compilers(language, filter),
-            Some(Backend::SourcePawn) => {
-                let sourcepawn = self.sourcepawn.as_ref().unwrap();
-                Ok(vec![format!(
-                    "{} -> **sourcepawn**",
-                    sourcepawn.compiler_name()
-                )])
-            }
+            Some(backend) => backend.list_compilers(language, filter),
             None => Err(CommandError::from(format!(
                 "Unable to find compilers for target '{}'.",
                 language
@@ -497,7 +918,7 @@ impl CompilationManager {
         }
     }
 
-    /// Get list of available Compiler Explorer libraries for a language.
+    /// Get list of available libraries for a language.
     pub async fn get_library_list(
         &self,
         language: &str,
@@ -506,443 +927,24 @@ impl CompilationManager {
         let lower_lang = language.to_lowercase();
         let language = shortname_to_qualified(&lower_lang);
 
-        let godbolt = self.godbolt.as_ref().ok_or_else(|| {
-            CommandError::from(
-                "Compiler Explorer is unavailable. This may be due to an outage. Please try again later.",
-            )
-        })?;
-
-        if !godbolt
-            .cache
-            .iter()
-            .any(|entry| entry.language.id == language)
-        {
-            return Err(CommandError::from(format!(
-                "Unable to find language '{}'. Libraries are only available for Compiler Explorer targets.",
-                language
-            )));
-        }
-
-        let libraries = godbolt.libraries_for(language).await.map_err(|e| {
-            CommandError::from(format!(
-                "Unable to fetch libraries for '{}': {}",
-                language, e
-            ))
-        })?;
-
-        let mut results: Vec<(f64, String)> = Vec::new();
-
-        for library in libraries {
-            let versions = match (library.versions.first(), library.versions.last()) {
-                (Some(first), Some(last)) if library.versions.len() > 1 => {
-                    format!("{} … {}", first.version, last.version)
-                }
-                (Some(only), _) => only.version.clone(),
-                _ => String::from("none"),
-            };
-            let display = format!("{} -> **{}** ({})", library.name, library.id, versions);
-
-            if let Some(filter_str) = filter {
-                if !matches_filter(&library.id, &library.name, filter_str) {
-                    continue;
-                }
-                let similarity = compute_similarity(&library.id, &library.name, filter_str);
-                results.push((similarity, display));
-            } else {
-                results.push((0.0, display));
-            }
-        }
-
-        if filter.is_some() {
-            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        }
-
-        Ok(results.into_iter().map(|(_, s)| s).collect())
-    }
-
-    fn list_godbolt_compilers(
-        &self,
-        language: &str,
-        filter: Option<&str>,
-    ) -> Result<Vec<String>, CommandError> {
-        let godbolt = self.godbolt.as_ref().unwrap();
-        let mut results: Vec<(f64, String)> = Vec::new();
-
-        for cache_entry in &godbolt.cache {
-            if cache_entry.language.id != language {
-                continue;
-            }
-
-            for compiler in &cache_entry.compilers {
-                let display = format!("{} -> **{}**", &compiler.name, &compiler.id);
-
-                if let Some(filter_str) = filter {
-                    if !matches_filter(&compiler.id, &compiler.name, filter_str) {
-                        continue;
-                    }
-                    let similarity = compute_similarity(&compiler.id, &compiler.name, filter_str);
-                    results.push((similarity, display));
-                } else {
-                    results.push((0.0, display));
-                }
-            }
-        }
-
-        if filter.is_some() {
-            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        }
-
-        Ok(results.into_iter().map(|(_, s)| s).collect())
-    }
-
-    fn list_wandbox_compilers(
-        &self,
-        language: &str,
-        filter: Option<&str>,
-    ) -> Result<Vec<String>, CommandError> {
-        let wandbox = self.wandbox.as_ref().unwrap();
-        let compilers = wandbox
-            .get_compilers(shortname_to_qualified(language))
+        let backend = self
+            .backends()
+            .find(|backend| backend.supports_libraries(language))
             .ok_or_else(|| {
                 CommandError::from(format!(
-                    "Unable to find compilers for target '{}'.",
+                    "Unable to find language '{}'. Libraries are only available for Compiler Explorer targets.",
                     language
                 ))
             })?;
 
-        let mut results: Vec<(f64, String)> = Vec::new();
-
-        for compiler in compilers {
-            if let Some(filter_str) = filter {
-                if !matches_filter(&compiler.name, &compiler.name, filter_str) {
-                    continue;
-                }
-                let similarity = similar_string::compare_similarity(filter_str, &compiler.name);
-                results.push((similarity, compiler.name));
-            } else {
-                results.push((0.0, compiler.name));
-            }
-        }
-
-        if filter.is_some() {
-            results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
-        }
-
-        Ok(results.into_iter().map(|(_, s)| s).collect())
-    }
-}
-
-// ============================================================================
-// Helper functions
-// ============================================================================
-
-/// Normalize target names (e.g., "haskell" -> "ghc901")
-fn normalize_target(target: &str) -> &str {
-    match target {
-        "haskell" => "ghc901",
-        other => other,
-    }
-}
-
-/// Prepare code by adding boilerplate and fixing common issues
-fn boilerplate_generation(language: &str, code: &str) -> String {
-    let generator = boilerplate_factory(language, code);
-    let code = if generator.needs_boilerplate() {
-        generator.generate()
-    } else {
-        code.to_string()
-    };
-
-    fix_common_problems(language, code)
-}
-
-/// Fix common language-specific issues in user code
-fn fix_common_problems(language: &str, code: String) -> String {
-    match language {
-        "java" => {
-            // Remove 'public' from class declarations (Godbolt doesn't like public classes)
-            let mut fixed = code.clone();
-            for m in JAVA_PUBLIC_CLASS_REGEX.captures_iter(&code) {
-                if let Some(pub_keyword) = m.name("public") {
-                    fixed.replace_range(pub_keyword.range(), "");
-                }
-            }
-            fixed
-        }
-        _ => code,
+        backend.list_libraries(language, filter).await
     }
-}
 
-/// Remove `-lib` flags (`-lib fmt:trunk`, `-lib fmt`, `-libfmt`) from the
-/// compiler options, returning the extracted library specs
-fn take_library_specs(options: &mut Vec<String>) -> Result<Vec<String>, CommandError> {
-    let mut specs = Vec::new();
-    let mut remaining = Vec::new();
-
-    let mut iter = std::mem::take(options).into_iter();
-    while let Some(opt) = iter.next() {
-        if opt == "-lib" {
-            let spec = iter.next().ok_or_else(|| {
-                CommandError::from("The `-lib` flag requires a library, e.g. `-lib fmt:trunk`")
-            })?;
-            specs.push(spec);
-        } else if let Some(spec) = opt.strip_prefix("-lib") {
-            specs.push(spec.to_string());
-        } else {
-            remaining.push(opt);
-        }
+    /// Get list of Compiler Explorer tools for a compiler or language.
+    pub async fn get_tool_list(&self, target: &str) -> Result<Vec<String>, CommandError> {
+        let lower_target = target.to_lowercase();
+        let target = shortname_to_qualified(&lower_target);
+        let target = self.routing.get().alias(target).to_string();
+        self.require_godbolt()?.list_tools(&target).await
     }
-
-    *options = remaining;
-    Ok(specs)
-}
-
-/// Resolve `-lib` specs (`<library>:<version>`) against Compiler Explorer's
-/// library list for the given language
-async fn resolve_libraries(
-    godbolt: &GodboltService,
-    language_id: &str,
-    specs: &[String],
-) -> Result<Vec<LibrarySelection>, CommandError> {
-    if specs.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let libraries = godbolt.libraries_for(language_id).await.map_err(|e| {
-        CommandError::from(format!(
-            "Unable to fetch libraries for '{}': {}",
-            language_id, e
-        ))
-    })?;
-
-    let mut selections = Vec::new();
-    for spec in specs {
-        let (name, version_str) = spec.split_once(':').unwrap_or((spec.as_str(), ""));
-
-        let library = libraries
-            .iter()
-            .find(|lib| lib.id.eq_ignore_ascii_case(name))
-            .ok_or_else(|| {
-                CommandError::from(format!(
-                    "Unknown library '{}' for language '{}'. See `;libraries {}` to browse.\n\nAvailable libraries: {}",
-                    name,
-                    language_id,
-                    language_id,
-                    summarize_names(libraries.iter().map(|lib| lib.id.as_str()))
-                ))
-            })?;
-
-        let version = if version_str.is_empty() {
-            library.versions.last().ok_or_else(|| {
-                CommandError::from(format!(
-                    "Library '{}' has no versions available.",
-                    library.id
-                ))
-            })?
-        } else {
-            library
-                .versions
-                .iter()
-                .find(|ver| {
-                    ver.id.eq_ignore_ascii_case(version_str)
-                        || ver.version.eq_ignore_ascii_case(version_str)
-                        || ver
-                            .alias
-                            .iter()
-                            .any(|alias| alias.eq_ignore_ascii_case(version_str))
-                })
-                .ok_or_else(|| {
-                    CommandError::from(format!(
-                        "Unknown version '{}' for library '{}'.\n\nAvailable versions: {}",
-                        version_str,
-                        library.id,
-                        summarize_names(library.versions.iter().map(|ver| ver.version.as_str()))
-                    ))
-                })?
-        };
-
-        selections.push(LibrarySelection {
-            id: library.id.clone(),
-            version: version.id.clone(),
-        });
-    }
-
-    Ok(selections)
-}
-
-/// Join names with commas, truncating once the list grows too long for an
-/// error message
-fn summarize_names<'a>(names: impl Iterator<Item = &'a str>) -> String {
-    let mut summary = String::new();
-    for name in names {
-        if !summary.is_empty() {
-            summary.push_str(", ");
-        }
-        if summary.len() + name.len() > 800 {
-            summary.push('…');
-            break;
-        }
-        summary.push_str(name);
-    }
-    summary
-}
-
-/// Build request options for code execution
-fn build_execute_options(request: &ParserResult) -> RequestOptions {
-    RequestOptions {
-        user_arguments: request.options.join(" "),
-        compiler_options: CompilerOptions {
-            skip_asm: true,
-            executor_request: true,
-            ..Default::default()
-        },
-        execute_parameters: godbolt::ExecuteParameters {
-            args: request.args.clone(),
-            stdin: request.stdin.clone(),
-        },
-        filters: CompilationFilters {
-            comment_only: Some(true),
-            demangle: Some(true),
-            directives: Some(true),
-            execute: Some(true),
-            intel: Some(true),
-            labels: Some(true),
-            trim: Some(true),
-            ..Default::default()
-        },
-        ..Default::default()
-    }
-}
-
-/// Build request options for a compile-only check
-fn build_check_options(request: &ParserResult) -> RequestOptions {
-    RequestOptions {
-        user_arguments: request.options.join(" "),
-        compiler_options: CompilerOptions {
-            skip_asm: true,
-            executor_request: false,
-            ..Default::default()
-        },
-        execute_parameters: Default::default(),
-        filters: CompilationFilters {
-            comment_only: Some(true),
-            demangle: Some(true),
-            directives: Some(true),
-            execute: Some(false),
-            intel: Some(true),
-            labels: Some(true),
-            trim: Some(true),
-            ..Default::default()
-        },
-        ..Default::default()
-    }
-}
-
-/// Build request options for assembly output
-fn build_asm_options(request: &ParserResult) -> RequestOptions {
-    // Only request preprocessor output when the user explicitly asks for it via
-    // `-E`. When present, we show the cleaner (header-filtered, formatted)
-    // preprocessor source instead of the assembly.
-    let produce_pp = request
-        .options
-        .iter()
-        .any(|opt| opt == "-E")
-        .then_some(PpOptions {
-            filter_headers: true,
-            clang_format: true,
-        });
-
-    RequestOptions {
-        user_arguments: request.options.join(" "),
-        compiler_options: CompilerOptions {
-            skip_asm: false,
-            executor_request: false,
-            produce_pp,
-            ..Default::default()
-        },
-        execute_parameters: Default::default(),
-        filters: CompilationFilters {
-            comment_only: Some(true),
-            demangle: Some(true),
-            directives: Some(true),
-            execute: Some(false),
-            intel: Some(true),
-            labels: Some(true),
-            trim: Some(true),
-            ..Default::default()
-        },
-        ..Default::default()
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::take_library_specs;
-
-    #[test]
-    fn takes_library_specs_out_of_options() {
-        let mut options = vec![
-            String::from("-O2"),
-            String::from("-lib"),
-            String::from("fmt:trunk"),
-            String::from("-Wall"),
-            String::from("-lib"),
-            String::from("ctre:dev"),
-        ];
-
-        let specs = take_library_specs(&mut options).unwrap();
-        assert_eq!(specs, vec!["fmt:trunk", "ctre:dev"]);
-        assert_eq!(options, vec!["-O2", "-Wall"]);
-    }
-
-    #[test]
-    fn takes_concatenated_library_specs() {
-        let mut options = vec![
-            String::from("-libboost"),
-            String::from("-O2"),
-            String::from("-libfmt:trunk"),
-        ];
-
-        let specs = take_library_specs(&mut options).unwrap();
-        assert_eq!(specs, vec!["boost", "fmt:trunk"]);
-        assert_eq!(options, vec!["-O2"]);
-    }
-
-    #[test]
-    fn takes_library_specs_without_versions() {
-        let mut options = vec![String::from("-lib"), String::from("boost")];
-
-        let specs = take_library_specs(&mut options).unwrap();
-        assert_eq!(specs, vec!["boost"]);
-        assert!(options.is_empty());
-    }
-
-    #[test]
-    fn leaves_options_without_libraries_alone() {
-        let mut options = vec![String::from("-O2"), String::from("-Wall")];
-
-        let specs = take_library_specs(&mut options).unwrap();
-        assert!(specs.is_empty());
-        assert_eq!(options, vec!["-O2", "-Wall"]);
-    }
-
-    #[test]
-    fn rejects_dangling_lib_flag() {
-        let mut options = vec![String::from("-O2"), String::from("-lib")];
-        assert!(take_library_specs(&mut options).is_err());
-    }
-}
-
-/// Check if a compiler matches the filter string
-fn matches_filter(id: &str, name: &str, filter: &str) -> bool {
-    filter
-        .split_whitespace()
-        .any(|part| id.contains(part) || name.contains(part))
-}
-
-/// Compute similarity score for sorting filtered results
-fn compute_similarity(id: &str, name: &str, filter: &str) -> f64 {
-    let id_sim = similar_string::compare_similarity(filter, id);
-    let name_sim = similar_string::compare_similarity(filter, name);
-    f64::max(id_sim, name_sim)
 }
@@ -0,0 +1,256 @@
+//! Health of the upstream services the bot depends on.
+//!
+//! Every request to a service is recorded with its latency and whether the
+//! service failed (transport errors, timeouts, 5xx). Compile errors and other
+//! answers the service gave us count as successes. Once a service fails too
+//! often its circuit opens and requests fail fast for CIRCUIT_OPEN_DURATION;
+//! after that a single request is let through as a probe, and its result
+//! decides whether the circuit closes or opens again. Everything else keeps
+//! failing fast while the probe runs.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Requests remembered per service for success rate and latency percentiles
+const WINDOW: usize = 100;
+
+/// Failure rate over the window that opens the circuit, once the window has
+/// at least CIRCUIT_MIN_SAMPLES requests
+const CIRCUIT_FAILURE_RATE: f64 = 0.5;
+const CIRCUIT_MIN_SAMPLES: usize = 10;
+
+/// Consecutive failures that open the circuit regardless of the window
+const CIRCUIT_CONSECUTIVE_FAILURES: u32 = 5;
+
+const CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(30);
+
+/// How long a probe may take to report back before another request is let
+/// through in its place, in case it was cancelled
+const CIRCUIT_PROBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Circuit {
+    Closed,
+    /// Requests fail fast until the given time
+    Open(Instant),
+    /// The open period is over and a probe request decides
+    HalfOpen,
+}
+
+struct Sample {
+    latency: Duration,
+    success: bool,
+}
+
+struct ServiceHealth {
+    name: String,
+    samples: VecDeque<Sample>,
+    consecutive_failures: u32,
+    last_error: Option<(SystemTime, String)>,
+    circuit: Circuit,
+    /// When the probe of a half-open circuit was let through, if one is
+    /// running
+    probe: Option<Instant>,
+}
+
+impl ServiceHealth {
+    fn new(name: &str) -> Self {
+        ServiceHealth {
+            name: name.to_string(),
+            samples: VecDeque::new(),
+            consecutive_failures: 0,
+            last_error: None,
+            circuit: Circuit::Closed,
+            probe: None,
+        }
+    }
+
+    fn failure_rate(&self) -> f64 {
+        let failures = self.samples.iter().filter(|s| !s.success).count();
+        failures as f64 / self.samples.len().max(1) as f64
+    }
+
+    fn should_open(&self) -> bool {
+        self.consecutive_failures >= CIRCUIT_CONSECUTIVE_FAILURES
+            || (self.samples.len() >= CIRCUIT_MIN_SAMPLES
+                && self.failure_rate() >= CIRCUIT_FAILURE_RATE)
+    }
+}
+
+/// A point-in-time view of one service, for `;status`
+pub struct ServiceStatus {
+    pub name: String,
+    pub requests: usize,
+    /// Fraction of recent requests that succeeded, None without requests
+    pub success_rate: Option<f64>,
+    /// 50th, 95th and 99th percentile latency of recent requests
+    pub latency: Option<[Duration; 3]>,
+    pub last_error: Option<(SystemTime, String)>,
+    pub circuit: Circuit,
+}
+
+#[derive(Default)]
+pub struct HealthManager {
+    services: Mutex<Vec<ServiceHealth>>,
+}
+
+impl HealthManager {
+    pub fn new() -> Self {
+        HealthManager::default()
+    }
+
+    /// Start tracking a service so it shows up before its first request
+    pub fn register(&self, service: &str) {
+        let mut services = self.services.lock().unwrap();
+        if !services.iter().any(|s| s.name == service) {
+            services.push(ServiceHealth::new(service));
+        }
+    }
+
+    /// Whether a request to the service may be sent. A request let through
+    /// must be followed by `record`, or `abandon` if it is never sent. The
+    /// error is a message suitable for display.
+    pub fn check(&self, service: &str) -> Result<(), String> {
+        let mut services = self.services.lock().unwrap();
+        let health = match services.iter_mut().find(|s| s.name == service) {
+            Some(health) => health,
+            None => return Ok(()),
+        };
+
+        match health.circuit {
+            Circuit::Open(until) if until > Instant::now() => {
+                let mut message = format!(
+                    "{} is having problems right now, so requests to it are paused for {}s.",
+                    service,
+                    (until - Instant::now()).as_secs() + 1
+                );
+                if let Some((_, error)) = &health.last_error {
+                    message.push_str(&format!(" Last error: {}", error));
+                }
+                Err(message)
+            }
+            Circuit::Open(_) => {
+                health.circuit = Circuit::HalfOpen;
+                health.probe = Some(Instant::now());
+                Ok(())
+            }
+            Circuit::HalfOpen => match health.probe {
+                Some(started) if started.elapsed() < CIRCUIT_PROBE_TIMEOUT => Err(format!(
+                    "{} is recovering from problems, so requests to it are paused until a test request succeeds.",
+                    service
+                )),
+                // The last probe never reported back, this request takes over
+                _ => {
+                    health.probe = Some(Instant::now());
+                    Ok(())
+                }
+            },
+            Circuit::Closed => Ok(()),
+        }
+    }
+
+    /// End the open period of a service's circuit now
+    #[cfg(test)]
+    pub fn end_open_period(&self, service: &str) {
+        let mut services = self.services.lock().unwrap();
+        if let Some(health) = services.iter_mut().find(|s| s.name == service) {
+            if let Circuit::Open(_) = health.circuit {
+                health.circuit = Circuit::Open(Instant::now());
+            }
+        }
+    }
+
+    /// A request let through by `check` wasn't sent after all. If it was the
+    /// probe of a half-open circuit, the next request probes instead.
+    pub fn abandon(&self, service: &str) {
+        let mut services = self.services.lock().unwrap();
+        if let Some(health) = services.iter_mut().find(|s| s.name == service) {
+            health.probe = None;
+        }
+    }
+
+    /// Record a finished request; `result` carries the error for failures
+    pub fn record(&self, service: &str, latency: Duration, result: Result<(), String>) {
+        let mut services = self.services.lock().unwrap();
+        let health = match services.iter().position(|s| s.name == service) {
+            Some(index) => &mut services[index],
+            None => {
+                services.push(ServiceHealth::new(service));
+                services.last_mut().unwrap()
+            }
+        };
+
+        let success = result.is_ok();
+        health.samples.push_back(Sample { latency, success });
+        if health.samples.len() > WINDOW {
+            health.samples.pop_front();
+        }
+
+        match result {
+            Ok(()) => health.consecutive_failures = 0,
+            Err(error) => {
+                health.consecutive_failures += 1;
+                health.last_error = Some((SystemTime::now(), error));
+            }
+        }
+
+        match health.circuit {
+            Circuit::HalfOpen if success => {
+                info!("{} recovered, closing its circuit", service);
+                health.circuit = Circuit::Closed;
+                health.probe = None;
+                // Failures from before the outage shouldn't reopen it
+                health.samples.clear();
+                health.samples.push_back(Sample { latency, success });
+            }
+            Circuit::HalfOpen => {
+                health.circuit = Circuit::Open(Instant::now() + CIRCUIT_OPEN_DURATION);
+                health.probe = None;
+            }
+            Circuit::Closed if health.should_open() => {
+                warn!(
+                    "{} is failing ({:.0}% of recent requests), opening its circuit",
+                    service,
+                    health.failure_rate() * 100.0
+                );
+                health.circuit = Circuit::Open(Instant::now() + CIRCUIT_OPEN_DURATION);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn statuses(&self) -> Vec<ServiceStatus> {
+        let services = self.services.lock().unwrap();
+        services
+            .iter()
+            .map(|health| {
+                let mut latencies: Vec<Duration> =
+                    health.samples.iter().map(|s| s.latency).collect();
+                latencies.sort();
+
+                let requests = health.samples.len();
+                ServiceStatus {
+                    name: health.name.clone(),
+                    requests,
+                    success_rate: (requests > 0).then(|| 1.0 - health.failure_rate()),
+                    latency: (requests > 0).then(|| {
+                        [
+                            percentile(&latencies, 50),
+                            percentile(&latencies, 95),
+                            percentile(&latencies, 99),
+                        ]
+                    }),
+                    last_error: health.last_error.clone(),
+                    circuit: health.circuit,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty values
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    let rank = (percent * sorted.len()).div_ceil(100);
+    sorted[rank.saturating_sub(1)]
+}
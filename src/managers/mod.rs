@@ -1,3 +1,5 @@
 pub mod command;
 pub mod compilation;
+pub mod health;
+pub mod settings;
 pub mod stats;
@@ -0,0 +1,590 @@
+//! Persistent per-guild and per-user preferences, changed with `/settings`.
+//!
+//! Settings are kept in the SQLite database named by SETTINGS_PATH
+//! (settings.db by default). The database records the schema version it was
+//! last migrated to as its `user_version`; older databases are brought up to
+//! date by running the remaining MIGRATIONS when they are opened. Everything
+//! is cached in memory as well, so requests never wait on the database.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::RwLock;
+
+use rusqlite::{params, Connection, Transaction};
+
+use crate::backends::godbolt::is_asm_flag;
+use crate::managers::compilation::CompilationManager;
+use crate::utls::diagnostics::OutputMode;
+use crate::utls::parser::{shortname_to_qualified, ParserResult};
+use crate::utls::presets::Preset;
+
+/// Upgrades the database from each schema version to the next, in order.
+/// Never change one that has shipped; append a new one instead.
+pub const MIGRATIONS: &[&str] = &[
+    // Version 1: settings for guilds and users, keyed by scope ("guild" or
+    // "user") and id, with the per-language ones in a table of their own.
+    // Flags are JSON arrays.
+    "CREATE TABLE settings (
+        scope TEXT NOT NULL,
+        id INTEGER NOT NULL,
+        output TEXT,
+        language TEXT,
+        PRIMARY KEY (scope, id)
+    );
+    CREATE TABLE language_settings (
+        scope TEXT NOT NULL,
+        id INTEGER NOT NULL,
+        language TEXT NOT NULL,
+        compiler TEXT,
+        flags TEXT,
+        PRIMARY KEY (scope, id, language)
+    );",
+    // Version 2: guild-only command prefixes, space separated, and assembly
+    // flags
+    "ALTER TABLE settings ADD COLUMN prefixes TEXT NOT NULL DEFAULT '';
+    ALTER TABLE settings ADD COLUMN asm_flags TEXT NOT NULL DEFAULT '[]';",
+];
+
+/// The schema version written by this build
+pub const SCHEMA_VERSION: u64 = MIGRATIONS.len() as u64;
+
+/// Most command prefixes a guild can have
+pub const MAX_PREFIXES: usize = 5;
+
+/// Longest command prefix, in characters
+pub const MAX_PREFIX_LENGTH: usize = 10;
+
+/// Who a group of settings belongs to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scope {
+    Guild(u64),
+    User(u64),
+}
+
+impl Scope {
+    /// How the scope is stored: its kind and id
+    fn key(&self) -> (&'static str, i64) {
+        match self {
+            Scope::Guild(id) => ("guild", *id as i64),
+            Scope::User(id) => ("user", *id as i64),
+        }
+    }
+}
+
+/// A preference as it is named in `/settings`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Setting {
+    Compiler,
+    Flags,
+    Output,
+    Language,
+    Prefix,
+    Asm,
+}
+
+impl Setting {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "compiler" => Some(Setting::Compiler),
+            "flags" => Some(Setting::Flags),
+            "output" => Some(Setting::Output),
+            "language" => Some(Setting::Language),
+            "prefix" => Some(Setting::Prefix),
+            "asm" => Some(Setting::Asm),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::Compiler => "compiler",
+            Setting::Flags => "flags",
+            Setting::Output => "output",
+            Setting::Language => "language",
+            Setting::Prefix => "prefix",
+            Setting::Asm => "asm",
+        }
+    }
+
+    /// Whether the setting is kept per language
+    pub fn per_language(&self) -> bool {
+        matches!(self, Setting::Compiler | Setting::Flags)
+    }
+
+    /// Whether the setting can only be changed for a whole guild
+    pub fn guild_only(&self) -> bool {
+        matches!(self, Setting::Prefix | Setting::Asm)
+    }
+}
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// Compiler used when a request only names the language, keyed by
+    /// language, e.g. "c++" -> "clang1810"
+    pub compilers: BTreeMap<String, String>,
+    /// Options placed before a request's own, keyed by language
+    pub flags: BTreeMap<String, Vec<String>>,
+    /// How compiler output is shown, if not the default
+    pub output: Option<OutputMode>,
+    /// Language of requests that don't name one, e.g. "c++"
+    pub language: Option<String>,
+    /// Command prefixes that replace BOT_PREFIX, for guilds only
+    pub prefixes: Vec<String>,
+    /// Options placed before a request's own on every ;asm, e.g. "--att",
+    /// for guilds only
+    pub asm_flags: Vec<String>,
+}
+
+impl Settings {
+    pub fn is_empty(&self) -> bool {
+        *self == Settings::default()
+    }
+
+    /// Change one setting. Per-language settings need the language.
+    pub fn set(
+        &mut self,
+        setting: Setting,
+        language: Option<&str>,
+        value: &str,
+    ) -> Result<(), String> {
+        let language = match (setting.per_language(), language) {
+            (true, Some(language)) => normalize_language(language),
+            (true, None) => {
+                return Err(format!(
+                    "Choose the language the {} is for.",
+                    setting.name()
+                ))
+            }
+            (false, _) => String::new(),
+        };
+
+        match setting {
+            Setting::Compiler => {
+                let compiler = value.trim();
+                if compiler.is_empty() {
+                    self.compilers.remove(&language);
+                } else {
+                    self.compilers.insert(language, compiler.to_string());
+                }
+            }
+            Setting::Flags => {
+                let flags = shell_words::split(value)
+                    .map_err(|e| format!("Unable to read the flags: {}", e))?;
+                if flags.is_empty() {
+                    self.flags.remove(&language);
+                } else {
+                    self.flags.insert(language, flags);
+                }
+            }
+            Setting::Output => {
+                self.output = Some(
+                    parse_output(&value.trim().to_lowercase())
+                        .ok_or_else(|| String::from("Output must be `summary` or `raw`."))?,
+                );
+            }
+            Setting::Language => {
+                let language = normalize_language(value);
+                self.language = (!language.is_empty()).then_some(language);
+            }
+            Setting::Prefix => self.prefixes = parse_prefixes(value)?,
+            Setting::Asm => self.asm_flags = parse_asm_flags(value)?,
+        }
+        Ok(())
+    }
+
+    /// Go back to the default for one setting, or for all of them. Without
+    /// a language, per-language settings are reset for every language.
+    pub fn reset(&mut self, setting: Option<Setting>, language: Option<&str>) {
+        let language = language.map(normalize_language);
+        let language = language.as_deref();
+        match setting {
+            Some(Setting::Compiler) => reset_language(&mut self.compilers, language),
+            Some(Setting::Flags) => reset_language(&mut self.flags, language),
+            Some(Setting::Output) => self.output = None,
+            Some(Setting::Language) => self.language = None,
+            Some(Setting::Prefix) => self.prefixes.clear(),
+            Some(Setting::Asm) => self.asm_flags.clear(),
+            None => *self = Settings::default(),
+        }
+    }
+
+    /// These settings with anything set in `overrides` taking precedence.
+    /// Prefixes and assembly flags belong to the guild and are kept as they
+    /// are.
+    pub fn overridden_by(mut self, overrides: &Settings) -> Settings {
+        self.compilers.extend(overrides.compilers.clone());
+        self.flags.extend(overrides.flags.clone());
+        self.output = overrides.output.or(self.output);
+        self.language = overrides.language.clone().or(self.language);
+        self
+    }
+
+    /// Apply the settings to a parsed request for `language`, the language
+    /// its target resolves to if known
+    pub fn apply(&self, request: &mut ParserResult, language: Option<&str>) {
+        let language = language.unwrap_or(&request.target).to_string();
+        if request.target == language {
+            if let Some(compiler) = self.compilers.get(&language) {
+                request.target = compiler.clone();
+            }
+        }
+        if let Some(flags) = self.flags.get(&language) {
+            let mut options = Vec::new();
+            for flag in flags {
+                match Preset::from_flag(flag) {
+                    Some(preset) if !request.presets.contains(&preset) => {
+                        request.presets.push(preset)
+                    }
+                    Some(_) => {}
+                    None => options.push(flag.clone()),
+                }
+            }
+            request.options.splice(0..0, options);
+        }
+        if let Some(output) = self.output {
+            request.output = output;
+        }
+    }
+}
+
+fn parse_output(value: &str) -> Option<OutputMode> {
+    match value {
+        "summary" => Some(OutputMode::Summary),
+        "raw" => Some(OutputMode::Raw),
+        _ => None,
+    }
+}
+
+fn output_name(output: OutputMode) -> &'static str {
+    match output {
+        OutputMode::Summary => "summary",
+        OutputMode::Raw => "raw",
+    }
+}
+
+/// Read space separated prefixes, e.g. "!! cb!"
+fn parse_prefixes(value: &str) -> Result<Vec<String>, String> {
+    let mut prefixes: Vec<String> = Vec::new();
+    for prefix in value.split_whitespace() {
+        if prefix.chars().count() > MAX_PREFIX_LENGTH {
+            return Err(format!(
+                "'{}' is too long, prefixes can be up to {} characters.",
+                prefix, MAX_PREFIX_LENGTH
+            ));
+        }
+        if prefix.contains('`') || prefix.starts_with("<@") {
+            return Err(format!("'{}' can't be used as a prefix.", prefix));
+        }
+        if !prefixes.iter().any(|existing| existing == prefix) {
+            prefixes.push(prefix.to_string());
+        }
+    }
+
+    if prefixes.len() > MAX_PREFIXES {
+        return Err(format!(
+            "A server can have up to {} prefixes.",
+            MAX_PREFIXES
+        ));
+    }
+    Ok(prefixes)
+}
+
+/// Read assembly flags, e.g. "--att --keep-directives". Only flags that
+/// change how assembly is shown are allowed. `--cfg` isn't one: there is no
+/// way to turn it back off, so every `;asm` in the guild would be a graph.
+fn parse_asm_flags(value: &str) -> Result<Vec<String>, String> {
+    let flags =
+        shell_words::split(value).map_err(|e| format!("Unable to read the flags: {}", e))?;
+    match flags.iter().find(|flag| !is_asm_flag(flag)) {
+        Some(flag) if flag == "--cfg" => Err(String::from(
+            "`--cfg` can't be a default, since it can't be turned off for a single request.",
+        )),
+        Some(flag) => Err(format!(
+            "'{}' isn't an assembly flag. Use flags like `--att` or `--binary`.",
+            flag
+        )),
+        None => Ok(flags),
+    }
+}
+
+/// The longest of `prefixes` that `content` starts with, so "!!" wins over
+/// "!" when a guild has both
+pub fn find_prefix<'a>(prefixes: &'a [String], content: &str) -> Option<&'a str> {
+    prefixes
+        .iter()
+        .filter(|prefix| content.starts_with(prefix.as_str()))
+        .max_by_key(|prefix| prefix.len())
+        .map(String::as_str)
+}
+
+fn reset_language<T>(settings: &mut BTreeMap<String, T>, language: Option<&str>) {
+    match language {
+        Some(language) => {
+            settings.remove(language);
+        }
+        None => settings.clear(),
+    }
+}
+
+/// Languages are stored the way the parser names them, e.g. "cpp" -> "c++"
+fn normalize_language(language: &str) -> String {
+    shortname_to_qualified(&language.trim().to_lowercase()).to_string()
+}
+
+pub struct SettingsManager {
+    /// The database, locked by the blocking tasks that write to it
+    database: Arc<Mutex<Connection>>,
+    /// Held for a whole update, so concurrent changes to the same settings
+    /// are applied one after another instead of overwriting each other
+    updating: Arc<tokio::sync::Mutex<()>>,
+    guilds: BTreeMap<u64, Settings>,
+    users: BTreeMap<u64, Settings>,
+}
+
+impl SettingsManager {
+    /// Opens the database named by SETTINGS_PATH, or settings.db. A missing
+    /// database is created.
+    pub fn from_env() -> Result<Self, String> {
+        let path = std::env::var("SETTINGS_PATH").unwrap_or_else(|_| String::from("settings.db"));
+        Self::load(PathBuf::from(path))
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let connection = Connection::open(&path)
+            .map_err(|e| format!("Unable to open '{}': {}", path.display(), e))?;
+        Self::open(connection).map_err(|e| format!("Unable to load '{}': {}", path.display(), e))
+    }
+
+    /// A store that is never saved
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self::open(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    /// Bring the database up to date and read every setting from it
+    pub fn open(mut connection: Connection) -> Result<Self, String> {
+        migrate(&mut connection)?;
+        let (guilds, users) = read_all(&connection).map_err(|e| e.to_string())?;
+        Ok(SettingsManager {
+            database: Arc::new(Mutex::new(connection)),
+            updating: Arc::new(tokio::sync::Mutex::new(())),
+            guilds,
+            users,
+        })
+    }
+
+    /// Settings of one guild or user
+    pub fn get(&self, scope: Scope) -> Settings {
+        let settings = match scope {
+            Scope::Guild(id) => self.guilds.get(&id),
+            Scope::User(id) => self.users.get(&id),
+        };
+        settings.cloned().unwrap_or_default()
+    }
+
+    /// The settings a user's requests run with: their guild's, overridden by
+    /// their own
+    pub fn effective(&self, guild: Option<u64>, user: u64) -> Settings {
+        let guild = guild
+            .map(|id| self.get(Scope::Guild(id)))
+            .unwrap_or_default();
+        guild.overridden_by(&self.get(Scope::User(user)))
+    }
+
+    /// Apply the requester's settings to a parsed request
+    pub fn apply(
+        &self,
+        request: &mut ParserResult,
+        guild: Option<u64>,
+        user: u64,
+        compilation: &CompilationManager,
+    ) {
+        let settings = self.effective(guild, user);
+        if settings.is_empty() {
+            return;
+        }
+        let language = compilation.language_of(&request.target);
+        settings.apply(request, language.as_deref());
+    }
+
+    /// The command prefixes a guild responds to: its own if it set any,
+    /// otherwise `default`. Mentioning the bot works everywhere.
+    pub fn prefixes(&self, guild: Option<u64>, default: &str) -> Vec<String> {
+        let prefixes = guild.and_then(|id| self.guilds.get(&id));
+        match prefixes {
+            Some(settings) if !settings.prefixes.is_empty() => settings.prefixes.clone(),
+            _ => vec![default.to_string()],
+        }
+    }
+
+    /// Options a guild places before the user's own on every ;asm
+    pub fn asm_flags(&self, guild: Option<u64>) -> Vec<String> {
+        guild
+            .and_then(|id| self.guilds.get(&id))
+            .map(|settings| settings.asm_flags.clone())
+            .unwrap_or_default()
+    }
+
+    /// Change the settings of one guild or user. The change is only kept
+    /// once it has been written to the database. `manager` is locked to read
+    /// the current settings and to swap in the new ones, but not while
+    /// saving, since every message needs it for the prefixes.
+    pub async fn update(
+        manager: &RwLock<SettingsManager>,
+        scope: Scope,
+        change: impl FnOnce(&mut Settings) -> Result<(), String>,
+    ) -> Result<Settings, String> {
+        let updating = manager.read().await.updating.clone();
+        let _updating = updating.lock().await;
+
+        let (database, mut updated) = {
+            let manager = manager.read().await;
+            (manager.database.clone(), manager.get(scope))
+        };
+        change(&mut updated)?;
+
+        let saved = updated.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = database.lock().unwrap();
+            save(&mut connection, scope, &saved)
+        })
+        .await
+        .map_err(|e| format!("Unable to save settings: {}", e))?
+        .map_err(|e| format!("Unable to save settings: {}", e))?;
+
+        let mut manager = manager.write().await;
+        let (settings, id) = match scope {
+            Scope::Guild(id) => (&mut manager.guilds, id),
+            Scope::User(id) => (&mut manager.users, id),
+        };
+        if updated.is_empty() {
+            settings.remove(&id);
+        } else {
+            settings.insert(id, updated.clone());
+        }
+        Ok(updated)
+    }
+}
+
+/// Bring a database up to SCHEMA_VERSION. New databases start at version 0.
+pub fn migrate(connection: &mut Connection) -> Result<(), String> {
+    let version: u64 = connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .map_err(|e| format!("Unable to read the schema version: {}", e))?;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "Settings were written by a newer version (schema {}, this build reads up to {})",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    let run = |transaction: &Transaction| {
+        for migration in &MIGRATIONS[version as usize..] {
+            transaction.execute_batch(migration)?;
+        }
+        transaction.pragma_update(None, "user_version", SCHEMA_VERSION)
+    };
+    let transaction = connection
+        .transaction()
+        .map_err(|e| format!("Unable to migrate settings: {}", e))?;
+    run(&transaction)
+        .and_then(|_| transaction.commit())
+        .map_err(|e| format!("Unable to migrate settings: {}", e))
+}
+
+type ScopeSettings = BTreeMap<u64, Settings>;
+
+/// Every guild's and user's settings
+fn read_all(connection: &Connection) -> rusqlite::Result<(ScopeSettings, ScopeSettings)> {
+    let mut scopes = (BTreeMap::new(), BTreeMap::new());
+    fn entry(scopes: &mut (ScopeSettings, ScopeSettings), kind: String, id: i64) -> &mut Settings {
+        let settings = if kind == "guild" {
+            &mut scopes.0
+        } else {
+            &mut scopes.1
+        };
+        settings.entry(id as u64).or_default()
+    }
+
+    let mut statement = connection
+        .prepare("SELECT scope, id, output, language, prefixes, asm_flags FROM settings")?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let settings = entry(&mut scopes, row.get(0)?, row.get(1)?);
+        settings.output = row
+            .get::<_, Option<String>>(2)?
+            .and_then(|output| parse_output(&output));
+        settings.language = row.get(3)?;
+        settings.prefixes = row
+            .get::<_, String>(4)?
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        settings.asm_flags = serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default();
+    }
+
+    let mut statement =
+        connection.prepare("SELECT scope, id, language, compiler, flags FROM language_settings")?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let settings = entry(&mut scopes, row.get(0)?, row.get(1)?);
+        let language: String = row.get(2)?;
+        if let Some(compiler) = row.get::<_, Option<String>>(3)? {
+            settings.compilers.insert(language.clone(), compiler);
+        }
+        if let Some(flags) = row.get::<_, Option<String>>(4)? {
+            settings
+                .flags
+                .insert(language, serde_json::from_str(&flags).unwrap_or_default());
+        }
+    }
+    Ok(scopes)
+}
+
+/// Replace everything stored for one guild or user with `settings`
+fn save(connection: &mut Connection, scope: Scope, settings: &Settings) -> rusqlite::Result<()> {
+    let (kind, id) = scope.key();
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "DELETE FROM settings WHERE scope = ?1 AND id = ?2",
+        params![kind, id],
+    )?;
+    transaction.execute(
+        "DELETE FROM language_settings WHERE scope = ?1 AND id = ?2",
+        params![kind, id],
+    )?;
+
+    if !settings.is_empty() {
+        transaction.execute(
+            "INSERT INTO settings (scope, id, output, language, prefixes, asm_flags)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                kind,
+                id,
+                settings.output.map(output_name),
+                settings.language,
+                settings.prefixes.join(" "),
+                serde_json::to_string(&settings.asm_flags).unwrap(),
+            ],
+        )?;
+
+        let languages = settings.compilers.keys().chain(settings.flags.keys());
+        for language in languages.collect::<BTreeSet<_>>() {
+            let flags = settings.flags.get(language);
+            transaction.execute(
+                "INSERT INTO language_settings (scope, id, language, compiler, flags)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    kind,
+                    id,
+                    language,
+                    settings.compilers.get(language),
+                    flags.map(|flags| serde_json::to_string(flags).unwrap()),
+                ],
+            )?;
+        }
+    }
+    transaction.commit()
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -17,6 +18,8 @@ pub struct StatsManager {
     leave_queue: u64,
     join_queue: u64,
     last_presence: u64,
+    /// Compilations moved to another backend, keyed by "from -> to"
+    failovers: HashMap<String, u64>,
 }
 
 impl StatsManager {
@@ -28,6 +31,7 @@ impl StatsManager {
             shards: 0,
             boot_count: Vec::new(),
             last_presence: 0,
+            failovers: HashMap::new(),
         }
     }
 
@@ -78,6 +82,19 @@ impl StatsManager {
         self.shards
     }
 
+    /// Registers a compilation that was retried on another backend
+    pub fn record_failover(&mut self, from: &str, to: &str) {
+        *self
+            .failovers
+            .entry(format!("{} -> {}", from, to))
+            .or_insert(0) += 1;
+    }
+
+    /// Failovers recorded since the last call
+    pub fn take_failovers(&mut self) -> HashMap<String, u64> {
+        std::mem::take(&mut self.failovers)
+    }
+
     pub fn add_shard(&mut self, server_count: u64) {
         self.shards += 1;
         self.boot_count.push(server_count);
@@ -111,6 +128,9 @@ impl StatsManager {
 
         let (server_count, shard_count) = {
             let mut stats = read.get::<StatsManagerCache>().unwrap().lock().await;
+            for (route, count) in stats.take_failovers() {
+                info!("Backend failover {}: {} compilation(s)", route, count);
+            }
             if !stats.take_dirty() {
                 return;
             }
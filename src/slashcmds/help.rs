@@ -5,7 +5,10 @@ use serenity::all::{
 use serenity::client::Context;
 use serenity::framework::standard::CommandResult;
 
-use crate::{cache::ConfigCache, utls::constants::*};
+use crate::{
+    cache::{ConfigCache, SettingsCache},
+    utls::constants::*,
+};
 
 pub async fn help(ctx: &Context, msg: &CommandInteraction) -> CommandResult {
     let data = ctx.data.read().await;
@@ -14,13 +17,23 @@ pub async fn help(ctx: &Context, msg: &CommandInteraction) -> CommandResult {
     let dbl_link = botinfo.get("DISCORDBOTS_LINK").unwrap();
     let github_link = botinfo.get("GITHUB_LINK").unwrap();
     let stats_link = botinfo.get("STATS_LINK").unwrap();
+    let prefix = data
+        .get::<SettingsCache>()
+        .unwrap()
+        .read()
+        .await
+        .prefixes(
+            msg.guild_id.map(|id| id.get()),
+            botinfo.get("BOT_PREFIX").unwrap(),
+        )
+        .swap_remove(0);
 
     let hello_embed = CreateEmbed::new()
         .color(COLOR_OKAY)
         .description(format!(
             "Hello! I can compile code for you. To compile code, \
             use the `{0}compile` command. type `{0}help compile` for more information.",
-            botinfo.get("BOT_PREFIX").unwrap()
+            prefix
         ))
         .thumbnail(ICON_HELP);
 
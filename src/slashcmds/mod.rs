@@ -3,3 +3,5 @@ pub mod format;
 pub mod help;
 pub mod invite;
 pub mod ping;
+pub mod settings;
+pub mod status;
@@ -0,0 +1,160 @@
+use serenity::all::{
+    CommandInteraction, CreateInteractionResponse, CreateInteractionResponseMessage,
+    ResolvedOption, ResolvedValue,
+};
+use serenity::framework::standard::{CommandError, CommandResult};
+use serenity::prelude::*;
+
+use crate::cache::{CompilerCache, SettingsCache};
+use crate::managers::compilation::CompilationManager;
+use crate::managers::settings::{Scope, Setting, SettingsManager};
+use crate::utls::discordhelpers::embeds;
+use crate::utls::parser::shortname_to_qualified;
+
+pub async fn settings(ctx: &Context, command: &CommandInteraction) -> CommandResult {
+    let options = command.data.options();
+    let Some(ResolvedOption {
+        name: subcommand,
+        value: ResolvedValue::SubCommand(options),
+        ..
+    }) = options.first()
+    else {
+        return Err(CommandError::from("Unknown settings command."));
+    };
+    let string = |key: &str| {
+        options
+            .iter()
+            .find(|option| option.name == key)
+            .and_then(|option| match option.value {
+                ResolvedValue::String(value) => Some(value),
+                _ => None,
+            })
+    };
+
+    let (scope, title) = match string("scope") {
+        Some("server") => {
+            let guild = command.guild_id.ok_or_else(|| {
+                CommandError::from("Server settings can only be changed from within a server.")
+            })?;
+            (Scope::Guild(guild.get()), "Server settings")
+        }
+        _ => (Scope::User(command.user.id.get()), "Your settings"),
+    };
+    let setting = string("setting").and_then(Setting::from_name);
+    if let (Scope::User(_), Some(setting)) = (scope, setting) {
+        if setting.guild_only() {
+            return Err(CommandError::from(format!(
+                "The {} can only be changed for the whole server.",
+                setting.name()
+            )));
+        }
+    }
+    let language = string("language");
+
+    let (settings_cache, compilation_manager) = {
+        let data = ctx.data.read().await;
+        (
+            data.get::<SettingsCache>().unwrap().clone(),
+            data.get::<CompilerCache>().unwrap().clone(),
+        )
+    };
+
+    let settings = match *subcommand {
+        "view" => settings_cache.read().await.get(scope),
+        "set" | "reset" => {
+            if let Scope::Guild(_) = scope {
+                let can_manage = command
+                    .member
+                    .as_ref()
+                    .and_then(|member| member.permissions)
+                    .is_some_and(|permissions| permissions.manage_guild());
+                if !can_manage {
+                    return Err(CommandError::from(
+                        "You need the Manage Server permission to change server settings.",
+                    ));
+                }
+            }
+
+            if *subcommand == "set" {
+                let setting = setting.ok_or_else(|| CommandError::from("Choose a setting."))?;
+                let value = string("value").unwrap_or_default();
+                match setting {
+                    // An empty value goes back to the default
+                    _ if value.trim().is_empty() => {}
+                    Setting::Compiler => {
+                        check_compiler(&*compilation_manager.read().await, language, value)?
+                    }
+                    Setting::Language => check_language(&*compilation_manager.read().await, value)?,
+                    _ => {}
+                }
+
+                SettingsManager::update(&settings_cache, scope, |settings| {
+                    settings.set(setting, language, value)
+                })
+                .await?
+            } else {
+                SettingsManager::update(&settings_cache, scope, |settings| {
+                    settings.reset(setting, language);
+                    Ok(())
+                })
+                .await?
+            }
+        }
+        _ => return Err(CommandError::from("Unknown settings command.")),
+    };
+
+    let embed = embeds::build_settings_embed(title, &settings);
+    command
+        .create_response(
+            &ctx.http,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .add_embed(embed)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Make sure a default compiler exists and compiles the language it's set for
+fn check_compiler(
+    compilation_manager: &CompilationManager,
+    language: Option<&str>,
+    compiler: &str,
+) -> Result<(), CommandError> {
+    let compiler = compiler.trim();
+    let Some(compiles) = compilation_manager.language_of(compiler) else {
+        return Err(CommandError::from(format!(
+            "Unknown compiler '{}'. Use a compiler id from the compilers command, e.g. g132.",
+            compiler
+        )));
+    };
+
+    if let Some(language) = language {
+        let language = shortname_to_qualified(&language.trim().to_lowercase()).to_string();
+        if compiles != language {
+            return Err(CommandError::from(format!(
+                "'{}' compiles {}, not {}.",
+                compiler, compiles, language
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Make sure a default language is one the bot can compile
+fn check_language(
+    compilation_manager: &CompilationManager,
+    language: &str,
+) -> Result<(), CommandError> {
+    let language = shortname_to_qualified(&language.trim().to_lowercase()).to_string();
+    if !compilation_manager.is_target_supported(&language) {
+        return Err(CommandError::from(format!(
+            "Unknown language '{}'. Use a language from the languages command, e.g. c++.",
+            language
+        )));
+    }
+    Ok(())
+}
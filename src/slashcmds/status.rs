@@ -0,0 +1,23 @@
+use serenity::all::{
+    CommandInteraction, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use serenity::{framework::standard::CommandResult, prelude::*};
+
+use crate::{cache::HealthCache, utls::discordhelpers::embeds};
+
+pub async fn status(ctx: &Context, msg: &CommandInteraction) -> CommandResult {
+    let statuses = {
+        let data = ctx.data.read().await;
+        data.get::<HealthCache>().unwrap().statuses()
+    };
+
+    let emb = embeds::build_status_embed(&statuses);
+
+    msg.create_response(
+        &ctx.http,
+        CreateInteractionResponse::Message(CreateInteractionResponseMessage::new().add_embed(emb)),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Tests for the flags that control ;asm filters and syntax.
+
+use godbolt::CompilationFilters;
+
+use crate::apis::godbolt::inline_opcodes;
+use crate::backends::godbolt::{apply_asm_flags, take_asm_flags};
+
+#[test]
+fn takes_only_asm_flags() {
+    let mut options: Vec<String> = ["-O2", "--att", "-std=c++20", "--binary"]
+        .iter()
+        .map(|opt| opt.to_string())
+        .collect();
+
+    let flags = take_asm_flags(&mut options);
+
+    assert_eq!(flags, ["--att", "--binary"]);
+    assert_eq!(options, ["-O2", "-std=c++20"]);
+}
+
+#[test]
+fn later_flags_override_defaults() {
+    let mut filters = CompilationFilters {
+        intel: Some(true),
+        directives: Some(true),
+        ..Default::default()
+    };
+    let flags: Vec<String> = ["--att", "--keep-directives", "--intel", "--lib-functions"]
+        .iter()
+        .map(|flag| flag.to_string())
+        .collect();
+
+    apply_asm_flags(&mut filters, &flags);
+
+    assert_eq!(filters.intel, Some(true));
+    assert_eq!(filters.directives, Some(false));
+    assert_eq!(filters.library_code, Some(false));
+    assert_eq!(filters.binary, None);
+}
+
+#[test]
+fn inlines_binary_encodings() {
+    let mut response = serde_json::json!({
+        "code": 0,
+        "asm": [
+            { "text": "square(int):" },
+            { "text": "        mov     eax, edi", "opcodes": ["89", "f8"] },
+            { "text": "        imul    eax, edi", "opcodes": ["0f", "af", "c7"] },
+            { "text": "        ret", "opcodes": ["c3"] }
+        ]
+    });
+
+    inline_opcodes(&mut response);
+
+    let text: Vec<&str> = response["asm"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        text,
+        [
+            "square(int):",
+            "89 f8     mov     eax, edi",
+            "0f af c7  imul    eax, edi",
+            "c3        ret",
+        ]
+    );
+}
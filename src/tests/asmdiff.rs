@@ -0,0 +1,38 @@
+//! Tests for diffing assembly with ;asmdiff.
+
+use crate::commands::asmdiff::{diff_assembly, normalize_assembly, split_sides};
+
+#[test]
+fn splits_sides() {
+    let (first, second) =
+        split_sides(";asmdiff g132 -O2 | clang1701 -O3\n```cpp\nint x;\n```").unwrap();
+
+    assert_eq!(first, ";asmdiff g132 -O2\n```cpp\nint x;\n```");
+    assert_eq!(second, ";asmdiff clang1701 -O3\n```cpp\nint x;\n```");
+    assert!(split_sides(";asmdiff g132 -O2\n```cpp\nint x;\n```").is_err());
+    assert!(split_sides(";asmdiff g132 | \n```cpp\nint x;\n```").is_err());
+    assert!(split_sides(";asmdiff a | b | c").is_err());
+}
+
+#[test]
+fn normalizes_whitespace() {
+    assert_eq!(
+        normalize_assembly("square(int):\n        mov     eax, edi\n\n        ret  "),
+        "square(int):\nmov eax, edi\nret\n"
+    );
+}
+
+#[test]
+fn diffs_listings() {
+    let diff = diff_assembly(
+        "f:\n  mov eax, 1\n  ret",
+        "f:\n  mov  eax, 1\n  nop\n  ret",
+        "gcc -O2",
+        "gcc -O3",
+    );
+
+    assert!(diff.starts_with("--- gcc -O2\n+++ gcc -O3\n"));
+    assert!(diff.contains("+nop\n"));
+    assert!(!diff.contains("-mov"));
+    assert!(diff_assembly("f:\n ret", "f:\n    ret\n", "a", "b").is_empty());
+}
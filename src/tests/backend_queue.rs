@@ -0,0 +1,71 @@
+//! Tests for per-backend queues, both on their own and as used by
+//! `CompilationManager`.
+
+use futures_util::poll;
+use serenity::model::user::User;
+use tokio::sync::mpsc;
+
+use crate::tests::failover::{manager, request, Behavior};
+use crate::utls::backend_queue::BackendQueue;
+
+#[tokio::test]
+async fn reports_positions_in_arrival_order() {
+    let queue = BackendQueue::new(1, 2);
+    let running = queue.acquire(None).await.unwrap();
+
+    let (second_updates, mut second_positions) = mpsc::unbounded_channel();
+    let (third_updates, mut third_positions) = mpsc::unbounded_channel();
+    let second = queue.acquire(Some(&second_updates));
+    let third = queue.acquire(Some(&third_updates));
+    tokio::pin!(second, third);
+
+    assert!(poll!(&mut second).is_pending());
+    assert!(poll!(&mut third).is_pending());
+    assert_eq!(second_positions.try_recv(), Ok(1));
+    assert_eq!(third_positions.try_recv(), Ok(2));
+
+    drop(running);
+    let permit = second.await;
+    assert!(permit.is_some());
+    assert_eq!(second_positions.try_recv(), Ok(0));
+
+    assert!(poll!(&mut third).is_pending());
+    assert_eq!(third_positions.try_recv(), Ok(1));
+
+    drop(permit);
+    assert!(third.await.is_some());
+}
+
+#[tokio::test]
+async fn rejects_when_full() {
+    let queue = BackendQueue::new(1, 1);
+    let _running = queue.acquire(None).await.unwrap();
+
+    let waiting = queue.acquire(None);
+    tokio::pin!(waiting);
+    assert!(poll!(&mut waiting).is_pending());
+
+    assert!(queue.acquire(None).await.is_none());
+}
+
+#[tokio::test]
+async fn busy_backend_rejects_request() {
+    let cm = manager(&[("godbolt", "g132", Behavior::Succeed)]).with_queue("godbolt", 0, 0);
+
+    let result = cm.compile(&request("c++"), &User::default(), None).await;
+
+    assert!(result.err().unwrap().to_string().contains("is busy"));
+}
+
+#[tokio::test]
+async fn busy_backend_fails_over() {
+    let cm = manager(&[
+        ("godbolt", "g132", Behavior::Succeed),
+        ("wandbox", "gcc-head", Behavior::Succeed),
+    ])
+    .with_queue("godbolt", 0, 0);
+
+    let result = cm.compile(&request("c++"), &User::default(), None).await;
+
+    assert_eq!(result.unwrap().details.compiler, "gcc-head");
+}
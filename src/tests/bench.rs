@@ -0,0 +1,53 @@
+//! Tests for ;bench timing statistics and argument handling.
+
+use serenity::model::user::User;
+
+use crate::commands::bench::split_runs;
+use crate::tests::failover::{manager, request, Behavior};
+use crate::utls::bench::BenchStats;
+
+#[test]
+fn computes_stats() {
+    let stats = BenchStats::from_times(&[12, 10, 11, 13]).unwrap();
+
+    assert_eq!(stats.runs, 4);
+    assert_eq!(stats.min, 10);
+    assert_eq!(stats.median, 11.5);
+    assert_eq!(stats.mean, 11.5);
+    assert!((stats.stddev - 1.118).abs() < 0.001);
+    assert!(!stats.is_noisy());
+}
+
+#[test]
+fn flags_noisy_runs() {
+    let stats = BenchStats::from_times(&[10, 30, 11]).unwrap();
+
+    assert_eq!(stats.median, 11.0);
+    assert!(stats.is_noisy());
+    assert!(BenchStats::from_times(&[]).is_none());
+}
+
+#[test]
+fn splits_run_count() {
+    let (runs, content) = split_runs(";bench 10 c++ -O2\n```cpp\nint main() {}\n```").unwrap();
+    assert_eq!(runs, 10);
+    assert_eq!(content, ";bench c++ -O2\n```cpp\nint main() {}\n```");
+
+    let (runs, content) = split_runs(";bench c++\n```cpp\nint main() {}\n```").unwrap();
+    assert_eq!(runs, 5);
+    assert_eq!(content, ";bench c++\n```cpp\nint main() {}\n```");
+
+    assert!(split_runs(";bench 1000 c++").is_err());
+}
+
+#[tokio::test]
+async fn rejects_backends_without_timing() {
+    let cm = manager(&[("wandbox", "gcc-head", Behavior::Succeed)]);
+
+    let result = cm
+        .benchmark(&request("gcc-head"), &User::default(), 5, None)
+        .await;
+
+    let error = result.err().unwrap().to_string();
+    assert!(error.contains("doesn't report execution times"));
+}
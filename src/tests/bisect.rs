@@ -0,0 +1,98 @@
+//! Tests for ordering compiler versions and bisecting them for ;bisect.
+
+use std::cell::RefCell;
+
+use godbolt::Compiler;
+
+use crate::commands::bisect::{family_compilers, parse_version, split_family};
+use crate::utls::bisect::bisect_versions;
+
+fn compiler(id: &str, name: &str, semver: &str) -> Compiler {
+    Compiler {
+        id: id.to_string(),
+        name: name.to_string(),
+        lang: String::from("c++"),
+        semver: Some(semver.to_string()),
+        instruction_set: Some(String::from("amd64")),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn parses_versions() {
+    assert_eq!(parse_version("13.2"), Some(vec![13, 2]));
+    assert_eq!(parse_version("v19.38"), Some(vec![19, 38]));
+    assert_eq!(parse_version("4.9.4-rc1"), Some(vec![4, 9, 4]));
+    assert_eq!(parse_version("(trunk)"), None);
+}
+
+#[test]
+fn orders_family_by_version() {
+    let compilers = vec![
+        compiler("g132", "x86-64 gcc 13.2", "13.2"),
+        compiler("clang1701", "x86-64 clang 17.0.1", "17.0.1"),
+        compiler("g95", "x86-64 gcc 9.5", "9.5"),
+        compiler("gsnapshot", "x86-64 gcc (trunk)", "(trunk)"),
+        compiler("g132a", "x86-64 gcc 13.2 (assertions)", "13.2"),
+        compiler("g1021", "x86-64 gcc 10.2", "10.2"),
+        compiler("g102", "x86-64 gcc 10.2", "10.2"),
+    ];
+
+    let ids: Vec<String> = family_compilers(&compilers, "GCC")
+        .into_iter()
+        .map(|c| c.id)
+        .collect();
+
+    assert_eq!(ids, vec!["g95", "g1021", "g132"]);
+}
+
+#[tokio::test]
+async fn finds_first_changed_version() {
+    let probed = RefCell::new(Vec::new());
+    let bisection = bisect_versions(40, |index| {
+        probed.borrow_mut().push(index);
+        async move { Ok(index >= 23) }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(bisection.last_same, 22);
+    assert_eq!(bisection.first_changed, Some(23));
+    assert_eq!(bisection.outcomes[22], Some(false));
+    assert_eq!(bisection.outcomes[23], Some(true));
+    assert!(bisection.probes() <= 8);
+    assert_eq!(probed.borrow().len(), bisection.probes());
+}
+
+#[tokio::test]
+async fn reports_no_change() {
+    let bisection = bisect_versions(10, |_| async { Ok(0) }).await.unwrap();
+
+    assert_eq!(bisection.first_changed, None);
+    assert_eq!(bisection.last_same, 9);
+    assert_eq!(bisection.probes(), 2);
+}
+
+#[tokio::test]
+async fn stops_on_failed_probe() {
+    let result = bisect_versions(10, |index| async move {
+        if index == 9 {
+            Err("unavailable".into())
+        } else {
+            Ok(0)
+        }
+    })
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn splits_family() {
+    let (family, content) =
+        split_family(";bisect GCC -O2 | 5\n```cpp\nint main() {}\n```").unwrap();
+
+    assert_eq!(family, "gcc");
+    assert_eq!(content, ";bisect -O2 | 5\n```cpp\nint main() {}\n```");
+    assert!(split_family(";bisect\n```cpp\nint main() {}\n```").is_err());
+}
@@ -0,0 +1,48 @@
+//! Tests for rendering `;asm --cfg` control-flow graphs as DOT.
+
+use crate::commands::asm::take_cfg_flag;
+use crate::utls::cfg::to_dot;
+
+#[test]
+fn renders_functions_as_clusters() {
+    let cfg = serde_json::json!({
+        "main": {
+            "nodes": [
+                { "id": "main", "label": "main:\n  test edi, edi\n  je .L2" },
+                { "id": ".L2", "label": ".L2:\n  ret" }
+            ],
+            "edges": [{ "from": "main", "to": ".L2", "arrows": "to", "color": "green" }]
+        }
+    });
+
+    let dot = to_dot(&cfg).unwrap();
+    assert!(dot.starts_with("digraph cfg {\n"));
+    assert!(dot.contains("subgraph cluster_0 {"));
+    assert!(dot.contains("label=\"main\";"));
+    assert!(dot.contains("\"f0_main\" [label=\"main:\\l  test edi, edi\\l  je .L2\\l\"];"));
+    assert!(dot.contains("\"f0_main\" -> \"f0_.L2\" [color=\"green\"];"));
+}
+
+#[test]
+fn reads_nested_asm_graphs() {
+    let cfg = serde_json::json!({
+        "asm": {
+            "f(int)": { "nodes": [{ "id": 0, "label": "mov eax, \"x\"" }], "edges": [] },
+            "g()": { "nodes": [{ "id": 0, "label": "ret" }], "edges": [] }
+        }
+    });
+
+    let dot = to_dot(&cfg).unwrap();
+    assert!(dot.contains("\"f0_0\" [label=\"mov eax, \\\"x\\\"\\l\"];"));
+    assert!(dot.contains("\"f1_0\" [label=\"ret\\l\"];"));
+    assert!(to_dot(&serde_json::json!({})).is_none());
+}
+
+#[test]
+fn takes_cfg_flag() {
+    let mut options = vec![String::from("-O2"), String::from("--cfg")];
+
+    assert!(take_cfg_flag(&mut options));
+    assert_eq!(options, vec![String::from("-O2")]);
+    assert!(!take_cfg_flag(&mut options));
+}
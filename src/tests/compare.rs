@@ -0,0 +1,58 @@
+//! Tests for splitting the compilers off a ;compare command and showing
+//! the comparison.
+
+use serenity::model::user::User;
+
+use crate::commands::compare::split_targets;
+use crate::managers::compilation::CompilationDetails;
+use crate::tests::failover::{manager, Behavior};
+use crate::utls::discordhelpers::embeds::{build_compare_embed, CompareEntry};
+
+#[test]
+fn splits_targets_from_options() {
+    let cm = manager(&[
+        ("godbolt", "g132", Behavior::Succeed),
+        ("wandbox", "gcc-head", Behavior::Succeed),
+    ]);
+
+    let (targets, assembly, content) = split_targets(
+        ";compare g132 GCC-HEAD g132 --asm -O2 | input\n```cpp\nint main() {}\n```",
+        &cm,
+    )
+    .unwrap();
+
+    assert_eq!(targets, vec!["g132", "gcc-head"]);
+    assert!(assembly);
+    assert_eq!(
+        content,
+        ";compare g132 -O2 | input\n```cpp\nint main() {}\n```"
+    );
+}
+
+#[test]
+fn requires_two_targets() {
+    let cm = manager(&[("godbolt", "g132", Behavior::Succeed)]);
+
+    let result = split_targets(";compare g132 -O2\n```int main() {}```", &cm);
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn shows_backend_execution_time() {
+    let entry = |exec_time| CompareEntry {
+        target: String::from("g132"),
+        result: Ok(CompilationDetails {
+            language: String::from("c++"),
+            compiler: String::from("x86-64 gcc 13.2"),
+            success: true,
+            exec_time,
+            ..Default::default()
+        }),
+    };
+    let embed = build_compare_embed(&User::default(), &[entry(Some(12)), entry(None)]);
+    let embed = serde_json::to_value(&embed).unwrap();
+
+    assert_eq!(embed["fields"][0]["value"], "c++ | x86-64 gcc 13.2 | 12ms");
+    assert_eq!(embed["fields"][1]["value"], "c++ | x86-64 gcc 13.2 | n/a");
+}
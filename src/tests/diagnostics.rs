@@ -0,0 +1,185 @@
+//! Tests for parsing and rendering compiler diagnostics.
+
+use crate::utls::diagnostics::{full_log, parse, render, sources, summarize, Diagnostic, Severity};
+use crate::utls::parser::{ParserResult, SourceFile};
+
+const SOURCE: &str = "int main() {\n\tint x = y;\n    return 0;\n}";
+
+/// SOURCE as godbolt reports it
+fn godbolt() -> Vec<SourceFile> {
+    sources(&ParserResult::default(), SOURCE, &["<source>", "example"])
+}
+
+fn diagnostic(file: &str, line: usize, column: Option<usize>, severity: Severity) -> Diagnostic {
+    Diagnostic {
+        file: file.to_string(),
+        line,
+        column,
+        severity,
+        message: String::from("message"),
+    }
+}
+
+#[test]
+fn parses_gcc_and_clang() {
+    let output = "<source>: In function 'int main()':\n\
+                  <source>:2:10: error: 'y' was not declared in this scope\n\
+                  <source>:2:6: warning: unused variable 'x' [-Wunused-variable]\n\
+                  /usr/include/stdio.h:12:3: note: declared here\n\
+                  Main.java:3: error: cannot find symbol";
+
+    let diagnostics = parse(output);
+
+    assert_eq!(diagnostics.len(), 4);
+    assert_eq!(diagnostics[0].file, "<source>");
+    assert_eq!(diagnostics[0].line, 2);
+    assert_eq!(diagnostics[0].column, Some(10));
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "'y' was not declared in this scope");
+    assert_eq!(diagnostics[1].severity, Severity::Warning);
+    assert_eq!(diagnostics[2].severity, Severity::Note);
+    assert_eq!(diagnostics[3].file, "Main.java");
+    assert_eq!(diagnostics[3].column, None);
+}
+
+#[test]
+fn parses_msvc_and_spcomp() {
+    let output = "example.cpp(2): error C2065: 'y': undeclared identifier\n\
+                  example.cpp(2,10): warning C4101: 'x': unreferenced local variable\n\
+                  /tmp/plugin.sp(5) : error 017: undefined symbol \"y\"";
+
+    let diagnostics = parse(output);
+
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].message, "'y': undeclared identifier");
+    assert_eq!(diagnostics[1].column, Some(10));
+    assert_eq!(diagnostics[2].file, "/tmp/plugin.sp");
+    assert_eq!(diagnostics[2].line, 5);
+    assert_eq!(diagnostics[2].message, "undefined symbol \"y\"");
+}
+
+#[test]
+fn parses_rustc() {
+    let output = "error[E0425]: cannot find value `y` in this scope\n \
+                  --> <source>:2:13\n  \
+                  |\n\
+                  2 |     let x = y;\n  \
+                  |             ^ not found in this scope\n\n\
+                  error: aborting due to 1 previous error";
+
+    let diagnostics = parse(output);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+    assert_eq!(diagnostics[0].column, Some(13));
+    assert_eq!(
+        diagnostics[0].message,
+        "cannot find value `y` in this scope"
+    );
+}
+
+#[test]
+fn renders_snippet_with_caret() {
+    let diag = diagnostic("<source>", 2, Some(10), Severity::Error);
+
+    assert_eq!(
+        render(&diag, &godbolt()),
+        "error: message\n  --> 2:10\n  |\n2 |  int x = y;\n  |          ^\n"
+    );
+}
+
+#[test]
+fn renders_location_only_outside_user_code() {
+    let header = diagnostic("/usr/include/stdio.h", 2, Some(3), Severity::Note);
+    let past_end = diagnostic("<source>", 40, None, Severity::Error);
+    let other = diagnostic("util.h", 2, Some(3), Severity::Error);
+
+    assert_eq!(
+        render(&header, &godbolt()),
+        "note: message\n --> /usr/include/stdio.h:2:3\n"
+    );
+    assert_eq!(
+        render(&past_end, &godbolt()),
+        "error: message\n --> <source>:40\n"
+    );
+    assert_eq!(
+        render(&other, &godbolt()),
+        "error: message\n --> util.h:2:3\n"
+    );
+}
+
+#[test]
+fn renders_snippets_from_every_backends_main_file() {
+    for (file, names) in [
+        ("/app/example.cpp", &["<source>", "example"][..]),
+        ("prog.cc", &["prog"]),
+        ("prog.java", &["prog"]),
+        ("file0.code.java", &["file0"]),
+        ("/tmp/run/main.rs", &["main.rs"]),
+        ("Main.java", &["main.java"]),
+    ] {
+        let diag = diagnostic(file, 2, Some(10), Severity::Error);
+        let sources = sources(&ParserResult::default(), SOURCE, names);
+        assert!(render(&diag, &sources).contains("int x = y;"), "{}", file);
+    }
+}
+
+#[test]
+fn renders_snippets_from_each_project_file() {
+    let header = SourceFile {
+        name: String::from("main.h"),
+        code: String::from("#pragma once\nint f(;\n"),
+    };
+    let request = ParserResult {
+        files: vec![
+            SourceFile {
+                name: String::from("main.cpp"),
+                code: SOURCE.to_string(),
+            },
+            header,
+        ],
+        ..Default::default()
+    };
+    let sources = sources(&request, SOURCE, &["<source>", "example"]);
+
+    let in_header = diagnostic("/app/main.h", 2, Some(7), Severity::Error);
+    let rendered = render(&in_header, &sources);
+    assert!(rendered.contains("int f(;"));
+    assert!(!rendered.contains("int x = y;"));
+
+    let in_main = diagnostic("/app/main.cpp", 2, Some(10), Severity::Error);
+    assert!(render(&in_main, &sources).contains("int x = y;"));
+    let elsewhere = diagnostic("/app/util.h", 2, Some(10), Severity::Error);
+    assert_eq!(
+        render(&elsewhere, &sources),
+        "error: message\n --> /app/util.h:2:10\n"
+    );
+}
+
+#[test]
+fn summarizes_errors_first() {
+    let output = "<source>:2:6: warning: unused variable 'x'\n\
+                  <source>:2:10: error: 'y' was not declared in this scope\n\
+                  <source>:3:5: note: in expansion of macro";
+
+    let summary = summarize(output, &godbolt()).unwrap();
+
+    assert!(summary.text.starts_with("error: 'y' was not declared"));
+    assert!(!summary.text.contains("unused variable"));
+    assert_eq!(summary.counts(), "1 error, 1 warning");
+    assert!(summarize("Segmentation fault", &godbolt()).is_none());
+}
+
+#[test]
+fn attaches_long_logs() {
+    let short = "<source>:2:10: error: 'y' was not declared in this scope";
+    let long = format!(
+        "{}\n{}",
+        short,
+        "<source>:3:5: note: candidate\n".repeat(60)
+    );
+
+    assert!(full_log(short).is_none());
+    assert_eq!(full_log(&"x".repeat(2000)), Some("x".repeat(2000)));
+    assert_eq!(full_log(&long).as_deref(), Some(long.trim()));
+}
@@ -0,0 +1,95 @@
+//! Tests for the ;ir, ;ast, ;remarks and ;gccdump output modes.
+
+use crate::backends::godbolt::{dump_output, DumpKind};
+use crate::utls::discordhelpers::embeds::fits_in_embed;
+
+fn response(value: serde_json::Value) -> godbolt::CompilationResult {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn reads_llvm_ir() {
+    let response = response(serde_json::json!({
+        "code": 0,
+        "irOutput": { "asm": [{ "text": "define i32 @square(i32 %x) {" }, { "text": "}" }] }
+    }));
+
+    assert_eq!(
+        dump_output(&response, DumpKind::LlvmIr, false).unwrap(),
+        "define i32 @square(i32 %x) {\n}"
+    );
+    assert!(dump_output(&response, DumpKind::ClangAst, false).is_none());
+}
+
+#[test]
+fn reads_ast_as_lines_or_text() {
+    let lines = response(serde_json::json!({
+        "code": 0,
+        "astOutput": [{ "text": "TranslationUnitDecl" }, { "text": "`-FunctionDecl main" }]
+    }));
+    let text = response(serde_json::json!({ "code": 0, "astOutput": "TranslationUnitDecl" }));
+
+    assert_eq!(
+        dump_output(&lines, DumpKind::ClangAst, false).unwrap(),
+        "TranslationUnitDecl\n`-FunctionDecl main"
+    );
+    assert_eq!(
+        dump_output(&text, DumpKind::ClangAst, false).unwrap(),
+        "TranslationUnitDecl"
+    );
+}
+
+#[test]
+fn formats_remarks() {
+    let response = response(serde_json::json!({
+        "code": 0,
+        "optOutput": [
+            {
+                "Pass": "inline",
+                "optType": "Passed",
+                "DebugLoc": { "Line": 3, "Column": 12 },
+                "displayString": "'square' inlined into 'main'"
+            },
+            {
+                "Pass": "loop-vectorize",
+                "optType": "Missed",
+                "DebugLoc": { "Line": 5, "Column": 5 },
+                "Args": [{ "String": "loop not vectorized" }, { "String": ": too costly" }]
+            }
+        ]
+    }));
+
+    assert_eq!(
+        dump_output(&response, DumpKind::OptRemarks, false).unwrap(),
+        "3:12 Passed inline: 'square' inlined into 'main'\n\
+         5:5 Missed loop-vectorize: loop not vectorized: too costly"
+    );
+}
+
+#[test]
+fn gcc_dump_lists_passes_until_one_is_chosen() {
+    let response = response(serde_json::json!({
+        "code": 0,
+        "gccDumpOutput": {
+            "all": [{ "name": "original (tree)" }, { "name": "optimized (tree)" }],
+            "currentPassOutput": ";; Function square"
+        }
+    }));
+
+    let listing = dump_output(&response, DumpKind::GccDump, false).unwrap();
+    assert!(listing.contains("--pass=<name>"));
+    assert!(listing.contains("optimized (tree)"));
+    assert_eq!(
+        dump_output(&response, DumpKind::GccDump, true).unwrap(),
+        ";; Function square"
+    );
+}
+
+#[test]
+fn long_output_is_attached() {
+    let short = "define i32 @f() {\n}";
+    let long = "  %1 = add nsw i32 %0, 1\n".repeat(500);
+
+    assert!(fits_in_embed(short));
+    assert!(!fits_in_embed(&long));
+}
@@ -0,0 +1,172 @@
+//! Tests for cross-backend failover in `CompilationManager`, using fake
+//! backends that either succeed, reject the request or are unavailable.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use serenity::async_trait;
+use serenity::builder::CreateEmbed;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+use tokio::sync::Mutex;
+
+use crate::backends::backend::{BackendUnavailable, CompilerBackend, ResolvedTarget};
+use crate::managers::compilation::{CompilationDetails, CompilationManager, CompilationResult};
+use crate::managers::stats::StatsManager;
+use crate::utls::discordhelpers::embeds;
+use crate::utls::parser::ParserResult;
+
+#[derive(Clone, Copy)]
+pub enum Behavior {
+    Succeed,
+    Reject,
+    Unavailable,
+}
+
+struct FakeBackend {
+    id: &'static str,
+    compiler: &'static str,
+    behavior: Behavior,
+}
+
+#[async_trait]
+impl CompilerBackend for FakeBackend {
+    fn id(&self) -> &'static str {
+        self.id
+    }
+
+    fn display_name(&self) -> &'static str {
+        self.id
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        (target == "c++" || target == self.compiler).then(|| ResolvedTarget {
+            language: String::from("c++"),
+            compiler: self.compiler.to_string(),
+        })
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.execute(request, author, false).await
+    }
+
+    async fn execute(
+        &self,
+        request: &ParserResult,
+        author: &User,
+        _boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        match self.behavior {
+            Behavior::Succeed => {
+                let details = CompilationDetails {
+                    language: String::from("c++"),
+                    compiler: request.target.clone(),
+                    success: true,
+                    executed: true,
+                    counts: Some(String::from("1 error")),
+                    service: Some(self.id),
+                    ..Default::default()
+                };
+                let embed =
+                    CreateEmbed::new().footer(embeds::build_result_footer(author, &details));
+                Ok(CompilationResult { details, embed })
+            }
+            Behavior::Reject => Err(CommandError::from("bad request")),
+            Behavior::Unavailable => Err(CommandError::from(BackendUnavailable(format!(
+                "{} is down",
+                self.id
+            )))),
+        }
+    }
+
+    fn languages(&self) -> Vec<String> {
+        vec![String::from("c++")]
+    }
+
+    fn list_compilers(
+        &self,
+        _language: &str,
+        _filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        Ok(vec![self.compiler.to_string()])
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub fn manager(backends: &[(&'static str, &'static str, Behavior)]) -> CompilationManager {
+    CompilationManager::with_backends(
+        backends
+            .iter()
+            .map(|&(id, compiler, behavior)| {
+                Box::new(FakeBackend {
+                    id,
+                    compiler,
+                    behavior,
+                }) as Box<dyn CompilerBackend>
+            })
+            .collect(),
+    )
+}
+
+pub fn request(target: &str) -> ParserResult {
+    ParserResult {
+        target: target.to_string(),
+        code: String::from("int main() {}"),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn fails_over_to_equivalent_compiler() {
+    let mut cm = manager(&[
+        ("godbolt", "g132", Behavior::Unavailable),
+        ("wandbox", "gcc-head", Behavior::Succeed),
+    ]);
+    let stats = Arc::new(Mutex::new(StatsManager::new()));
+    cm.attach_stats(stats.clone());
+
+    let result = cm
+        .execute(&request("g132"), &User::default(), true, None)
+        .await;
+
+    let result = result.expect("failover did not happen");
+    assert_eq!(result.details.compiler, "gcc-head");
+    let embed = serde_json::to_value(&result.embed).unwrap();
+    assert_eq!(
+        embed["footer"]["text"],
+        " | c++ | gcc-head | 1 error | wandbox (godbolt unavailable)"
+    );
+    let failovers = stats.lock().await.take_failovers();
+    assert_eq!(failovers.get("godbolt -> wandbox"), Some(&1));
+}
+
+#[tokio::test]
+async fn does_not_retry_rejected_requests() {
+    let cm = manager(&[
+        ("godbolt", "g132", Behavior::Reject),
+        ("wandbox", "gcc-head", Behavior::Succeed),
+    ]);
+
+    let result = cm.compile(&request("c++"), &User::default(), None).await;
+
+    assert_eq!(result.err().unwrap().to_string(), "bad request");
+}
+
+#[tokio::test]
+async fn reports_original_error_without_fallback() {
+    let cm = manager(&[
+        ("godbolt", "g132", Behavior::Unavailable),
+        ("wandbox", "gcc-head", Behavior::Unavailable),
+    ]);
+
+    let result = cm.compile(&request("c++"), &User::default(), None).await;
+
+    assert_eq!(result.err().unwrap().to_string(), "godbolt is down");
+}
@@ -0,0 +1,131 @@
+//! Tests for health tracking and circuit breaking.
+
+use std::time::Duration;
+
+use serenity::model::user::User;
+
+use crate::managers::health::{Circuit, HealthManager};
+use crate::tests::failover::{manager, request, Behavior};
+
+fn failure() -> Result<(), String> {
+    Err(String::from("connection refused"))
+}
+
+#[test]
+fn opens_after_consecutive_failures() {
+    let health = HealthManager::new();
+    for _ in 0..4 {
+        health.record("godbolt.org", Duration::from_millis(100), failure());
+    }
+    assert!(health.check("godbolt.org").is_ok());
+
+    health.record("godbolt.org", Duration::from_millis(100), failure());
+    let message = health.check("godbolt.org").unwrap_err();
+    assert!(message.contains("paused"));
+    assert!(message.contains("connection refused"));
+}
+
+#[test]
+fn opens_on_failure_rate() {
+    let health = HealthManager::new();
+    for _ in 0..5 {
+        health.record("wandbox.org", Duration::from_millis(100), Ok(()));
+        health.record("wandbox.org", Duration::from_millis(100), failure());
+    }
+
+    assert!(health.check("wandbox.org").is_err());
+}
+
+fn open(health: &HealthManager, service: &str) {
+    for _ in 0..5 {
+        health.record(service, Duration::from_millis(100), failure());
+    }
+    health.end_open_period(service);
+}
+
+#[test]
+fn half_open_lets_one_probe_through() {
+    let health = HealthManager::new();
+    open(&health, "godbolt.org");
+
+    assert!(health.check("godbolt.org").is_ok());
+    assert_eq!(health.statuses()[0].circuit, Circuit::HalfOpen);
+    let message = health.check("godbolt.org").unwrap_err();
+    assert!(message.contains("recovering"));
+
+    health.record("godbolt.org", Duration::from_millis(100), Ok(()));
+    assert_eq!(health.statuses()[0].circuit, Circuit::Closed);
+    assert!(health.check("godbolt.org").is_ok());
+    assert!(health.check("godbolt.org").is_ok());
+}
+
+#[test]
+fn failed_probe_reopens() {
+    let health = HealthManager::new();
+    open(&health, "godbolt.org");
+
+    assert!(health.check("godbolt.org").is_ok());
+    health.record("godbolt.org", Duration::from_millis(100), failure());
+
+    assert!(matches!(health.statuses()[0].circuit, Circuit::Open(_)));
+    assert!(health.check("godbolt.org").unwrap_err().contains("paused"));
+}
+
+#[test]
+fn abandoned_probe_is_replaced() {
+    let health = HealthManager::new();
+    open(&health, "godbolt.org");
+
+    assert!(health.check("godbolt.org").is_ok());
+    health.abandon("godbolt.org");
+    assert!(health.check("godbolt.org").is_ok());
+    assert!(health.check("godbolt.org").is_err());
+}
+
+#[test]
+fn reports_percentiles() {
+    let health = HealthManager::new();
+    health.register("piston");
+    for ms in 1..=100 {
+        health.record("piston", Duration::from_millis(ms), Ok(()));
+    }
+
+    let statuses = health.statuses();
+    let status = &statuses[0];
+    assert_eq!(status.requests, 100);
+    assert_eq!(status.success_rate, Some(1.0));
+    assert_eq!(
+        status.latency,
+        Some([
+            Duration::from_millis(50),
+            Duration::from_millis(95),
+            Duration::from_millis(99)
+        ])
+    );
+    assert_eq!(status.circuit, Circuit::Closed);
+}
+
+#[tokio::test]
+async fn rejected_requests_do_not_count_as_failures() {
+    let cm = manager(&[("godbolt", "g132", Behavior::Reject)]);
+    for _ in 0..10 {
+        let _ = cm.compile(&request("c++"), &User::default(), None).await;
+    }
+
+    let statuses = cm.health().statuses();
+    assert_eq!(statuses[0].success_rate, Some(1.0));
+    assert!(statuses[0].last_error.is_none());
+}
+
+#[tokio::test]
+async fn open_circuit_fails_fast() {
+    let cm = manager(&[("godbolt", "g132", Behavior::Unavailable)]);
+    for _ in 0..5 {
+        let result = cm.compile(&request("c++"), &User::default(), None).await;
+        assert_eq!(result.err().unwrap().to_string(), "godbolt is down");
+    }
+
+    let result = cm.compile(&request("c++"), &User::default(), None).await;
+
+    assert!(result.err().unwrap().to_string().contains("paused"));
+}
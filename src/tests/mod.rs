@@ -1,7 +1,51 @@
+#[cfg(test)]
+pub mod asm_filters;
+#[cfg(test)]
+pub mod asmdiff;
+#[cfg(test)]
+pub mod backend_queue;
+#[cfg(test)]
+pub mod bench;
+#[cfg(test)]
+pub mod bisect;
 pub mod boilerplate;
+#[cfg(test)]
+pub mod cfg;
+#[cfg(test)]
+pub mod compare;
 pub mod cpp;
 #[cfg(test)]
+pub mod diagnostics;
+#[cfg(test)]
+pub mod dump;
+#[cfg(test)]
+pub mod failover;
+#[cfg(test)]
+pub mod health;
+#[cfg(test)]
 #[cfg_attr(feature = "clippy", allow(clippy::all))]
 pub mod parser;
 #[cfg(test)]
+pub mod piston;
+#[cfg(test)]
+pub mod presets;
+#[cfg(test)]
+pub mod project;
+#[cfg(test)]
+pub mod result_cache;
+#[cfg(test)]
+pub mod routing;
+#[cfg(test)]
+pub mod sandbox;
+#[cfg(test)]
+pub mod settings;
+#[cfg(test)]
+pub mod source_map;
+#[cfg(test)]
 pub mod sourcepawn;
+#[cfg(test)]
+pub mod test_runner;
+#[cfg(test)]
+pub mod tool;
+#[cfg(test)]
+pub mod triage;
@@ -1,5 +1,6 @@
 use crate::managers::compilation::CompilationManager;
 use crate::utls::parser::get_components;
+use crate::utls::presets::Preset;
 #[cfg(test)]
 use serenity::model::user::User;
 use std::sync::Arc;
@@ -30,6 +31,28 @@ async fn standard_parse() {
     assert_eq!(parser_result.code, "int main() {}\n");
 }
 
+#[tokio::test]
+async fn standard_parse_mention() {
+    let dummy_user = User::default();
+    let input = indoc::indoc!(
+        "<@1234> compile c++ -O3
+        ```
+        int main() {}
+        ```"
+    );
+
+    let reply = None;
+    let result = get_components(input, &dummy_user, None, &reply, false).await;
+    if result.is_err() {
+        panic!("Parser failed.");
+    }
+
+    let parser_result = result.unwrap();
+    assert_eq!(parser_result.target, "c++");
+    assert_eq!(parser_result.options, ["-O3"]);
+    assert_eq!(parser_result.code, "int main() {}\n");
+}
+
 #[tokio::test]
 async fn standard_parse_args() {
     let dummy_user = User::default();
@@ -321,3 +344,24 @@ async fn parse_url_with_block_stdin() {
     assert_eq!(parser_result.url, "https://pastebin.com/raw/ERqDRZva");
     assert_eq!(parser_result.code, "int main() {}");
 }
+
+#[tokio::test]
+async fn standard_parse_presets() {
+    let dummy_user = User::default();
+    let input = indoc::indoc!(
+        ";compile c++ -O2 --asan --ubsan --asan
+        ```
+        int main() {}
+        ```"
+    );
+
+    let reply = None;
+    let result = get_components(input, &dummy_user, None, &reply, false).await;
+    if result.is_err() {
+        panic!("Parser failed.");
+    }
+
+    let parser_result = result.unwrap();
+    assert_eq!(parser_result.options, ["-O2"]);
+    assert_eq!(parser_result.presets, [Preset::Asan, Preset::Ubsan]);
+}
@@ -0,0 +1,113 @@
+//! Tests for the Piston client against a small stand-in server that serves
+//! a fixed runtime catalog and echoes stdin back as program output.
+
+use serde_json::{json, Value};
+use serenity::model::user::User;
+use warp::Filter;
+
+use crate::apis::piston::PistonService;
+use crate::backends::backend::CompilerBackend;
+use crate::backends::piston::PistonBackend;
+use crate::utls::parser::ParserResult;
+
+async fn stand_in() -> String {
+    let runtimes = warp::get()
+        .and(warp::path!("api" / "v2" / "runtimes"))
+        .map(|| {
+            warp::reply::json(&json!([
+                { "language": "python", "version": "3.9.4", "aliases": ["py"] },
+                { "language": "python", "version": "3.10.0", "aliases": ["py"] },
+                { "language": "javascript", "version": "18.15.0", "aliases": ["js"], "runtime": "node" },
+                { "language": "c++", "version": "10.2.0", "aliases": ["cpp"], "runtime": "gcc" }
+            ]))
+        });
+
+    let execute = warp::post()
+        .and(warp::path!("api" / "v2" / "execute"))
+        .and(warp::body::json())
+        .map(|request: Value| {
+            let stdin = request["stdin"].as_str().unwrap_or_default().to_string();
+            let compile = (request["language"] == "c++").then(
+                || json!({ "stdout": "", "stderr": "", "output": "", "code": 0, "signal": null }),
+            );
+            warp::reply::json(&json!({
+                "language": request["language"],
+                "version": request["version"],
+                "compile": compile,
+                "run": { "stdout": stdin, "stderr": "", "output": stdin, "code": 0, "signal": null }
+            }))
+        });
+
+    let (addr, server) = warp::serve(runtimes.or(execute)).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+    format!("http://{}/", addr)
+}
+
+async fn service() -> PistonService {
+    PistonService::new(reqwest::Client::new(), &stand_in().await)
+        .await
+        .expect("stand-in server is not reachable")
+}
+
+#[tokio::test]
+async fn resolves_newest_version() {
+    let piston = service().await;
+    assert_eq!(piston.get_runtimes().len(), 4);
+
+    let runtime = piston.resolve("python").unwrap();
+    assert_eq!(runtime.version, "3.10.0");
+    assert_eq!(piston.resolve("py").unwrap().version, "3.10.0");
+}
+
+#[tokio::test]
+async fn resolves_compiler_ids() {
+    let piston = service().await;
+
+    assert_eq!(piston.resolve("python-3.9.4").unwrap().version, "3.9.4");
+    assert_eq!(
+        piston.resolve("node-18.15.0").unwrap().language,
+        "javascript"
+    );
+    assert!(piston.resolve("gcc-10.2.0").is_some());
+    assert!(piston.resolve("haskell").is_none());
+}
+
+#[tokio::test]
+async fn executes_through_backend() {
+    let backend = PistonBackend::new(service().await);
+    let request = ParserResult {
+        target: String::from("cpp"),
+        code: String::from("int main() {}"),
+        stdin: String::from("testing 1 2 3"),
+        ..Default::default()
+    };
+
+    let result = backend
+        .execute(&request, &User::default(), false)
+        .await
+        .unwrap();
+    assert!(result.details.success);
+    assert!(result.details.executed);
+    assert_eq!(result.details.language, "c++");
+    assert_eq!(result.details.compiler, "gcc-10.2.0");
+    assert_eq!(result.details.service, Some("piston"));
+
+    let embed = serde_json::to_value(&result.embed).unwrap();
+    assert_eq!(embed["footer"]["text"], " | c++ | gcc-10.2.0 | piston");
+}
+
+#[tokio::test]
+async fn rejects_compiler_options() {
+    let backend = PistonBackend::new(service().await);
+    let request = ParserResult {
+        target: String::from("python"),
+        code: String::from("print(input())"),
+        options: vec![String::from("-O")],
+        ..Default::default()
+    };
+
+    assert!(backend
+        .execute(&request, &User::default(), false)
+        .await
+        .is_err());
+}
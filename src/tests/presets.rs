@@ -0,0 +1,101 @@
+//! Tests for expanding `--asan` style presets into compiler options.
+
+use serenity::model::user::User;
+
+use crate::tests::failover::{manager, request, Behavior};
+use crate::utls::presets::{expand, Preset, Toolchain};
+
+fn options(presets: &[Preset], compiler: &str) -> Vec<String> {
+    expand(presets, compiler, "godbolt.org", true).unwrap()
+}
+
+#[test]
+fn detects_toolchains() {
+    assert_eq!(Toolchain::detect("g132"), Some(Toolchain::Gcc));
+    assert_eq!(Toolchain::detect("cg132"), Some(Toolchain::Gcc));
+    assert_eq!(Toolchain::detect("gcc-13.2.0"), Some(Toolchain::Gcc));
+    assert_eq!(Toolchain::detect("g++"), Some(Toolchain::Gcc));
+    assert_eq!(Toolchain::detect("clang1600"), Some(Toolchain::Clang));
+    assert_eq!(Toolchain::detect("cclang1600"), Some(Toolchain::Clang));
+    assert_eq!(
+        Toolchain::detect("vcpp_v19_latest_x64"),
+        Some(Toolchain::Msvc)
+    );
+    assert_eq!(Toolchain::detect("r1750"), None);
+    assert_eq!(Toolchain::detect("go"), None);
+}
+
+#[test]
+fn spells_sanitizers_per_toolchain() {
+    assert_eq!(
+        options(&[Preset::Asan], "g132"),
+        ["-fsanitize=address", "-fno-omit-frame-pointer", "-g"]
+    );
+    assert_eq!(
+        options(&[Preset::Asan], "vcpp_v19_latest_x64"),
+        ["/fsanitize=address", "/Zi"]
+    );
+    assert!(options(&[Preset::Msan], "clang1600").contains(&String::from("-fsanitize=memory")));
+}
+
+#[test]
+fn merges_shared_options() {
+    let expanded = options(&[Preset::Asan, Preset::Ubsan], "clang1600");
+    assert_eq!(expanded.iter().filter(|option| *option == "-g").count(), 1);
+    assert!(expanded.contains(&String::from("-fsanitize=address")));
+    assert!(expanded.contains(&String::from("-fsanitize=undefined")));
+}
+
+#[test]
+fn rejects_unsupported_pairs() {
+    let error = expand(&[Preset::Ubsan], "vcpp_v19_latest_x64", "godbolt.org", true)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("`--ubsan` isn't supported by MSVC"));
+
+    let error = expand(&[Preset::Msan], "g132", "godbolt.org", true)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("Try a Clang compiler"));
+
+    let error = expand(&[Preset::Asan], "gcc-13.2.0", "wandbox.org", false)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("`--asan` isn't available on wandbox.org"));
+
+    assert!(expand(&[Preset::Asan], "r1750", "godbolt.org", true).is_err());
+    assert!(expand(&[Preset::Asan, Preset::Tsan], "g132", "godbolt.org", true).is_err());
+}
+
+#[test]
+fn allows_build_presets_without_sanitizer_support() {
+    let expanded = expand(&[Preset::Hardened], "gcc-13.2.0", "wandbox.org", false).unwrap();
+    assert!(expanded.contains(&String::from("-D_FORTIFY_SOURCE=2")));
+    assert_eq!(
+        expand(&[Preset::Debug], "vcpp_v19_latest_x64", "godbolt.org", true).unwrap(),
+        ["/Od", "/Zi", "/RTC1"]
+    );
+}
+
+#[tokio::test]
+async fn manager_rejects_sanitizers_on_unsupported_backends() {
+    let cm = manager(&[("wandbox", "gcc-head", Behavior::Succeed)]);
+
+    let mut sanitized = request("c++");
+    sanitized.presets = vec![Preset::Asan];
+    let error = cm
+        .execute(&sanitized, &User::default(), false, None)
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("`--asan` isn't available on wandbox"));
+
+    let mut hardened = request("c++");
+    hardened.presets = vec![Preset::Hardened];
+    let result = cm
+        .execute(&hardened, &User::default(), false, None)
+        .await
+        .unwrap();
+    assert!(result.details.success);
+}
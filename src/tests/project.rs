@@ -0,0 +1,153 @@
+//! Tests for multi-file projects made of code blocks labelled with file
+//! names.
+
+use serenity::model::user::User;
+
+use crate::tests::failover::{manager, request, Behavior};
+use crate::utls::parser::{get_components, SourceFile};
+
+fn file(name: &str, code: &str) -> SourceFile {
+    SourceFile {
+        name: name.to_string(),
+        code: code.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn parses_named_blocks() {
+    let input = indoc::indoc!(
+        ";compile -O2
+        ```h util.h
+        int util();
+        ```
+        ```cpp main.cpp
+        #include \"util.h\"
+        int main() { return util(); }
+        ```
+        ```cpp util.cpp
+        int util() { return 0; }
+        ```"
+    );
+
+    let result = get_components(input, &User::default(), None, &None, true)
+        .await
+        .unwrap();
+
+    assert_eq!(result.target, "c++");
+    assert_eq!(result.options, ["-O2"]);
+    assert!(!result.is_cmake());
+    assert_eq!(
+        result.files,
+        [
+            file(
+                "main.cpp",
+                "#include \"util.h\"\nint main() { return util(); }\n"
+            ),
+            file("util.h", "int util();\n"),
+            file("util.cpp", "int util() { return 0; }\n"),
+        ]
+    );
+    assert_eq!(result.code, result.files[0].code);
+}
+
+#[tokio::test]
+async fn cmake_lists_is_main_file() {
+    let input = indoc::indoc!(
+        ";compile
+        ```
+        some input
+        ```
+        ```cmake CMakeLists.txt
+        add_executable(main main.c)
+        ```
+        ```c main.c
+        int main() {}
+        ```"
+    );
+
+    let result = get_components(input, &User::default(), None, &None, true)
+        .await
+        .unwrap();
+
+    assert_eq!(result.target, "c");
+    assert_eq!(result.stdin, "some input\n");
+    assert!(result.is_cmake());
+    assert_eq!(result.code, "add_executable(main main.c)\n");
+    assert_eq!(result.files.len(), 2);
+    assert_eq!(result.files[1].name, "main.c");
+}
+
+#[tokio::test]
+async fn takes_language_from_extension() {
+    let input = ";compile\n``` main.rs\nfn main() {}\n```";
+
+    let result = get_components(input, &User::default(), None, &None, true)
+        .await
+        .unwrap();
+
+    assert_eq!(result.target, "rust");
+    assert!(result.files.is_empty());
+    assert_eq!(result.code, "fn main() {}\n");
+}
+
+#[tokio::test]
+async fn single_labelled_block_is_not_a_project() {
+    let input = ";compile -O2\n```cpp main.cpp\nint main() {}\n```";
+
+    let result = get_components(input, &User::default(), None, &None, true)
+        .await
+        .unwrap();
+
+    assert_eq!(result.target, "c++");
+    assert_eq!(result.options, ["-O2"]);
+    assert!(result.files.is_empty());
+    assert_eq!(result.code, "int main() {}\n");
+}
+
+#[tokio::test]
+async fn rejects_bad_projects() {
+    let duplicate = ";compile\n```cpp a.cpp\nint a;\n```\n```cpp a.cpp\nint b;\n```";
+    let headers_only = ";compile\n```h a.h\nint a;\n```\n```h b.h\nint b;\n```";
+    let unlabelled =
+        ";compile\n```\n1\n```\n```\n2\n```\n```cpp a.cpp\nint a;\n```\n```h a.h\nint a;\n```";
+
+    for input in [duplicate, headers_only, unlabelled] {
+        let result = get_components(input, &User::default(), None, &None, true).await;
+        assert!(result.is_err(), "{} should fail", input);
+    }
+}
+
+#[tokio::test]
+async fn rejects_backends_without_projects() {
+    let cm = manager(&[("wandbox", "gcc-head", Behavior::Succeed)]);
+    let mut project = request("gcc-head");
+    project.files = vec![file("main.cpp", "int main() {}"), file("util.h", "")];
+
+    let result = cm.execute(&project, &User::default(), false, None).await;
+
+    let error = result.err().unwrap().to_string();
+    assert!(error.contains("can't build multi-file projects"));
+}
+
+#[tokio::test]
+async fn ignores_code_after_the_language() {
+    let input = ";compile javascript\n```js console.log(x)\nconsole.log(1);\n```";
+
+    let result = get_components(input, &User::default(), None, &None, false)
+        .await
+        .unwrap();
+
+    assert!(result.files.is_empty());
+    assert_eq!(result.code, "js console.log(x)\nconsole.log(1);\n");
+}
+
+#[tokio::test]
+async fn ignores_unknown_extensions() {
+    let input = ";compile python\n```py data.txt\nprint(1)\n```";
+
+    let result = get_components(input, &User::default(), None, &None, false)
+        .await
+        .unwrap();
+
+    assert!(result.files.is_empty());
+}
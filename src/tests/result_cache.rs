@@ -0,0 +1,205 @@
+//! Tests for the result cache in `CompilationManager`, using a backend that
+//! counts its upstream calls.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::async_trait;
+use serenity::builder::CreateEmbed;
+use serenity::framework::standard::CommandError;
+use serenity::model::user::User;
+
+use crate::backends::backend::{CompilerBackend, ResolvedTarget};
+use crate::managers::compilation::{CompilationDetails, CompilationManager, CompilationResult};
+use crate::utls::discordhelpers::embeds;
+use crate::utls::parser::ParserResult;
+
+struct CountingBackend {
+    calls: Arc<AtomicUsize>,
+    fail: bool,
+}
+
+#[async_trait]
+impl CompilerBackend for CountingBackend {
+    fn id(&self) -> &'static str {
+        "counting"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "counting"
+    }
+
+    fn resolve(&self, target: &str) -> Option<ResolvedTarget> {
+        (target == "c++").then(|| ResolvedTarget {
+            language: String::from("c++"),
+            compiler: String::from("g132"),
+        })
+    }
+
+    async fn compile(
+        &self,
+        request: &ParserResult,
+        author: &User,
+    ) -> Result<CompilationResult, CommandError> {
+        self.execute(request, author, false).await
+    }
+
+    async fn execute(
+        &self,
+        _request: &ParserResult,
+        author: &User,
+        _boilerplate: bool,
+    ) -> Result<CompilationResult, CommandError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        if self.fail {
+            return Err(CommandError::from("bad request"));
+        }
+        let details = CompilationDetails {
+            language: String::from("c++"),
+            compiler: String::from("g132"),
+            success: true,
+            executed: true,
+            counts: Some(String::from("1 warning")),
+            service: Some(self.display_name()),
+            ..Default::default()
+        };
+        let embed = CreateEmbed::new().footer(embeds::build_result_footer(author, &details));
+        Ok(CompilationResult { details, embed })
+    }
+
+    fn languages(&self) -> Vec<String> {
+        vec![String::from("c++")]
+    }
+
+    fn list_compilers(
+        &self,
+        _language: &str,
+        _filter: Option<&str>,
+    ) -> Result<Vec<String>, CommandError> {
+        Ok(vec![String::from("g132")])
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn manager(fail: bool) -> (CompilationManager, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let backend = CountingBackend {
+        calls: calls.clone(),
+        fail,
+    };
+    (
+        CompilationManager::with_backends(vec![Box::new(backend)]),
+        calls,
+    )
+}
+
+fn request(code: &str) -> ParserResult {
+    ParserResult {
+        target: String::from("c++"),
+        code: code.to_string(),
+        ..Default::default()
+    }
+}
+
+fn footer(result: &CompilationResult) -> String {
+    let embed = serde_json::to_value(&result.embed).unwrap();
+    embed["footer"]["text"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn reuses_identical_requests() {
+    let (cm, calls) = manager(false);
+    let author = User::default();
+
+    let first = cm
+        .execute(&request("int main() {}"), &author, false, None)
+        .await;
+    let second = cm
+        .execute(&request("int main() {}"), &author, false, None)
+        .await;
+
+    assert!(first.is_ok());
+    assert!(footer(&second.unwrap()).ends_with("| cached"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn keeps_the_footer_for_new_requesters() {
+    let (cm, _) = manager(false);
+    let mut first = User::default();
+    first.name = String::from("first");
+    let mut second = User::default();
+    second.name = String::from("second");
+
+    cm.execute(&request("int main() {}"), &first, false, None)
+        .await
+        .unwrap();
+    let cached = cm
+        .execute(&request("int main() {}"), &second, false, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        footer(&cached),
+        "second | c++ | g132 | 1 warning | counting | cached"
+    );
+}
+
+#[tokio::test]
+async fn keys_on_code_and_mode() {
+    let (cm, calls) = manager(false);
+    let author = User::default();
+
+    cm.execute(&request("int main() {}"), &author, false, None)
+        .await
+        .unwrap();
+    cm.execute(&request("int main() { }"), &author, false, None)
+        .await
+        .unwrap();
+    cm.execute(&request("int main() {}"), &author, true, None)
+        .await
+        .unwrap();
+    cm.compile(&request("int main() {}"), &author, None)
+        .await
+        .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn coalesces_in_flight_requests() {
+    let (cm, calls) = manager(false);
+    let author = User::default();
+    let request = request("int main() {}");
+
+    let (first, second) = tokio::join!(
+        cm.execute(&request, &author, false, None),
+        cm.execute(&request, &author, false, None)
+    );
+
+    assert!(first.is_ok() && second.is_ok());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn does_not_store_errors() {
+    let (cm, calls) = manager(true);
+    let author = User::default();
+
+    let first = cm.compile(&request("int main() {}"), &author, None).await;
+    let second = cm.compile(&request("int main() {}"), &author, None).await;
+
+    assert_eq!(first.err().unwrap().to_string(), "bad request");
+    assert_eq!(second.err().unwrap().to_string(), "bad request");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,97 @@
+//! Tests for `CompilationManager` resolution under routing configs, reusing
+//! the fake backends from the failover tests.
+
+use std::collections::HashMap;
+
+use serenity::model::user::User;
+
+use crate::backends::routing::RoutingConfig;
+use crate::tests::failover::{manager, request, Behavior};
+
+fn config() -> RoutingConfig {
+    RoutingConfig {
+        preferred_backends: HashMap::new(),
+        denied_compilers: HashMap::new(),
+        denied_languages: HashMap::new(),
+        aliases: HashMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn prefers_configured_backend() {
+    let mut routing = config();
+    routing
+        .preferred_backends
+        .insert(String::from("c++"), String::from("wandbox"));
+    let cm = manager(&[
+        ("godbolt", "g132", Behavior::Reject),
+        ("wandbox", "gcc-head", Behavior::Succeed),
+    ])
+    .with_routing(routing);
+
+    let result = cm.compile(&request("c++"), &User::default(), None).await;
+
+    assert!(result.is_ok());
+    assert!(cm.is_target_supported("c++"));
+}
+
+#[tokio::test]
+async fn skips_denied_compilers() {
+    let mut routing = config();
+    routing
+        .denied_compilers
+        .insert(String::from("godbolt"), vec![String::from("g132")]);
+    let cm = manager(&[
+        ("godbolt", "g132", Behavior::Reject),
+        ("wandbox", "g132", Behavior::Succeed),
+    ])
+    .with_routing(routing);
+
+    let result = cm
+        .execute(&request("g132"), &User::default(), false, None)
+        .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn skips_denied_languages() {
+    let mut routing = config();
+    routing
+        .denied_languages
+        .insert(String::from("godbolt"), vec![String::from("c++")]);
+    let cm = manager(&[("godbolt", "g132", Behavior::Succeed)]).with_routing(routing);
+
+    assert!(!cm.is_target_supported("c++"));
+}
+
+#[tokio::test]
+async fn rewrites_aliased_targets() {
+    let mut routing = config();
+    routing
+        .aliases
+        .insert(String::from("c++23"), String::from("g132"));
+    let cm = manager(&[("godbolt", "g132", Behavior::Succeed)]).with_routing(routing);
+
+    let result = cm.compile(&request("c++23"), &User::default(), None).await;
+
+    assert_eq!(result.unwrap().details.compiler, "g132");
+}
+
+#[test]
+fn missing_fields_keep_defaults() {
+    let routing: RoutingConfig =
+        serde_json::from_str(r#"{ "aliases": { "c++23": "g132" } }"#).unwrap();
+
+    assert_eq!(routing.preferred_backend("scala"), Some("wandbox"));
+    assert!(routing.is_compiler_denied("wandbox", "ghc-head"));
+    assert_eq!(routing.alias("c++23"), "g132");
+}
+
+#[test]
+fn example_matches_defaults() {
+    let example: RoutingConfig =
+        serde_json::from_str(include_str!("../../routing.example.json")).unwrap();
+
+    assert_eq!(example, RoutingConfig::default());
+}
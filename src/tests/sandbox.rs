@@ -0,0 +1,258 @@
+//! Tests for the local sandbox. Running programs needs root (to switch to
+//! the sandbox user and create namespaces), so those tests are ignored by
+//! default; run with `cargo test sandbox -- --ignored`.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::utls::sandbox::{self, SandboxLimits, SandboxUsers};
+
+/// A uid no account on a test machine uses
+const TEST_UID: u32 = 61999;
+
+async fn sh(script: &str, stdin: &str, limits: SandboxLimits) -> sandbox::ProcessOutput {
+    let dir = tempfile::tempdir().unwrap();
+    sh_in(dir.path(), script, stdin, limits).await
+}
+
+async fn sh_in(
+    dir: &Path,
+    script: &str,
+    stdin: &str,
+    limits: SandboxLimits,
+) -> sandbox::ProcessOutput {
+    sandbox::hand_over(dir, TEST_UID).unwrap();
+    let args = vec![String::from("-c"), script.to_string()];
+    let mounts: Vec<PathBuf> = sandbox::DEFAULT_MOUNTS.iter().map(PathBuf::from).collect();
+    sandbox::run("sh", &args, dir, stdin, &limits, TEST_UID, &mounts)
+        .await
+        .expect("sandbox failed to start")
+}
+
+#[test]
+fn rejects_privileged_users() {
+    assert!(SandboxUsers::new(0..=10).is_err());
+    let own = unsafe { libc::getuid() };
+    assert!(SandboxUsers::new(own..=own).is_err());
+    let (first, last) = (61001, 61000);
+    assert!(SandboxUsers::new(first..=last).is_err());
+}
+
+#[tokio::test]
+async fn reserves_each_user_once() {
+    let users = SandboxUsers::new(61000..=61001).unwrap();
+    let first = users.acquire().await;
+    let second = users.acquire().await;
+    assert_ne!(first.uid, second.uid);
+
+    // Both are taken until one is returned
+    let waiting = tokio::time::timeout(Duration::from_millis(50), users.acquire()).await;
+    assert!(waiting.is_err());
+
+    let uid = first.uid;
+    drop(first);
+    assert_eq!(users.acquire().await.uid, uid);
+}
+
+#[tokio::test]
+#[ignore]
+async fn captures_output_and_exit_code() {
+    let output = sh(
+        "echo out; echo err >&2; exit 3",
+        "",
+        SandboxLimits::default(),
+    )
+    .await;
+
+    assert_eq!(output.stdout, "out\n");
+    assert_eq!(output.stderr, "err\n");
+    assert_eq!(output.exit_code, Some(3));
+    assert!(!output.success());
+    assert!(!output.timed_out);
+}
+
+#[tokio::test]
+#[ignore]
+async fn reports_the_terminating_signal() {
+    let output = sh("kill -SEGV $$", "", SandboxLimits::default()).await;
+
+    assert_eq!(output.exit_code, None);
+    assert_eq!(output.signal, Some(libc::SIGSEGV));
+}
+
+#[tokio::test]
+#[ignore]
+async fn feeds_stdin() {
+    let output = sh("cat", "testing 1 2 3", SandboxLimits::default()).await;
+
+    assert!(output.success());
+    assert_eq!(output.stdout, "testing 1 2 3");
+}
+
+#[tokio::test]
+#[ignore]
+async fn kills_on_timeout() {
+    let limits = SandboxLimits {
+        timeout: Duration::from_millis(500),
+        ..Default::default()
+    };
+    let output = sh("sleep 10 & sleep 10", "", limits).await;
+
+    assert!(output.timed_out);
+    assert!(!output.success());
+}
+
+#[tokio::test]
+#[ignore]
+async fn truncates_large_output() {
+    let output = sh("yes", "", SandboxLimits::default()).await;
+
+    assert!(output.truncated);
+    assert_eq!(output.stdout.len(), 64 * 1024);
+}
+
+#[tokio::test]
+#[ignore]
+async fn enforces_file_size_limit() {
+    let limits = SandboxLimits {
+        file_size_bytes: 1024,
+        ..Default::default()
+    };
+    let output = sh("head -c 4096 /dev/zero > big", "", limits).await;
+
+    assert!(!output.success());
+}
+
+#[tokio::test]
+#[ignore]
+async fn has_no_network_interfaces() {
+    // Only the loopback device exists in a fresh network namespace
+    let output = sh("grep -c : /proc/self/net/dev", "", SandboxLimits::default()).await;
+
+    assert_eq!(output.stdout.trim(), "1");
+}
+
+#[tokio::test]
+#[ignore]
+async fn runs_as_the_sandbox_user() {
+    let output = sh("id -u; id -g; id -G", "", SandboxLimits::default()).await;
+
+    let expected = format!("{0}\n{0}\n{0}\n", TEST_UID);
+    assert_eq!(output.stdout, expected);
+}
+
+#[tokio::test]
+#[ignore]
+async fn cant_read_the_bots_files() {
+    let secrets = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(secrets.path(), "BOT_TOKEN=secret").unwrap();
+    std::fs::set_permissions(secrets.path(), std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    let script = format!(
+        "cat {} /proc/$PPID/environ /proc/{}/environ",
+        secrets.path().display(),
+        std::process::id()
+    );
+    let output = sh(&script, "", SandboxLimits::default()).await;
+
+    assert!(!output.stdout.contains("secret"));
+    assert!(!output.stdout.contains("PATH="));
+    assert!(!output.success());
+}
+
+#[tokio::test]
+#[ignore]
+async fn cant_see_the_hosts_files() {
+    // Readable by everyone, but outside the mounts
+    let dir = tempfile::tempdir().unwrap();
+    let visible = dir.path().join("visible");
+    std::fs::write(&visible, "BOT_TOKEN=secret").unwrap();
+    std::fs::set_permissions(dir.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::fs::set_permissions(&visible, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let script = format!("cat {}", visible.display());
+    let output = sh(&script, "", SandboxLimits::default()).await;
+
+    assert!(!output.stdout.contains("secret"));
+    assert!(!output.success());
+}
+
+#[tokio::test]
+#[ignore]
+async fn sees_only_its_own_processes() {
+    // The namespace's init and the shell itself
+    let output = sh("echo /proc/[0-9]*", "", SandboxLimits::default()).await;
+
+    assert_eq!(output.stdout, "/proc/1 /proc/2\n");
+}
+
+#[tokio::test]
+#[ignore]
+async fn can_only_write_to_its_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = sh_in(
+        dir.path(),
+        "touch /usr/x || touch /x",
+        "",
+        SandboxLimits::default(),
+    )
+    .await;
+    assert!(!output.success());
+
+    let output = sh_in(
+        dir.path(),
+        "touch /sandbox/x /tmp/y",
+        "",
+        SandboxLimits::default(),
+    )
+    .await;
+    assert!(output.success());
+    assert!(dir.path().join("x").exists());
+    assert!(dir.path().join("y").exists());
+}
+
+#[tokio::test]
+#[ignore]
+async fn cant_signal_the_bot() {
+    let script = format!("kill -0 {}", std::process::id());
+    let output = sh(&script, "", SandboxLimits::default()).await;
+
+    assert!(!output.success());
+}
+
+#[tokio::test]
+#[ignore]
+async fn kills_processes_that_leave_the_group() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("alive");
+
+    // setsid moves the sleeper out of the sandbox's process group, and the
+    // redirects keep it from holding the output pipes open
+    let script = "setsid sh -c 'sleep 1; touch alive' </dev/null >/dev/null 2>&1 & sleep 0.2";
+    let output = sh_in(dir.path(), script, "", SandboxLimits::default()).await;
+    assert!(output.success());
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!file.exists());
+}
+
+#[tokio::test]
+#[ignore]
+async fn counts_only_the_requests_processes() {
+    // The bot's own threads don't use up the process limit
+    let limits = SandboxLimits {
+        processes: 8,
+        ..Default::default()
+    };
+    let output = sh("sleep 0.1 & sleep 0.1 & sleep 0.1 & wait", "", limits).await;
+    assert!(output.success());
+
+    let output = sh(
+        "for i in 1 2 3 4 5 6 7 8 9 10; do sleep 1 & done; wait",
+        "",
+        limits,
+    )
+    .await;
+    assert!(!output.stderr.is_empty());
+}
@@ -0,0 +1,372 @@
+//! Tests for the settings store, its migrations, and applying settings to
+//! requests.
+
+use rusqlite::Connection;
+use serenity::model::user::User;
+use tokio::sync::RwLock;
+
+use crate::managers::settings::{
+    find_prefix, migrate, Scope, Setting, Settings, SettingsManager, MAX_PREFIXES, MIGRATIONS,
+    SCHEMA_VERSION,
+};
+use crate::utls::diagnostics::OutputMode;
+use crate::utls::parser::{
+    get_components_with_language, split_mention, strip_mention, ParserResult,
+};
+use crate::utls::presets::Preset;
+
+fn request(target: &str, options: &[&str]) -> ParserResult {
+    ParserResult {
+        target: target.to_string(),
+        options: options.iter().map(|option| option.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn schema_version(connection: &Connection) -> u64 {
+    connection
+        .pragma_query_value(None, "user_version", |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn migrates_new_databases() {
+    let mut connection = Connection::open_in_memory().unwrap();
+    migrate(&mut connection).unwrap();
+    assert_eq!(schema_version(&connection), SCHEMA_VERSION);
+
+    // Migrating again changes nothing
+    migrate(&mut connection).unwrap();
+    assert_eq!(schema_version(&connection), SCHEMA_VERSION);
+}
+
+#[test]
+fn upgrades_old_databases() {
+    let connection = Connection::open_in_memory().unwrap();
+    connection.execute_batch(MIGRATIONS[0]).unwrap();
+    connection
+        .execute_batch(
+            "INSERT INTO settings (scope, id, output) VALUES ('guild', 1, 'raw');
+             PRAGMA user_version = 1;",
+        )
+        .unwrap();
+
+    let manager = SettingsManager::open(connection).unwrap();
+
+    let settings = manager.get(Scope::Guild(1));
+    assert_eq!(settings.output, Some(OutputMode::Raw));
+    assert!(settings.prefixes.is_empty());
+    assert!(settings.asm_flags.is_empty());
+}
+
+#[test]
+fn rejects_newer_databases() {
+    let mut connection = Connection::open_in_memory().unwrap();
+    connection
+        .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+        .unwrap();
+    assert!(migrate(&mut connection).is_err());
+}
+
+#[tokio::test]
+async fn saves_and_loads() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.db");
+
+    let manager = RwLock::new(SettingsManager::load(path.clone()).unwrap());
+    SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Compiler, Some("cpp"), "clang1810")?;
+        settings.set(Setting::Flags, Some("c"), "-O2 -Wall")?;
+        settings.set(Setting::Language, None, "cpp")?;
+        settings.set(Setting::Prefix, None, "! ?")?;
+        settings.set(Setting::Asm, None, "--att")
+    })
+    .await
+    .unwrap();
+    SettingsManager::update(&manager, Scope::User(1), |settings| {
+        settings.set(Setting::Output, None, "raw")
+    })
+    .await
+    .unwrap();
+    SettingsManager::update(&manager, Scope::User(1), |settings| {
+        settings.reset(None, None);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    let loaded = SettingsManager::load(path).unwrap();
+    assert_eq!(
+        loaded.get(Scope::Guild(1)),
+        manager.read().await.get(Scope::Guild(1))
+    );
+    let settings = loaded.get(Scope::Guild(1));
+    assert_eq!(settings.compilers.get("c++").unwrap(), "clang1810");
+    assert_eq!(settings.flags.get("c").unwrap(), &["-O2", "-Wall"]);
+    assert_eq!(settings.language.as_deref(), Some("c++"));
+    assert!(loaded.get(Scope::User(1)).is_empty());
+}
+
+#[tokio::test]
+async fn keeps_settings_when_saving_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("settings.db");
+    let manager = RwLock::new(SettingsManager::load(path.clone()).unwrap());
+
+    Connection::open(&path)
+        .unwrap()
+        .execute_batch("DROP TABLE language_settings;")
+        .unwrap();
+    let result = SettingsManager::update(&manager, Scope::User(1), |settings| {
+        settings.set(Setting::Output, None, "raw")
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert!(manager.read().await.get(Scope::User(1)).is_empty());
+}
+
+#[tokio::test]
+async fn users_override_their_guild() {
+    let manager = RwLock::new(SettingsManager::in_memory());
+    SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Compiler, Some("c++"), "g132")?;
+        settings.set(Setting::Output, None, "raw")
+    })
+    .await
+    .unwrap();
+    SettingsManager::update(&manager, Scope::User(2), |settings| {
+        settings.set(Setting::Compiler, Some("c++"), "clang1810")
+    })
+    .await
+    .unwrap();
+
+    let effective = manager.read().await.effective(Some(1), 2);
+    assert_eq!(effective.compilers.get("c++").unwrap(), "clang1810");
+    assert_eq!(effective.output, Some(OutputMode::Raw));
+
+    let elsewhere = manager.read().await.effective(None, 2);
+    assert_eq!(elsewhere.output, None);
+}
+
+#[test]
+fn validates_changes() {
+    let mut settings = Settings::default();
+    assert!(settings.set(Setting::Compiler, None, "g132").is_err());
+    assert!(settings.set(Setting::Output, None, "loud").is_err());
+    assert!(settings.set(Setting::Flags, Some("c++"), "-DX='").is_err());
+    assert!(settings.is_empty());
+
+    settings
+        .set(Setting::Flags, Some("c++"), "-O2 -Wall")
+        .unwrap();
+    settings.set(Setting::Flags, Some("c"), "-O1").unwrap();
+    settings.reset(Some(Setting::Flags), Some("cpp"));
+    assert!(!settings.flags.contains_key("c++"));
+    assert!(settings.flags.contains_key("c"));
+
+    settings.reset(None, None);
+    assert!(settings.is_empty());
+
+    // An empty value goes back to the default, like for flags
+    settings
+        .set(Setting::Compiler, Some("c++"), "g132")
+        .unwrap();
+    settings.set(Setting::Compiler, Some("c++"), "  ").unwrap();
+    assert!(settings.is_empty());
+}
+
+#[tokio::test]
+async fn keeps_concurrent_updates() {
+    let manager = RwLock::new(SettingsManager::in_memory());
+    let compiler = SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Compiler, Some("c++"), "g132")
+    });
+    let output = SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Output, None, "raw")
+    });
+    let (compiler, output) = tokio::join!(compiler, output);
+    compiler.unwrap();
+    output.unwrap();
+
+    let settings = manager.read().await.get(Scope::Guild(1));
+    assert_eq!(settings.compilers.get("c++").unwrap(), "g132");
+    assert_eq!(settings.output, Some(OutputMode::Raw));
+}
+
+#[test]
+fn applies_to_requests() {
+    let mut settings = Settings::default();
+    settings
+        .set(Setting::Compiler, Some("c++"), "clang1810")
+        .unwrap();
+    settings
+        .set(Setting::Flags, Some("c++"), "-O2 --asan")
+        .unwrap();
+    settings.set(Setting::Output, None, "raw").unwrap();
+
+    let mut language = request("c++", &["-O3"]);
+    settings.apply(&mut language, Some("c++"));
+    assert_eq!(language.target, "clang1810");
+    assert_eq!(language.options, ["-O2", "-O3"]);
+    assert_eq!(language.presets, [Preset::Asan]);
+    assert_eq!(language.output, OutputMode::Raw);
+
+    // Naming a compiler keeps it, but still gets the language's flags
+    let mut compiler = request("g132", &[]);
+    settings.apply(&mut compiler, Some("c++"));
+    assert_eq!(compiler.target, "g132");
+    assert_eq!(compiler.options, ["-O2"]);
+
+    let mut other = request("rust", &[]);
+    settings.apply(&mut other, Some("rust"));
+    assert_eq!(other.target, "rust");
+    assert!(other.options.is_empty());
+}
+
+#[tokio::test]
+async fn guild_prefixes() {
+    let manager = RwLock::new(SettingsManager::in_memory());
+    assert_eq!(manager.read().await.prefixes(Some(1), ";"), [";"]);
+
+    SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Prefix, None, "! !! !")
+    })
+    .await
+    .unwrap();
+    assert_eq!(manager.read().await.prefixes(Some(1), ";"), ["!", "!!"]);
+    assert_eq!(manager.read().await.prefixes(Some(2), ";"), [";"]);
+    assert_eq!(manager.read().await.prefixes(None, ";"), [";"]);
+
+    // Users' settings never change the prefix of the guild they're in
+    let effective = manager.read().await.effective(Some(1), 3);
+    assert_eq!(effective.prefixes, ["!", "!!"]);
+
+    SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.reset(Some(Setting::Prefix), None);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(manager.read().await.prefixes(Some(1), ";"), [";"]);
+}
+
+#[test]
+fn validates_prefixes() {
+    let mut settings = Settings::default();
+    assert!(settings
+        .set(Setting::Prefix, None, "averyveryverylongprefix")
+        .is_err());
+    assert!(settings.set(Setting::Prefix, None, "`").is_err());
+    assert!(settings.set(Setting::Prefix, None, "<@1234>").is_err());
+    let many: Vec<String> = (0..=MAX_PREFIXES).map(|i| format!("{}!", i)).collect();
+    assert!(settings
+        .set(Setting::Prefix, None, &many.join(" "))
+        .is_err());
+    assert!(settings.is_empty());
+    assert!(Setting::Prefix.guild_only());
+}
+
+#[tokio::test]
+async fn guild_asm_flags() {
+    let manager = RwLock::new(SettingsManager::in_memory());
+    assert!(manager.read().await.asm_flags(Some(1)).is_empty());
+
+    let mut settings = Settings::default();
+    assert!(settings.set(Setting::Asm, None, "--att -O2").is_err());
+    assert!(settings.set(Setting::Asm, None, "--att --cfg").is_err());
+    assert!(Setting::Asm.guild_only());
+
+    SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Asm, None, "--att --binary")
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        manager.read().await.asm_flags(Some(1)),
+        ["--att", "--binary"]
+    );
+    assert!(manager.read().await.asm_flags(Some(2)).is_empty());
+    assert!(manager.read().await.asm_flags(None).is_empty());
+}
+
+#[test]
+fn finds_longest_prefix() {
+    let prefixes = vec![String::from("!"), String::from("!!")];
+    assert_eq!(find_prefix(&prefixes, "!!compile c++"), Some("!!"));
+    assert_eq!(find_prefix(&prefixes, "!asm c++"), Some("!"));
+    assert_eq!(find_prefix(&prefixes, ";compile c++"), None);
+}
+
+#[test]
+fn strips_mentions() {
+    assert_eq!(
+        split_mention("<@1234> compile c++"),
+        Some((1234, "compile c++"))
+    );
+    assert_eq!(split_mention("<@!1234>asm c"), Some((1234, "asm c")));
+    assert_eq!(split_mention(";compile c++"), None);
+    assert_eq!(strip_mention("<@1234>  compile c++"), "compile c++");
+    assert_eq!(strip_mention(";compile c++"), ";compile c++");
+}
+
+#[tokio::test]
+async fn default_language() {
+    let manager = RwLock::new(SettingsManager::in_memory());
+    SettingsManager::update(&manager, Scope::Guild(1), |settings| {
+        settings.set(Setting::Language, None, "cpp")
+    })
+    .await
+    .unwrap();
+    SettingsManager::update(&manager, Scope::User(2), |settings| {
+        settings.set(Setting::Language, None, "rs")
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(
+        manager
+            .read()
+            .await
+            .effective(Some(1), 3)
+            .language
+            .as_deref(),
+        Some("c++")
+    );
+    assert_eq!(
+        manager
+            .read()
+            .await
+            .effective(Some(1), 2)
+            .language
+            .as_deref(),
+        Some("rust")
+    );
+
+    let mut settings = manager.read().await.get(Scope::User(2));
+    settings.reset(Some(Setting::Language), None);
+    assert!(settings.is_empty());
+}
+
+#[tokio::test]
+async fn requests_fall_back_to_the_default_language() {
+    let input = ";compile -O2\n```\nint main() {}\n```";
+    let author = User::default();
+
+    let result = get_components_with_language(input, &author, None, &None, true, Some("c++"))
+        .await
+        .unwrap();
+    assert_eq!(result.target, "c++");
+    assert_eq!(result.options, ["-O2"]);
+
+    // A language the request names itself wins
+    let input = ";compile -O2\n```rs\nfn main() {}\n```";
+    let result = get_components_with_language(input, &author, None, &None, true, Some("c++"))
+        .await
+        .unwrap();
+    assert_eq!(result.target, "rust");
+
+    let input = ";compile\n```\nint main() {}\n```";
+    let result = get_components_with_language(input, &author, None, &None, true, None).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,100 @@
+//! Tests for grouping ;asm output by source line with --map and L12-L20.
+
+use godbolt::{AsmLine, SourceLocation};
+
+use crate::backends::godbolt::{parse_line_range, take_source_map};
+use crate::commands::asm::split_line_range;
+use crate::utls::discordhelpers::embeds::{map_assembly, SourceMap};
+
+fn asm(text: &str, line: Option<i32>) -> AsmLine {
+    AsmLine {
+        text: Some(text.to_string()),
+        source: line.map(|line| SourceLocation {
+            file: None,
+            line: Some(line),
+            column: None,
+        }),
+    }
+}
+
+fn listing() -> Vec<AsmLine> {
+    vec![
+        asm("square(int):", None),
+        asm("        mov     eax, edi", Some(2)),
+        asm("        imul    eax, edi", Some(2)),
+        asm("        ret", Some(3)),
+    ]
+}
+
+const CODE: &str = "int square(int x) {\n    return x * x;\n}";
+
+#[test]
+fn groups_assembly_under_source_lines() {
+    let map = SourceMap {
+        code: CODE.to_string(),
+        lines: None,
+    };
+
+    assert_eq!(
+        map_assembly(&listing(), &map),
+        [
+            "square(int):",
+            "; 2 | return x * x;",
+            "        mov     eax, edi",
+            "        imul    eax, edi",
+            "; 3 | }",
+            "        ret",
+        ]
+    );
+}
+
+#[test]
+fn keeps_only_requested_lines() {
+    let map = SourceMap {
+        code: CODE.to_string(),
+        lines: Some((3, 3)),
+    };
+
+    assert_eq!(map_assembly(&listing(), &map), ["; 3 | }", "        ret"]);
+}
+
+#[test]
+fn parses_line_ranges() {
+    assert_eq!(parse_line_range("L12-L20"), Some((12, 20)));
+    assert_eq!(parse_line_range("12-20"), Some((12, 20)));
+    assert_eq!(parse_line_range("L7"), Some((7, 7)));
+    assert_eq!(parse_line_range("L20-L12"), None);
+    assert_eq!(parse_line_range("L0"), None);
+    assert_eq!(parse_line_range("Lfoo"), None);
+}
+
+#[test]
+fn takes_source_map_options() {
+    let mut options = vec![String::from("-O2"), String::from("--map")];
+    assert_eq!(take_source_map(&mut options).unwrap(), Some(None));
+    assert_eq!(options, ["-O2"]);
+
+    let mut options = vec![String::from("--lines=L2-L3")];
+    assert_eq!(take_source_map(&mut options).unwrap(), Some(Some((2, 3))));
+
+    let mut options = vec![String::from("-O2")];
+    assert_eq!(take_source_map(&mut options).unwrap(), None);
+    assert!(take_source_map(&mut vec![String::from("--lines=x")]).is_err());
+}
+
+#[test]
+fn moves_leading_range_to_options() {
+    assert_eq!(
+        split_line_range(";asm L12-L20 g132 -O2\n```cpp\nint x;\n```"),
+        ";asm g132 -O2 --lines=L12-L20\n```cpp\nint x;\n```"
+    );
+    assert_eq!(
+        split_line_range(";asm L5```cpp\nint x;\n```"),
+        ";asm --lines=L5```cpp\nint x;\n```"
+    );
+    assert_eq!(split_line_range(";asm lua\n```"), ";asm lua\n```");
+    assert_eq!(
+        split_line_range("<@504095380166803466> asm L12-L20 c++\n```cpp\nint x;\n```"),
+        "asm c++ --lines=L12-L20\n```cpp\nint x;\n```"
+    );
+}
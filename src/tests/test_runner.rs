@@ -0,0 +1,83 @@
+//! Tests for checking program output with ;test.
+
+use crate::commands::test::split_cases;
+use crate::utls::test_cases::{diff_output, Comparison, TestCase};
+
+#[test]
+fn splits_cases_from_program() {
+    let content = ";test python\n```py\nprint(input())\n```\n```input\n1\n```\n```expected\n1\n```\n```INPUT\n2\n```\n```expected\n2\n```";
+
+    let (cases, rest) = split_cases(content).unwrap();
+
+    assert_eq!(
+        cases,
+        [
+            TestCase {
+                input: String::from("1\n"),
+                expected: String::from("1\n"),
+            },
+            TestCase {
+                input: String::from("2\n"),
+                expected: String::from("2\n"),
+            },
+        ]
+    );
+    assert_eq!(rest.trim_end(), ";test python\n```py\nprint(input())\n```");
+}
+
+#[test]
+fn rejects_unpaired_blocks() {
+    let program = ";test python\n```py\nprint(1)\n```\n";
+
+    assert!(split_cases(program).is_err());
+    assert!(split_cases(&format!("{}```input\n1\n```", program)).is_err());
+    assert!(split_cases(&format!("{}```expected\n1\n```", program)).is_err());
+    assert!(split_cases(&format!("{}```input\n1\n```\n```input\n2\n```", program)).is_err());
+}
+
+#[test]
+fn takes_comparison_options() {
+    let mut options: Vec<String> = ["-O2", "--ignore-whitespace", "--tolerance=1e-6"]
+        .iter()
+        .map(|opt| opt.to_string())
+        .collect();
+
+    let comparison = Comparison::take(&mut options).unwrap();
+
+    assert!(comparison.ignore_whitespace);
+    assert_eq!(comparison.tolerance, Some(1e-6));
+    assert_eq!(options, ["-O2"]);
+    assert!(Comparison::take(&mut vec![String::from("--tolerance=abc")]).is_err());
+}
+
+#[test]
+fn compares_output() {
+    let exact = Comparison::default();
+    assert!(exact.matches("1 2\n3\n", "1 2  \n3\n\n"));
+    assert!(!exact.matches("1 2\n3\n", "1  2\n3\n"));
+
+    let words = Comparison {
+        ignore_whitespace: true,
+        tolerance: None,
+    };
+    assert!(words.matches("1 2\n3\n", "1  2 3"));
+    assert!(!words.matches("1 2 3", "1 2"));
+
+    let numbers = Comparison {
+        ignore_whitespace: false,
+        tolerance: Some(1e-3),
+    };
+    assert!(numbers.matches("3.14159 ok", "3.1414 ok"));
+    assert!(numbers.matches("1000000", "1000500"));
+    assert!(!numbers.matches("3.14159", "3.15"));
+    assert!(!numbers.matches("ok", "OK"));
+}
+
+#[test]
+fn diffs_wrong_answers() {
+    let diff = diff_output("1\n2\n", "1\n3");
+
+    assert!(diff.starts_with("--- expected\n+++ actual\n"));
+    assert!(diff.contains("-2\n"));
+    assert!(diff.contains("+3\n"));
+}
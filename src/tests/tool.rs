@@ -0,0 +1,70 @@
+//! Tests for running Compiler Explorer tools with ;tool.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use godbolt::{Compiler, RequestOptions, ToolInvocation};
+
+use crate::apis::godbolt::GodboltService;
+use crate::backends::godbolt::tool_output;
+use crate::commands::tool::split_tool;
+
+#[test]
+fn splits_tool() {
+    let (tool, content) =
+        split_tool(";tool Clang-TidyTrunk c++ -O2\n--checks=*\n```cpp\nint main() {}\n```")
+            .unwrap();
+
+    assert_eq!(tool, "clang-tidytrunk");
+    assert_eq!(
+        content,
+        ";tool c++ -O2\n--checks=*\n```cpp\nint main() {}\n```"
+    );
+    assert!(split_tool(";tool").is_err());
+}
+
+#[test]
+fn reads_tool_output() {
+    let response: godbolt::CompilationResult = serde_json::from_value(serde_json::json!({
+        "code": 0,
+        "tools": [
+            { "id": "pahole", "code": 0, "stdout": [{ "text": "struct a {" }], "stderr": [] },
+            {
+                "id": "clang-tidytrunk",
+                "code": 1,
+                "stdout": [{ "text": "warning: use nullptr" }],
+                "stderr": [{ "text": "1 warning generated." }]
+            }
+        ]
+    }))
+    .unwrap();
+
+    let output = tool_output(&response, "clang-tidytrunk").unwrap();
+    assert_eq!(output.code, 1);
+    assert_eq!(output.text(), "warning: use nullptr\n1 warning generated.");
+    assert!(tool_output(&response, "llvm-mcatrunk").is_none());
+}
+
+#[test]
+fn link_includes_tool_pane() {
+    let compiler = Compiler {
+        id: String::from("clang1701"),
+        lang: String::from("c++"),
+        ..Default::default()
+    };
+    let options = RequestOptions {
+        tools: vec![ToolInvocation {
+            id: String::from("clang-tidytrunk"),
+            args: String::from("--checks=*"),
+            stdin: None,
+        }],
+        ..Default::default()
+    };
+
+    let link =
+        GodboltService::get_base64(&compiler, "int main() {}", &[], false, &options).unwrap();
+    let state: serde_json::Value = serde_json::from_slice(&STANDARD.decode(link).unwrap()).unwrap();
+
+    let tools = &state["sessions"][0]["compilers"][0]["tools"];
+    assert_eq!(tools[0]["id"], "clang-tidytrunk");
+    assert_eq!(tools[0]["args"], "--checks=*");
+    assert!(state.get("trees").is_none());
+}
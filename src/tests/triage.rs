@@ -0,0 +1,86 @@
+//! Tests for recognizing crashes and reading sanitizer reports.
+
+use crate::utls::triage::{analyze, exit_signal, render, signal_name, signal_number, Finding};
+
+const SOURCE: &str =
+    "#include <vector>\nint main() {\n    std::vector<int> v(2);\n    return v[5];\n}";
+
+#[test]
+fn recognizes_crash_signals() {
+    assert_eq!(signal_name("SIGSEGV"), Some("SIGSEGV"));
+    assert_eq!(signal_name("Segmentation fault"), Some("SIGSEGV"));
+    assert_eq!(signal_name("Aborted"), Some("SIGABRT"));
+    assert_eq!(signal_name("SIGKILL"), None);
+    assert_eq!(signal_name(""), None);
+    assert_eq!(signal_number(8), Some("SIGFPE"));
+    assert_eq!(signal_number(9), None);
+}
+
+#[test]
+fn reads_exit_signals() {
+    assert_eq!(exit_signal(0, "SIGSEGV"), None);
+    assert_eq!(
+        exit_signal(-1, "Program terminated with signal: SIGSEGV"),
+        Some("SIGSEGV")
+    );
+    assert_eq!(exit_signal(134, ""), Some("SIGABRT"));
+    assert_eq!(exit_signal(1, "error"), None);
+}
+
+#[test]
+fn finds_first_user_frame_in_asan_report() {
+    let report = "=================================================================\n\
+                  ==1==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000024\n\
+                  READ of size 4 at 0x602000000024 thread T0\n    \
+                  #0 0x7f3a in __memcpy /usr/lib/libc.so.6\n    \
+                  #1 0x401276 in main /app/example.cpp:4:12\n    \
+                  #2 0x7f3b in __libc_start_main\n";
+
+    let finding = analyze(report).unwrap();
+    assert_eq!(
+        finding.message,
+        "AddressSanitizer: heap-buffer-overflow on address 0x602000000024"
+    );
+    assert_eq!(finding.line, Some(4));
+    assert_eq!(finding.column, Some(12));
+}
+
+#[test]
+fn finds_ubsan_location() {
+    let report = "<source>:3:14: runtime error: signed integer overflow: 2147483647 + 1 \
+                  cannot be represented in type 'int'\n";
+
+    let finding = analyze(report).unwrap();
+    assert!(finding.message.starts_with("signed integer overflow"));
+    assert_eq!(finding.line, Some(3));
+    assert_eq!(finding.column, Some(14));
+}
+
+#[test]
+fn nothing_reported() {
+    assert_eq!(analyze(""), None);
+    assert_eq!(analyze("Program returned: 0\n"), None);
+}
+
+#[test]
+fn renders_against_source() {
+    let finding = Finding {
+        message: String::from("AddressSanitizer: heap-buffer-overflow"),
+        line: Some(4),
+        column: Some(12),
+    };
+
+    let rendered = render(&finding, SOURCE);
+    assert!(rendered.contains("error: AddressSanitizer: heap-buffer-overflow"));
+    assert!(rendered.contains("4 |     return v[5];"));
+
+    let finding = Finding {
+        line: None,
+        column: None,
+        ..finding
+    };
+    assert_eq!(
+        render(&finding, SOURCE),
+        "error: AddressSanitizer: heap-buffer-overflow\n"
+    );
+}
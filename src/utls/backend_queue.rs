@@ -0,0 +1,162 @@
+//! Bounded concurrency for a single backend.
+//!
+//! At most `concurrency` requests run at once; up to `max_waiting` more wait
+//! for a slot in arrival order and are told their position as it changes.
+//! Anything past that is turned away instead of piling up until the HTTP
+//! client times out.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use tokio::sync::{mpsc, watch, Semaphore, SemaphorePermit};
+
+/// Requests that may run at once, and wait for a slot, on a backend missing
+/// from BACKEND_CONCURRENCY or BACKEND_QUEUE
+const DEFAULT_CONCURRENCY: usize = 8;
+const DEFAULT_QUEUE: usize = 32;
+
+/// Receives a waiting request's 1-based queue position whenever it changes,
+/// then 0 once the request has a slot
+pub type QueueUpdates = mpsc::UnboundedSender<usize>;
+
+/// Per-backend limits from BACKEND_CONCURRENCY and BACKEND_QUEUE, both lists
+/// like `godbolt=8,wandbox=4`
+#[derive(Default)]
+pub struct QueueLimits {
+    concurrency: HashMap<String, usize>,
+    queue: HashMap<String, usize>,
+}
+
+impl QueueLimits {
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .map(|v| parse_limits(&v))
+                .unwrap_or_default()
+        };
+        QueueLimits {
+            concurrency: var("BACKEND_CONCURRENCY"),
+            queue: var("BACKEND_QUEUE"),
+        }
+    }
+
+    /// A queue for the backend with the given id
+    pub fn queue_for(&self, id: &str) -> BackendQueue {
+        BackendQueue::new(
+            self.concurrency
+                .get(id)
+                .copied()
+                .unwrap_or(DEFAULT_CONCURRENCY),
+            self.queue.get(id).copied().unwrap_or(DEFAULT_QUEUE),
+        )
+    }
+}
+
+fn parse_limits(list: &str) -> HashMap<String, usize> {
+    list.split(',')
+        .filter_map(|entry| {
+            let (id, limit) = entry.split_once('=')?;
+            match limit.trim().parse() {
+                Ok(limit) => Some((id.trim().to_string(), limit)),
+                Err(_) => {
+                    warn!("Ignoring invalid backend limit '{}'", entry);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+pub struct BackendQueue {
+    permits: Semaphore,
+    max_waiting: usize,
+    /// Last ticket handed out, and the tickets of waiting requests oldest first
+    waiting: Mutex<(u64, VecDeque<u64>)>,
+    /// Bumped whenever a request leaves the queue
+    changed: watch::Sender<()>,
+}
+
+impl BackendQueue {
+    pub fn new(concurrency: usize, max_waiting: usize) -> Self {
+        BackendQueue {
+            permits: Semaphore::new(concurrency),
+            max_waiting,
+            waiting: Mutex::new((0, VecDeque::new())),
+            changed: watch::Sender::new(()),
+        }
+    }
+
+    pub fn max_waiting(&self) -> usize {
+        self.max_waiting
+    }
+
+    /// Wait for a slot, reporting our position to `updates` while queued.
+    /// Returns None without waiting if the queue is already full.
+    pub async fn acquire(&self, updates: Option<&QueueUpdates>) -> Option<SemaphorePermit<'_>> {
+        if let Ok(permit) = self.permits.try_acquire() {
+            return Some(permit);
+        }
+
+        let ticket = {
+            let mut waiting = self.waiting.lock().unwrap();
+            let (next, tickets) = &mut *waiting;
+            if tickets.len() >= self.max_waiting {
+                return None;
+            }
+            *next += 1;
+            tickets.push_back(*next);
+            *next
+        };
+        let _ticket = Ticket {
+            queue: self,
+            ticket,
+        };
+
+        let mut changed = self.changed.subscribe();
+        let acquire = self.permits.acquire();
+        tokio::pin!(acquire);
+
+        let mut reported = 0;
+        loop {
+            let position = self.position(ticket);
+            if let Some(updates) = updates.filter(|_| position != reported) {
+                let _ = updates.send(position);
+                reported = position;
+            }
+
+            tokio::select! {
+                permit = &mut acquire => {
+                    if let Some(updates) = updates {
+                        let _ = updates.send(0);
+                    }
+                    return permit.ok();
+                }
+                _ = changed.changed() => {}
+            }
+        }
+    }
+
+    fn position(&self, ticket: u64) -> usize {
+        let waiting = self.waiting.lock().unwrap();
+        waiting.1.iter().position(|t| *t == ticket).unwrap_or(0) + 1
+    }
+}
+
+/// A waiting request's place in line, given up when it gets a slot or is
+/// dropped
+struct Ticket<'a> {
+    queue: &'a BackendQueue,
+    ticket: u64,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        self.queue
+            .waiting
+            .lock()
+            .unwrap()
+            .1
+            .retain(|t| *t != self.ticket);
+        self.queue.changed.send_replace(());
+    }
+}
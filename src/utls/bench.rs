@@ -0,0 +1,53 @@
+//! Timing statistics for ;bench, computed over the execution times of
+//! repeated runs.
+
+/// Standard deviation, relative to the mean, above which timings are too
+/// noisy to compare
+const NOISY_DEVIATION: f64 = 0.1;
+
+/// Timing statistics over a set of runs, in milliseconds
+#[derive(Debug, PartialEq)]
+pub struct BenchStats {
+    pub runs: usize,
+    pub min: i64,
+    pub median: f64,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl BenchStats {
+    pub fn from_times(times: &[i64]) -> Option<Self> {
+        if times.is_empty() {
+            return None;
+        }
+
+        let mut sorted = times.to_vec();
+        sorted.sort_unstable();
+
+        let runs = sorted.len();
+        let median = if runs.is_multiple_of(2) {
+            (sorted[runs / 2 - 1] + sorted[runs / 2]) as f64 / 2.0
+        } else {
+            sorted[runs / 2] as f64
+        };
+        let mean = sorted.iter().sum::<i64>() as f64 / runs as f64;
+        let variance = sorted
+            .iter()
+            .map(|time| (*time as f64 - mean).powi(2))
+            .sum::<f64>()
+            / runs as f64;
+
+        Some(BenchStats {
+            runs,
+            min: sorted[0],
+            median,
+            mean,
+            stddev: variance.sqrt(),
+        })
+    }
+
+    /// Whether the runs varied too much for the numbers to mean much
+    pub fn is_noisy(&self) -> bool {
+        self.mean > 0.0 && self.stddev / self.mean > NOISY_DEVIATION
+    }
+}
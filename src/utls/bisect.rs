@@ -0,0 +1,127 @@
+//! Finds the compiler version where a snippet's behaviour changed, trying as
+//! few versions as possible.
+
+use std::future::Future;
+
+use serenity::framework::standard::CommandError;
+
+/// What a compiler did with the snippet, compared between versions
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+    /// Compiler diagnostics as `line:column: message`, without the
+    /// surrounding source excerpts that differ between versions
+    pub diagnostics: Vec<String>,
+}
+
+impl Outcome {
+    pub fn from_result(result: &godbolt::CompilationResult) -> Self {
+        let lines = |lines: &[godbolt::OutputLine]| {
+            lines
+                .iter()
+                .map(|line| line.text.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let build_stderr = match &result.build_result {
+            Some(build) => &build.stderr,
+            None => &result.stderr,
+        };
+
+        Outcome {
+            exit_code: result.code,
+            stdout: lines(&result.stdout),
+            stderr: if result.did_execute.unwrap_or(false) {
+                lines(&result.stderr)
+            } else {
+                String::new()
+            },
+            diagnostics: build_stderr
+                .iter()
+                .filter_map(|line| line.tag.as_ref())
+                .map(|tag| {
+                    format!(
+                        "{}:{}: {}",
+                        tag.line.unwrap_or(0),
+                        tag.column.unwrap_or(0),
+                        tag.text
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Result of bisecting a list of versions ordered oldest first
+pub struct Bisection<T> {
+    /// Outcome of every version that was actually tried
+    pub outcomes: Vec<Option<T>>,
+    /// Newest version known to behave like the oldest one
+    pub last_same: usize,
+    /// Oldest version known to behave differently, if any does
+    pub first_changed: Option<usize>,
+}
+
+impl<T> Bisection<T> {
+    pub fn probes(&self) -> usize {
+        self.outcomes.iter().filter(|o| o.is_some()).count()
+    }
+}
+
+/// Find the first of `count` versions whose outcome differs from the oldest
+/// one. Assumes behaviour changes at most once in the range, which lets it
+/// try O(log n) versions instead of all of them.
+pub async fn bisect_versions<T, F, Fut>(
+    count: usize,
+    mut probe: F,
+) -> Result<Bisection<T>, CommandError>
+where
+    T: PartialEq,
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<T, CommandError>>,
+{
+    let mut outcomes: Vec<Option<T>> = (0..count).map(|_| None).collect();
+    if count == 0 {
+        return Err(CommandError::from("There are no versions to bisect"));
+    }
+
+    outcomes[0] = Some(probe(0).await?);
+    let last = count - 1;
+    if last == 0 {
+        return Ok(Bisection {
+            outcomes,
+            last_same: 0,
+            first_changed: None,
+        });
+    }
+
+    outcomes[last] = Some(probe(last).await?);
+    if outcomes[last] == outcomes[0] {
+        return Ok(Bisection {
+            outcomes,
+            last_same: last,
+            first_changed: None,
+        });
+    }
+
+    let (mut low, mut high) = (0, last);
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        let outcome = probe(mid).await?;
+        let same = Some(&outcome) == outcomes[0].as_ref();
+        outcomes[mid] = Some(outcome);
+        if same {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Ok(Bisection {
+        outcomes,
+        last_same: low,
+        first_changed: Some(high),
+    })
+}
@@ -0,0 +1,94 @@
+//! Renders the control-flow graphs Compiler Explorer produces for `;asm --cfg`
+//! as Graphviz DOT.
+//!
+//! Each function becomes a cluster of basic blocks. Edges keep Compiler
+//! Explorer's colouring: green for a taken branch, red for the fall-through
+//! and blue for an unconditional jump.
+
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+/// Render every function's graph into a single DOT digraph, or None if there
+/// are no graphs
+pub fn to_dot(cfg: &Value) -> Option<String> {
+    // Newer versions nest the assembly graphs under `asm`, next to any IR ones
+    let functions = match cfg.get("asm") {
+        Some(asm) if asm.is_object() => asm.as_object()?,
+        _ => cfg.as_object()?,
+    };
+    if functions.is_empty() {
+        return None;
+    }
+
+    let mut dot = String::from("digraph cfg {\n");
+    dot.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+    for (index, (name, graph)) in functions.iter().enumerate() {
+        // Block ids only have to be unique within a function
+        let node_id = |id: &Value| format!("f{}_{}", index, id_text(id));
+
+        writeln!(dot, "    subgraph cluster_{} {{", index).unwrap();
+        writeln!(dot, "        label=\"{}\";", escape(name)).unwrap();
+        for node in array(graph, "nodes") {
+            let label = node
+                .get("label")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            writeln!(
+                dot,
+                "        \"{}\" [label=\"{}\"];",
+                node_id(node.get("id").unwrap_or(&Value::Null)),
+                label_text(label)
+            )
+            .unwrap();
+        }
+        for edge in array(graph, "edges") {
+            let (Some(from), Some(to)) = (edge.get("from"), edge.get("to")) else {
+                continue;
+            };
+            write!(dot, "        \"{}\" -> \"{}\"", node_id(from), node_id(to)).unwrap();
+            match edge.get("color").and_then(Value::as_str) {
+                Some(color) if !color.is_empty() => {
+                    writeln!(dot, " [color=\"{}\"];", escape(color)).unwrap()
+                }
+                _ => dot.push_str(";\n"),
+            }
+        }
+        dot.push_str("    }\n");
+    }
+    dot.push_str("}\n");
+    Some(dot)
+}
+
+fn array<'a>(graph: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+    graph
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+/// Ids come back as strings or numbers depending on the compiler
+fn id_text(id: &Value) -> String {
+    match id {
+        Value::String(id) => escape(id),
+        other => other.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Left-justify every line of a block, the way assembly reads
+fn label_text(label: &str) -> String {
+    let mut text: String = label
+        .trim_end()
+        .lines()
+        .map(|line| format!("{}\\l", escape(line)))
+        .collect();
+    if text.is_empty() {
+        text.push_str("\\l");
+    }
+    text
+}
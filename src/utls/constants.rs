@@ -42,6 +42,24 @@ lazy_static! {
         Regex::new("\"[^\"]*?\"|(?P<public>public)[\\s]+?class[\\s]*?").unwrap();
     pub static ref C_LIKE_INCLUDE_REGEX: Regex =
         Regex::new("\"[^\"]+\"|(?P<statement>#include\\s<(?P<url>.+?)>)").unwrap();
+    // file:line[:column]: severity: message, from GCC, Clang and javac
+    pub static ref GCC_DIAGNOSTIC_REGEX: Regex =
+        Regex::new(r"^(?P<file>[^:\s][^:]*?):(?P<line>\d+):(?:(?P<column>\d+):)?\s*(?P<severity>fatal error|error|warning|note):\s*(?P<message>.*)$").unwrap();
+    // file(line[,column]) : severity [code]: message, from MSVC and spcomp
+    pub static ref MSVC_DIAGNOSTIC_REGEX: Regex =
+        Regex::new(r"^(?P<file>[^(\s][^(]*?)\((?P<line>\d+)(?:,(?P<column>\d+))?\)\s*:\s*(?P<severity>fatal error|error|warning|note)(?:\s+[A-Z]*\d+)?\s*:\s*(?P<message>.*)$").unwrap();
+    // rustc states the problem first and where it is on a later ` --> ` line
+    pub static ref RUSTC_DIAGNOSTIC_REGEX: Regex =
+        Regex::new(r"^(?P<severity>error|warning)(?:\[E\d+\])?: (?P<message>.*)$").unwrap();
+    pub static ref RUSTC_LOCATION_REGEX: Regex =
+        Regex::new(r"^\s*--> (?P<file>[^:]+):(?P<line>\d+):(?P<column>\d+)").unwrap();
+    pub static ref SANITIZER_LOCATION_REGEX: Regex =
+        Regex::new(r"(?:<source>|/app/example\.[A-Za-z+]+):(?P<line>\d+)(?::(?P<column>\d+))?").unwrap();
+    // a mention of the bot used as the command prefix, e.g. "<@1234> compile"
+    pub static ref MENTION_PREFIX_REGEX: Regex =
+        Regex::new(r"^\s*<@!?(?P<id>\d+)>\s*").unwrap();
+    // a file name after the fence language (```cpp main.cpp) is only taken as
+    // such when it has a known source or header extension
     pub static ref CODE_BLOCK_REGEX: Regex =
-        Regex::new(r"```(?:(?P<language>[^\s`]*)\r?\n)?(?P<code>[\s\S]*?)```").unwrap();
+        Regex::new(r"```(?:(?P<language>[^\s`]*)(?:[ \t]+(?P<filename>[\w./+-]+\.(?i:c|cc|cpp|cxx|c\+\+|h|hh|hpp|hxx|h\+\+|inc|ipp|tpp|ixx|cppm|rs|go|zig|d|java|kt|scala|cs|fs|py|rb|js|ts|hs|jl|swift|pas|f|f90|f95|s|asm|sp|cmake)|CMakeLists\.txt))?[ \t]*\r?\n)?(?P<code>[\s\S]*?)```").unwrap();
 }
@@ -0,0 +1,252 @@
+//! Pulls structured diagnostics out of compiler output so embeds can show
+//! the first few problems against the user's own code, instead of however
+//! much of the raw log fits.
+//!
+//! Understands GCC, Clang, javac, MSVC, spcomp and rustc formats. Output in
+//! any other format is left for the caller to show as it came.
+
+use std::fmt::Write as _;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utls::constants::{
+    GCC_DIAGNOSTIC_REGEX, MAX_ERROR_LEN, MSVC_DIAGNOSTIC_REGEX, RUSTC_DIAGNOSTIC_REGEX,
+    RUSTC_LOCATION_REGEX,
+};
+use crate::utls::parser::{ParserResult, SourceFile};
+
+/// Most diagnostics rendered into an embed
+const MAX_SHOWN: usize = 3;
+
+/// Longest message kept in a rendered diagnostic
+const MAX_MESSAGE_LEN: usize = 200;
+
+/// How compiler output is shown in embeds
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    /// Annotated snippets of the first diagnostics, when they can be parsed
+    #[default]
+    Summary,
+    /// The output as the compiler wrote it
+    Raw,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn parse(severity: &str) -> Self {
+        match severity {
+            "error" | "fatal error" => Severity::Error,
+            "warning" => Severity::Warning,
+            _ => Severity::Note,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Every diagnostic in the output, in the order the compiler gave them
+pub fn parse(output: &str) -> Vec<Diagnostic> {
+    let output = strip_ansi_escapes::strip(output);
+    let output = String::from_utf8_lossy(&output);
+
+    let mut diagnostics = Vec::new();
+    let mut rustc_pending: Option<(Severity, String)> = None;
+    for line in output.lines() {
+        let line = line.trim_end();
+        let captures = GCC_DIAGNOSTIC_REGEX
+            .captures(line)
+            .or_else(|| MSVC_DIAGNOSTIC_REGEX.captures(line));
+        if let Some(cap) = captures {
+            diagnostics.push(Diagnostic {
+                file: cap["file"].trim().to_string(),
+                line: cap["line"].parse().unwrap_or(0),
+                column: cap.name("column").and_then(|c| c.as_str().parse().ok()),
+                severity: Severity::parse(&cap["severity"]),
+                message: cap["message"].trim().to_string(),
+            });
+            rustc_pending = None;
+        } else if let Some(cap) = RUSTC_DIAGNOSTIC_REGEX.captures(line) {
+            // Summaries like "aborting due to previous error" never get a location
+            rustc_pending = Some((
+                Severity::parse(&cap["severity"]),
+                cap["message"].to_string(),
+            ));
+        } else if let Some(cap) = RUSTC_LOCATION_REGEX.captures(line) {
+            if let Some((severity, message)) = rustc_pending.take() {
+                diagnostics.push(Diagnostic {
+                    file: cap["file"].to_string(),
+                    line: cap["line"].parse().unwrap_or(0),
+                    column: cap["column"].parse().ok(),
+                    severity,
+                    message,
+                });
+            }
+        }
+    }
+    diagnostics
+}
+
+/// The first problems in some compiler output, ready for an embed
+pub struct Summary {
+    /// Annotated snippets of the first errors, or warnings if there are none
+    pub text: String,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl Summary {
+    /// Error and warning counts, e.g. `2 errors, 1 warning`
+    pub fn counts(&self) -> String {
+        let plural = |count: usize, what: &str| {
+            format!("{} {}{}", count, what, if count == 1 { "" } else { "s" })
+        };
+        format!(
+            "{}, {}",
+            plural(self.errors, "error"),
+            plural(self.warnings, "warning")
+        )
+    }
+}
+
+/// Summarize compiler output against the code it was given, or None if no
+/// errors or warnings could be found in it
+pub fn summarize(output: &str, sources: &[SourceFile]) -> Option<Summary> {
+    let diagnostics = parse(output);
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|diag| diag.severity == severity)
+            .count()
+    };
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    if errors == 0 && warnings == 0 {
+        return None;
+    }
+
+    let shown = if errors > 0 {
+        Severity::Error
+    } else {
+        Severity::Warning
+    };
+    let mut text = String::new();
+    for diag in diagnostics
+        .iter()
+        .filter(|diag| diag.severity == shown)
+        .take(MAX_SHOWN)
+    {
+        let snippet = render(diag, sources);
+        if !text.is_empty() && text.len() + snippet.len() + 1 > MAX_ERROR_LEN {
+            break;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(&snippet);
+    }
+
+    Some(Summary {
+        text,
+        errors,
+        warnings,
+    })
+}
+
+/// The full log, for attaching, when an embed would only show part of it
+pub fn full_log(output: &str) -> Option<String> {
+    let output = output.trim();
+    (output.len() > MAX_ERROR_LEN).then(|| output.to_string())
+}
+
+/// The request's code under every name a compiler may report it by: the
+/// `names` a service saves the main file as, and each file of a project
+/// under its own name
+pub fn sources(request: &ParserResult, code: &str, names: &[&str]) -> Vec<SourceFile> {
+    let main = names.iter().map(|name| SourceFile {
+        name: name.to_string(),
+        code: code.to_string(),
+    });
+    main.chain(request.files.iter().cloned()).collect()
+}
+
+/// One diagnostic with the line of code it points at and a caret under the
+/// column. Diagnostics in files that aren't among `sources`, like system
+/// headers, get just their location.
+pub fn render(diag: &Diagnostic, sources: &[SourceFile]) -> String {
+    let mut message: String = diag.message.chars().take(MAX_MESSAGE_LEN).collect();
+    if message.len() < diag.message.len() {
+        message.push('…');
+    }
+
+    let location = match diag.column {
+        Some(column) => format!("{}:{}", diag.line, column),
+        None => diag.line.to_string(),
+    };
+    let code = find_source(sources, &diag.file)
+        .and_then(|source| source.lines().nth(diag.line.wrapping_sub(1)));
+    let Some(code) = code else {
+        return format!(
+            "{}: {}\n --> {}:{}\n",
+            diag.severity.name(),
+            message,
+            diag.file,
+            location
+        );
+    };
+
+    // Tabs become single spaces so the caret lines up with the column
+    let code = code.replace('\t', " ");
+    let gutter = " ".repeat(diag.line.to_string().len());
+    let mut text = format!(
+        "{}: {}\n{} --> {}\n{} |\n{} | {}\n",
+        diag.severity.name(),
+        message,
+        gutter,
+        location,
+        gutter,
+        diag.line,
+        code.trim_end()
+    );
+    if let Some(column) = diag.column.filter(|column| *column > 0) {
+        writeln!(text, "{} | {}^", gutter, " ".repeat(column - 1)).unwrap();
+    }
+    text
+}
+
+/// The code of the file a diagnostic points into. Services build in their
+/// own directories (`/app/main.cpp`), so only the file names are compared,
+/// ignoring case. A name without an extension, like WandBox's `prog`, stands
+/// for that file with whichever extension the language gave it.
+fn find_source<'a>(sources: &'a [SourceFile], file: &str) -> Option<&'a str> {
+    let name = file.rsplit('/').next().unwrap_or(file);
+    let stem = name.split_once('.').map(|(stem, _)| stem);
+    sources
+        .iter()
+        .find(|source| {
+            name.eq_ignore_ascii_case(&source.name)
+                || (!source.name.contains('.')
+                    && stem.is_some_and(|stem| stem.eq_ignore_ascii_case(&source.name)))
+        })
+        .map(|source| source.code.as_str())
+}
@@ -4,6 +4,9 @@ use std::{env, str};
 use crate::apis::insights::InsightsResponse;
 use crate::cache::LinkAPICache;
 use crate::managers::compilation::CompilationDetails;
+use crate::managers::health::{Circuit, ServiceStatus};
+use crate::managers::settings::Settings;
+use godbolt::Compiler;
 use serenity::all::{CreateActionRow, CreateButton, CreateEmbedFooter, EditMessage};
 use serenity::http::Http;
 use serenity::{
@@ -12,36 +15,129 @@ use serenity::{
     model::prelude::*,
 };
 
+use crate::utls::bench::BenchStats;
+use crate::utls::bisect::{Bisection, Outcome};
 use crate::utls::constants::*;
+use crate::utls::diagnostics::{self, OutputMode};
 use crate::utls::discordhelpers;
+use crate::utls::parser::SourceFile;
+use crate::utls::result_cache::CachedResult;
+use crate::utls::test_cases::CaseOutcome;
+
+/// Most output fields a dump embed shows before pointing at the attachment
+const MAX_DUMP_FIELDS: usize = 4;
 
 #[derive(Default)]
 pub struct EmbedOptions {
     pub is_assembly: bool,
     pub preprocessor: bool,
-    pub compilation_info: CompilationDetails,
+    /// Output to show in place of the assembly, e.g. LLVM IR or a Clang AST
+    pub extra_output: Option<ExtraOutput>,
+    /// Group the assembly under the source lines it came from
+    pub source_map: Option<SourceMap>,
+    /// The code as the compiler saw it, under the names it reports it by,
+    /// to point diagnostics at (see `diagnostics::sources`)
+    pub sources: Vec<SourceFile>,
+    pub output: OutputMode,
+}
+
+/// The source assembly is mapped back to, and the lines to keep
+pub struct SourceMap {
+    pub code: String,
+    /// First and last source line to show assembly for, or None for all
+    pub lines: Option<(i32, i32)>,
+}
+
+/// Non-assembly compiler output and how to title and highlight it
+pub struct ExtraOutput {
+    pub title: &'static str,
+    pub fence: &'static str,
+    pub text: String,
 }
 
 impl EmbedOptions {
-    pub fn new(
-        is_assembly: bool,
-        preprocessor: bool,
-        compilation_info: CompilationDetails,
-    ) -> Self {
+    pub fn new(is_assembly: bool, preprocessor: bool) -> Self {
         EmbedOptions {
             is_assembly,
             preprocessor,
-            compilation_info,
+            extra_output: None,
+            source_map: None,
+            sources: Vec::new(),
+            output: OutputMode::Summary,
+        }
+    }
+}
+
+/// Add the compiler's output, as annotated snippets of its first errors when
+/// they can be found or otherwise as it came. Returns the error and warning
+/// counts for the footer, if any.
+fn add_compiler_output(
+    embed: CreateEmbed,
+    output: &str,
+    options: &EmbedOptions,
+) -> (CreateEmbed, Option<String>) {
+    let summary = match options.output {
+        OutputMode::Summary => diagnostics::summarize(output, &options.sources),
+        OutputMode::Raw => None,
+    };
+    match summary {
+        Some(summary) => {
+            let text = summary.text.replace('`', "\u{200B}`");
+            let embed = embed.field("Compiler Output", format!("```\n{}```", text), false);
+            (embed, Some(summary.counts()))
+        }
+        None => {
+            let str = discordhelpers::conform_external_str(output, MAX_ERROR_LEN, true);
+            let embed = embed.field("Compiler Output", format!("```\n{}\n```", str), false);
+            (embed, None)
         }
     }
 }
 
 pub trait ToEmbed {
-    fn to_embed(self, author: &User, options: &EmbedOptions) -> CreateEmbed;
+    /// The result's embed, without a footer, and the diagnostic counts found
+    /// in its compiler output
+    fn to_embed(self, options: &EmbedOptions) -> (CreateEmbed, Option<String>);
+}
+
+/// Render a backend's response with a footer made out to `author`. The
+/// diagnostic counts are kept in `details` so the footer can be rebuilt for
+/// cached and failed-over results.
+pub fn build_result_embed(
+    response: impl ToEmbed,
+    author: &User,
+    options: &EmbedOptions,
+    details: &mut CompilationDetails,
+) -> CreateEmbed {
+    let (embed, counts) = response.to_embed(options);
+    details.counts = counts;
+    embed.footer(build_result_footer(author, details))
+}
+
+/// A result's footer, e.g. "user | 12ms | c++ | g132 | 1 warning | godbolt.org"
+pub fn build_result_footer(author: &User, details: &CompilationDetails) -> CreateEmbedFooter {
+    CreateEmbedFooter::new(result_footer_text(&author.name, details))
+}
+
+fn result_footer_text(requester: &str, details: &CompilationDetails) -> String {
+    let mut text = requester.to_string();
+    if let Some(time) = details.exec_time {
+        text = format!("{} | {}ms", text, time);
+    }
+    let parts = [
+        Some(details.language.as_str()),
+        Some(details.compiler.as_str()),
+        details.counts.as_deref(),
+        details.service,
+    ];
+    for part in parts.into_iter().flatten().filter(|part| !part.is_empty()) {
+        text = format!("{} | {}", text, part);
+    }
+    text
 }
 
 impl ToEmbed for wandbox::CompilationResult {
-    fn to_embed(self, author: &User, options: &EmbedOptions) -> CreateEmbed {
+    fn to_embed(self, options: &EmbedOptions) -> (CreateEmbed, Option<String>) {
         let mut embed = CreateEmbed::new();
 
         if let Some(status) = self.status {
@@ -58,10 +154,9 @@ impl ToEmbed for wandbox::CompilationResult {
             // actually failed (ourselves vs wandbox)
             embed = embed.color(COLOR_OKAY);
         }
+        let mut counts = None;
         if !self.compiler_message.is_empty() {
-            let str =
-                discordhelpers::conform_external_str(&self.compiler_message, MAX_ERROR_LEN, true);
-            embed = embed.field("Compiler Output", format!("```{}\n```", str), false);
+            (embed, counts) = add_compiler_output(embed, &self.compiler_message, options);
         }
         if !self.program_message.is_empty() {
             let str =
@@ -72,27 +167,17 @@ impl ToEmbed for wandbox::CompilationResult {
             embed = embed.field("URL", url, false);
         }
 
-        let mut text = author.name.clone();
-        if !options.compilation_info.language.is_empty() {
-            text = format!("{} | {}", text, options.compilation_info.language);
-        }
-        if !options.compilation_info.compiler.is_empty() {
-            text = format!("{} | {}", text, options.compilation_info.compiler);
-        }
-
-        text = format!("{} | wandbox.org", text);
-
-        let footer = CreateEmbedFooter::new(text);
-        embed.footer(footer)
+        (embed, counts)
     }
 }
 
 impl ToEmbed for crate::apis::sourcepawn::SourcePawnResponse {
-    fn to_embed(self, author: &User, options: &EmbedOptions) -> CreateEmbed {
+    fn to_embed(self, options: &EmbedOptions) -> (CreateEmbed, Option<String>) {
         let mut embed = CreateEmbed::new();
         let overall = self.compile.success && self.run.as_ref().map(|r| r.success).unwrap_or(true);
         embed = embed.color(if overall { COLOR_OKAY } else { COLOR_FAIL });
 
+        let mut counts = None;
         let compiler_msg = clean_spcomp_output(
             &format!("{}\n{}", self.compile.stdout, self.compile.stderr),
             // The size stats are only interesting for compile-only requests
@@ -101,7 +186,7 @@ impl ToEmbed for crate::apis::sourcepawn::SourcePawnResponse {
 
         if options.is_assembly {
             if !self.compile.success {
-                return embed.field(
+                let embed = embed.field(
                     "Compilation Errors",
                     format!(
                         "```\n{}```",
@@ -109,6 +194,7 @@ impl ToEmbed for crate::apis::sourcepawn::SourcePawnResponse {
                     ),
                     false,
                 );
+                return (embed, None);
             }
 
             let asm_text = self.asm.map(|asm| asm.stdout).unwrap_or_default();
@@ -118,14 +204,17 @@ impl ToEmbed for crate::apis::sourcepawn::SourcePawnResponse {
             embed = new_embed;
 
             if !output {
+                let what = match &options.extra_output {
+                    Some(extra) => extra.title,
+                    None => "assembly",
+                };
                 embed = embed
                     .title("Compilation successful")
-                    .description("No assembly generated.");
+                    .description(format!("No {} generated.", what));
             }
         } else {
             if !compiler_msg.is_empty() {
-                let str = discordhelpers::conform_external_str(&compiler_msg, MAX_ERROR_LEN, true);
-                embed = embed.field("Compiler Output", format!("```\n{}\n```", str), false);
+                (embed, counts) = add_compiler_output(embed, &compiler_msg, options);
             }
 
             match &self.run {
@@ -173,15 +262,115 @@ impl ToEmbed for crate::apis::sourcepawn::SourcePawnResponse {
             }
         }
 
-        let mut text = author.name.clone();
-        if !options.compilation_info.language.is_empty() {
-            text = format!("{} | {}", text, options.compilation_info.language);
+        (embed, counts)
+    }
+}
+
+impl ToEmbed for crate::apis::piston::PistonResponse {
+    fn to_embed(self, options: &EmbedOptions) -> (CreateEmbed, Option<String>) {
+        let mut embed = CreateEmbed::new();
+        embed = embed.color(if self.success() {
+            COLOR_OKAY
+        } else {
+            COLOR_FAIL
+        });
+
+        let mut counts = None;
+        if let Some(compile) = &self.compile {
+            if !compile.output.trim().is_empty() {
+                (embed, counts) = add_compiler_output(embed, compile.output.trim(), options);
+            }
+            if !compile.success() {
+                // Piston skips the run stage when compilation fails
+                return (embed, counts);
+            }
+        }
+
+        let mut program = self.run.output.trim().to_string();
+        if let Some(signal) = &self.run.signal {
+            if !program.is_empty() {
+                program.push('\n');
+            }
+            program.push_str(&format!("Killed by {}.", signal));
+        } else if let Some(code) = self.run.code.filter(|&code| code != 0) {
+            if !program.is_empty() {
+                program.push('\n');
+            }
+            program.push_str(&format!("Exited with code {}.", code));
+        }
+
+        if !program.is_empty() {
+            let str = discordhelpers::conform_external_str(&program, MAX_OUTPUT_LEN, true);
+            embed = embed.field("Program Output", format!("```\n{}\n```", str), false);
+        } else {
+            embed = embed.title("Execution successful");
+        }
+
+        (embed, counts)
+    }
+}
+
+impl ToEmbed for crate::backends::local::LocalResponse {
+    fn to_embed(self, options: &EmbedOptions) -> (CreateEmbed, Option<String>) {
+        let mut embed = CreateEmbed::new();
+        embed = embed.color(if self.success() {
+            COLOR_OKAY
+        } else {
+            COLOR_FAIL
+        });
+
+        let mut counts = None;
+        if let Some(compile) = &self.compile {
+            let mut compiler_msg = format!("{}\n{}", compile.stdout, compile.stderr)
+                .trim()
+                .to_string();
+            if compile.timed_out {
+                compiler_msg.push_str("\nCompilation timed out.");
+            }
+            if !compiler_msg.trim().is_empty() {
+                (embed, counts) = add_compiler_output(embed, compiler_msg.trim(), options);
+            }
         }
-        if !options.compilation_info.compiler.is_empty() {
-            text = format!("{} | {}", text, options.compilation_info.compiler);
+
+        match &self.run {
+            Some(run) => {
+                let mut program = run.stdout.trim().to_string();
+                let mut append = |line: &str| {
+                    if !program.is_empty() {
+                        program.push('\n');
+                    }
+                    program.push_str(line);
+                };
+
+                if !run.stderr.trim().is_empty() {
+                    append(run.stderr.trim());
+                }
+                if run.truncated {
+                    append("… (output truncated)");
+                }
+                if run.timed_out {
+                    append("Execution timed out.");
+                } else if let Some(signal) = run.signal {
+                    append(&format!("Killed by signal {}.", signal));
+                } else if let Some(code) = run.exit_code.filter(|&code| code != 0) {
+                    append(&format!("Exited with code {}.", code));
+                }
+
+                if !program.is_empty() {
+                    let str = discordhelpers::conform_external_str(&program, MAX_OUTPUT_LEN, true);
+                    embed = embed.field("Program Output", format!("```\n{}\n```", str), false);
+                } else {
+                    embed = embed.title("Execution successful");
+                }
+            }
+            None => {
+                if self.success() {
+                    embed = embed.title("Compilation successful");
+                }
+            }
         }
 
-        embed.footer(CreateEmbedFooter::new(text))
+        (embed, counts)
     }
 }
 
@@ -206,8 +395,9 @@ fn clean_spcomp_output(output: &str, keep_stats: bool) -> String {
 }
 
 impl ToEmbed for godbolt::CompilationResult {
-    fn to_embed(self, author: &User, options: &EmbedOptions) -> CreateEmbed {
+    fn to_embed(self, options: &EmbedOptions) -> (CreateEmbed, Option<String>) {
         let mut embed = CreateEmbed::new();
+        let mut counts = None;
         if self.code == 0 {
             embed = embed.color(COLOR_OKAY);
         } else {
@@ -223,11 +413,12 @@ impl ToEmbed for godbolt::CompilationResult {
 
                 let compliant_str =
                     discordhelpers::conform_external_str(&errs, MAX_ERROR_LEN, true);
-                return embed.field(
+                let embed = embed.field(
                     "Compilation Errors",
                     format!("```\n{}```", compliant_str),
                     false,
                 );
+                return (embed, None);
             }
         };
 
@@ -244,18 +435,29 @@ impl ToEmbed for godbolt::CompilationResult {
                 None
             };
 
-            let (pieces, remainder, base_title, fence) = match pp_output {
-                Some(pp) => {
+            let (pieces, remainder, base_title, fence) = match (&options.extra_output, pp_output) {
+                (Some(extra), _) => {
+                    let (mut pieces, mut remainder) = chunk_output(extra.text.trim().lines());
+                    if pieces.len() >= MAX_DUMP_FIELDS {
+                        pieces.truncate(MAX_DUMP_FIELDS);
+                        remainder.clear();
+                        embed = embed.description("Output truncated, the full output is attached.");
+                    }
+                    (pieces, remainder, extra.title, extra.fence)
+                }
+                (None, Some(pp)) => {
                     let (pieces, remainder) = chunk_output(pp.lines());
                     (pieces, remainder, "Preprocessor Output", "cpp")
                 }
-                None => {
-                    let (pieces, remainder) = chunk_output(
-                        self.asm
-                            .iter()
-                            .flatten()
-                            .filter_map(|asm| asm.text.as_deref()),
-                    );
+                (None, None) => {
+                    let asm = self.asm.as_deref().unwrap_or_default();
+                    let (pieces, remainder) = match &options.source_map {
+                        Some(map) => {
+                            let lines = map_assembly(asm, map);
+                            chunk_output(lines.iter().map(String::as_str))
+                        }
+                        None => chunk_output(asm.iter().filter_map(|asm| asm.text.as_deref())),
+                    };
                     (pieces, remainder, "Assembly Output", "x86asm")
                 }
             };
@@ -265,9 +467,13 @@ impl ToEmbed for godbolt::CompilationResult {
             embed = new_embed;
 
             if !output {
+                let what = match &options.extra_output {
+                    Some(extra) => extra.title,
+                    None => "assembly",
+                };
                 embed = embed
                     .title("Compilation successful")
-                    .description("No assembly generated.");
+                    .description(format!("No {} generated.", what));
             }
         } else {
             let mut output = String::default();
@@ -275,15 +481,23 @@ impl ToEmbed for godbolt::CompilationResult {
                 writeln!(output, "{}", line.text).unwrap();
             }
 
+            // When the program ran, the build's output is kept apart and
+            // stderr is the program's own, shown along with its stdout
             let mut errs = String::default();
-            if let Some(build_result) = &self.build_result {
-                for line in &build_result.stderr {
-                    writeln!(errs, "{}", line.text).unwrap();
+            match &self.build_result {
+                Some(build_result) => {
+                    for line in &build_result.stderr {
+                        writeln!(errs, "{}", line.text).unwrap();
+                    }
+                    for line in &self.stderr {
+                        writeln!(output, "{}", line.text).unwrap();
+                    }
+                }
+                None => {
+                    for line in &self.stderr {
+                        writeln!(errs, "{}", line.text).unwrap();
+                    }
                 }
-            }
-
-            for line in &self.stderr {
-                writeln!(errs, "{}", line.text).unwrap();
             }
 
             let stdout = output.trim();
@@ -296,8 +510,7 @@ impl ToEmbed for godbolt::CompilationResult {
             }
             if !stderr.is_empty() {
                 output = true;
-                let str = discordhelpers::conform_external_str(stderr, MAX_ERROR_LEN, true);
-                embed = embed.field("Compiler Output", format!("```\n{}\n```", str), false);
+                (embed, counts) = add_compiler_output(embed, stderr, options);
             }
 
             if !output {
@@ -305,19 +518,7 @@ impl ToEmbed for godbolt::CompilationResult {
             }
         }
 
-        let mut appendstr = author.name.clone();
-        if let Some(time) = self.exec_time {
-            appendstr = format!("{} | {}ms", appendstr, time);
-        }
-        if !options.compilation_info.language.is_empty() {
-            appendstr = format!("{} | {}", appendstr, options.compilation_info.language);
-        }
-        if !options.compilation_info.compiler.is_empty() {
-            appendstr = format!("{} | {}", appendstr, options.compilation_info.compiler);
-        }
-
-        let footer = CreateEmbedFooter::new(format!("{} | godbolt.org", appendstr));
-        embed.footer(footer)
+        (embed, counts)
     }
 }
 
@@ -336,6 +537,50 @@ fn chunk_output<'a>(lines: impl Iterator<Item = &'a str>) -> (Vec<String>, Strin
     (pieces, append)
 }
 
+/// Assembly grouped under the source lines it was generated from, each
+/// group headed by that line's code. Lines from other files or with no
+/// source stay in the group they appear in, or are dropped when only some
+/// source lines were asked for.
+pub fn map_assembly(asm: &[godbolt::AsmLine], map: &SourceMap) -> Vec<String> {
+    let source: Vec<&str> = map.code.lines().collect();
+    let source_line = |line: &godbolt::AsmLine| {
+        line.source
+            .as_ref()
+            .filter(|source| source.file.is_none())
+            .and_then(|source| source.line)
+    };
+
+    let mut output = Vec::new();
+    let mut current = None;
+    for line in asm {
+        let Some(text) = line.text.as_deref() else {
+            continue;
+        };
+        let number = source_line(line);
+        if let Some((first, last)) = map.lines {
+            if !number.is_some_and(|number| (first..=last).contains(&number)) {
+                continue;
+            }
+        }
+        if let Some(number) = number.filter(|_| number != current) {
+            current = Some(number);
+            let code = usize::try_from(number - 1)
+                .ok()
+                .and_then(|index| source.get(index))
+                .map_or("", |code| code.trim());
+            output.push(format!("; {} | {}", number, code));
+        }
+        output.push(text.to_string());
+    }
+    output
+}
+
+/// Whether output fits in the fields of a dump embed, or needs attaching
+pub fn fits_in_embed(text: &str) -> bool {
+    let (pieces, _) = chunk_output(text.trim().lines());
+    pieces.len() < MAX_DUMP_FIELDS
+}
+
 /// Render chunked output as one or more code-fence embed fields, using the
 /// "<title> Pt. N" scheme when the output spans multiple chunks. Returns the
 /// updated embed and whether any field was added.
@@ -375,10 +620,10 @@ pub async fn edit_message_embed(
     compilation_details: Option<CompilationDetails>,
 ) -> serenity::Result<()> {
     let mut url = None;
-    if let Some(details) = compilation_details {
+    if let Some(details) = &compilation_details {
         let data = ctx.data.read().await;
         if let Some(link_cache) = data.get::<LinkAPICache>() {
-            if let Some(b64) = details.godbolt_base64 {
+            if let Some(b64) = &details.godbolt_base64 {
                 let long_url = format!("https://godbolt.org/clientstate/{}", b64);
                 let link_cache_lock = link_cache.read().await;
                 url = link_cache_lock.get_link(long_url).await;
@@ -392,7 +637,7 @@ pub async fn edit_message_embed(
         btns.push(CreateButton::new_link(shorturl).label("View on godbolt.org"));
     }
 
-    let edit = {
+    let mut edit = {
         if btns.is_empty() {
             EditMessage::default()
                 .embed(emb.clone())
@@ -403,11 +648,33 @@ pub async fn edit_message_embed(
                 .embed(emb.clone())
         }
     };
+    if let Some(details) = &compilation_details {
+        edit = discordhelpers::edit_compiler_log(edit, details);
+    }
 
     old.edit(ctx, edit).await?;
     Ok(())
 }
 
+/// Footer for a result that was served by another backend than the one the
+/// target resolved to, e.g. "user | c++ | gcc-head | 1 warning | wandbox.org
+/// (godbolt.org unavailable)"
+pub fn build_failover_footer(
+    author: &User,
+    details: &CompilationDetails,
+    from: &str,
+) -> CreateEmbedFooter {
+    let text = result_footer_text(&author.name, details);
+    CreateEmbedFooter::new(format!("{} ({} unavailable)", text, from))
+}
+
+/// Footer for a result served from the result cache: the original footer,
+/// made out to the new requester and marked as cached
+pub fn build_cached_footer(author: &User, cached: &CachedResult) -> CreateEmbedFooter {
+    let text = result_footer_text(&author.name, &cached.details);
+    CreateEmbedFooter::new(format!("{} | cached", text))
+}
+
 pub fn build_insights_response_embed(author: &User, res: InsightsResponse) -> CreateEmbed {
     let error = res.return_code != 0;
     let footer = CreateEmbedFooter::new(format!(
@@ -494,6 +761,376 @@ pub fn build_invite_embed(invite_link: &str) -> CreateEmbed {
         .description(description)
 }
 
+/// How one compiler fared in a comparison
+pub struct CompareEntry {
+    pub target: String,
+    pub result: Result<CompilationDetails, String>,
+}
+
+/// Summary page of a ;compare, one field per compiler
+pub fn build_compare_embed(author: &User, entries: &[CompareEntry]) -> CreateEmbed {
+    let all_succeeded = entries
+        .iter()
+        .all(|entry| entry.result.as_ref().is_ok_and(|details| details.success));
+
+    let mut emb = CreateEmbed::new()
+        .title("Compiler comparison")
+        .color(if all_succeeded {
+            COLOR_OKAY
+        } else {
+            COLOR_FAIL
+        })
+        .description("Use the arrows to see each compiler's output")
+        .footer(CreateEmbedFooter::new(format!(
+            "Requested by: {}",
+            author.name
+        )));
+
+    for entry in entries {
+        let (icon, value) = match &entry.result {
+            Ok(details) => (
+                if details.success { "✅" } else { "❌" },
+                format!(
+                    "{} | {} | {}",
+                    details.language,
+                    details.compiler,
+                    // How long the program ran, as measured by the backend
+                    details
+                        .exec_time
+                        .map_or_else(|| String::from("n/a"), |time| format!("{}ms", time))
+                ),
+            ),
+            Err(error) => (
+                "⚠️",
+                discordhelpers::conform_external_str(error, MAX_OUTPUT_LEN, true),
+            ),
+        };
+        emb = emb.field(format!("{} {}", icon, entry.target), value, true);
+    }
+    emb
+}
+
+/// Add the output of a Compiler Explorer tool to a compilation embed; None
+/// means the tool didn't run
+pub fn add_tool_output(embed: CreateEmbed, tool: &str, output: Option<String>) -> CreateEmbed {
+    let value = match output {
+        Some(output) if !output.trim().is_empty() => format!(
+            "```\n{}\n```",
+            discordhelpers::conform_external_str(output.trim(), MAX_ERROR_LEN, true)
+        ),
+        Some(_) => String::from("No output"),
+        None => String::from("The tool didn't run, which usually means compilation failed"),
+    };
+    embed.field(format!("{} output", tool), value, false)
+}
+
+pub fn build_bench_embed(
+    author: &User,
+    details: &CompilationDetails,
+    stats: &BenchStats,
+    runs: usize,
+) -> CreateEmbed {
+    let mut emb = CreateEmbed::new()
+        .title("Benchmark results")
+        .color(COLOR_OKAY)
+        .field("Min", format!("{}ms", stats.min), true)
+        .field("Median", format!("{:.1}ms", stats.median), true)
+        .field("Mean", format!("{:.1}ms", stats.mean), true)
+        .field("Std. dev.", format!("{:.1}ms", stats.stddev), true)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} | {} of {} runs timed | {} | {}",
+            author.name, stats.runs, runs, details.language, details.compiler
+        )));
+
+    if stats.is_noisy() {
+        emb = emb.color(COLOR_WARN).description(
+            "⚠️ Timings varied a lot between runs, so these numbers are noisy. \
+             Run more iterations or give the program more work to do.",
+        );
+    }
+    emb
+}
+
+pub fn build_asmdiff_embed(
+    author: &User,
+    first: &str,
+    second: &str,
+    diff: &str,
+    attached: bool,
+) -> CreateEmbed {
+    let emb = CreateEmbed::new()
+        .title("Assembly diff")
+        .color(COLOR_OKAY)
+        .footer(CreateEmbedFooter::new(format!(
+            "Requested by: {} | {} vs {}",
+            author.name, first, second
+        )));
+
+    if diff.is_empty() {
+        emb.description("No differences, both produce the same assembly.")
+    } else if attached {
+        emb.description("The diff is too long to show here, so it's attached.")
+    } else {
+        emb.description(format!("```diff\n{}```", diff.replace('`', "\u{200B}`")))
+    }
+}
+
+pub fn build_triage_embed(
+    author: &User,
+    details: &CompilationDetails,
+    signal: &str,
+    finding: Option<String>,
+    attached: bool,
+) -> CreateEmbed {
+    let emb = CreateEmbed::new()
+        .title(format!("Crash triage ({})", signal))
+        .footer(CreateEmbedFooter::new(format!(
+            "Requested by: {} | {} with AddressSanitizer and UBSan",
+            author.name, details.compiler
+        )));
+
+    match finding {
+        Some(finding) => emb
+            .color(COLOR_FAIL)
+            .description(format!("```\n{}```", finding.replace('`', "\u{200B}`"))),
+        None if attached => emb.color(COLOR_WARN).description(
+            "The sanitizers didn't report anything, so the crash may depend on the compiler. \
+             Their full output is attached.",
+        ),
+        None => emb.color(COLOR_WARN).description(
+            "The program didn't crash with the sanitizers enabled and they didn't report anything.",
+        ),
+    }
+}
+
+pub fn build_settings_embed(title: &str, settings: &Settings) -> CreateEmbed {
+    let emb = CreateEmbed::new().title(title).color(COLOR_OKAY);
+    if settings.is_empty() {
+        return emb.description("Nothing is set, so the defaults are used.");
+    }
+
+    let list = |entries: Vec<String>| {
+        if entries.is_empty() {
+            String::from("Default")
+        } else {
+            entries.join("\n")
+        }
+    };
+    let compilers = settings
+        .compilers
+        .iter()
+        .map(|(language, compiler)| format!("`{}` → `{}`", language, compiler))
+        .collect();
+    let flags = settings
+        .flags
+        .iter()
+        .map(|(language, flags)| format!("`{}`: `{}`", language, flags.join(" ")))
+        .collect();
+    let output = match settings.output {
+        Some(OutputMode::Summary) => "Summary",
+        Some(OutputMode::Raw) => "Raw",
+        None => "Default",
+    };
+
+    let mut emb = emb
+        .field("Compilers", list(compilers), false)
+        .field("Flags", list(flags), false)
+        .field("Output", output, false)
+        .field(
+            "Language",
+            settings
+                .language
+                .as_ref()
+                .map_or(String::from("Default"), |language| {
+                    format!("`{}`", language)
+                }),
+            false,
+        );
+    if !settings.prefixes.is_empty() {
+        let prefixes = settings
+            .prefixes
+            .iter()
+            .map(|prefix| format!("`{}`", prefix))
+            .collect::<Vec<String>>()
+            .join(" ");
+        emb = emb.field("Prefixes", prefixes, false);
+    }
+    if !settings.asm_flags.is_empty() {
+        emb = emb.field(
+            "Assembly flags",
+            format!("`{}`", settings.asm_flags.join(" ")),
+            false,
+        );
+    }
+    emb
+}
+
+/// Most wrong answers a ;test reply shows the diff of
+const MAX_TEST_DIFFS: usize = 3;
+
+pub fn build_test_embed(
+    author: &User,
+    details: &CompilationDetails,
+    outcomes: &[CaseOutcome],
+) -> CreateEmbed {
+    let passed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, CaseOutcome::Passed))
+        .count();
+
+    let mut table = String::new();
+    for (index, outcome) in outcomes.iter().enumerate() {
+        let status = match outcome {
+            CaseOutcome::Passed => "✅ passed",
+            CaseOutcome::WrongAnswer { .. } => "❌ wrong answer",
+            CaseOutcome::Failed => "💥 exited with an error",
+        };
+        writeln!(table, "Case {}: {}", index + 1, status).unwrap();
+    }
+
+    let mut emb = CreateEmbed::new()
+        .title(format!("Tests: {}/{} passed", passed, outcomes.len()))
+        .color(if passed == outcomes.len() {
+            COLOR_OKAY
+        } else {
+            COLOR_FAIL
+        })
+        .description(table)
+        .footer(CreateEmbedFooter::new(format!(
+            "{} | {} | {}",
+            author.name, details.language, details.compiler
+        )));
+
+    let diffs = outcomes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, outcome)| match outcome {
+            CaseOutcome::WrongAnswer { diff } => Some((index, diff)),
+            _ => None,
+        })
+        .take(MAX_TEST_DIFFS);
+    for (index, diff) in diffs {
+        let diff = discordhelpers::conform_external_str(diff, MAX_ERROR_LEN, true);
+        emb = emb.field(
+            format!("Case {} diff", index + 1),
+            format!("```diff\n{}```", diff),
+            false,
+        );
+    }
+    emb
+}
+
+pub fn build_bisect_embed(
+    author: &User,
+    family: &str,
+    versions: &[Compiler],
+    bisection: &Bisection<Outcome>,
+) -> CreateEmbed {
+    let oldest = &versions[0].name;
+    let newest = &versions[versions.len() - 1].name;
+
+    let mut emb = CreateEmbed::new()
+        .title(format!("Bisect: {}", family))
+        .footer(CreateEmbedFooter::new(format!(
+            "Requested by: {} | Tried {} of {} compilers",
+            author.name,
+            bisection.probes(),
+            versions.len()
+        )));
+
+    let outcome_field = |index: usize| {
+        let outcome = bisection.outcomes[index].as_ref().unwrap();
+        let mut value = format!("Exit code: {}", outcome.exit_code);
+        if !outcome.diagnostics.is_empty() {
+            let diagnostics = outcome.diagnostics.join("\n");
+            let _ = write!(
+                value,
+                "\nDiagnostics:\n```\n{}\n```",
+                discordhelpers::conform_external_str(&diagnostics, MAX_OUTPUT_LEN, true)
+            );
+        }
+        let output = format!("{}{}", outcome.stdout, outcome.stderr);
+        if !output.trim().is_empty() {
+            let _ = write!(
+                value,
+                "\nOutput:\n```\n{}\n```",
+                discordhelpers::conform_external_str(&output, MAX_OUTPUT_LEN, true)
+            );
+        }
+        value
+    };
+
+    match bisection.first_changed {
+        Some(changed) => {
+            let last_same = &versions[bisection.last_same].name;
+            let first_changed = &versions[changed].name;
+            emb = emb
+                .color(COLOR_WARN)
+                .description(format!(
+                    "Behaviour changed between **{}** and **{}**",
+                    last_same, first_changed
+                ))
+                .field(
+                    format!("{} to {}", oldest, last_same),
+                    outcome_field(bisection.last_same),
+                    false,
+                )
+                .field(first_changed.to_string(), outcome_field(changed), false);
+        }
+        None => {
+            emb = emb
+                .color(COLOR_OKAY)
+                .description(format!(
+                    "No change in output, exit code or diagnostics from **{}** to **{}**",
+                    oldest, newest
+                ))
+                .field("Every version", outcome_field(0), false);
+        }
+    }
+    emb
+}
+
+pub fn build_status_embed(statuses: &[ServiceStatus]) -> CreateEmbed {
+    let mut emb = CreateEmbed::new().title("Service Status").color(COLOR_OKAY);
+    if statuses
+        .iter()
+        .any(|status| status.circuit != Circuit::Closed)
+    {
+        emb = emb.color(COLOR_WARN);
+    }
+
+    for status in statuses {
+        let icon = match status.circuit {
+            Circuit::Closed => "🟢",
+            Circuit::HalfOpen => "🟡",
+            Circuit::Open(_) => "🔴",
+        };
+
+        let mut value = match (status.success_rate, status.latency) {
+            (Some(rate), Some([p50, p95, p99])) => format!(
+                "{:.1}% of last {} requests succeeded\np50 {}ms · p95 {}ms · p99 {}ms",
+                rate * 100.0,
+                status.requests,
+                p50.as_millis(),
+                p95.as_millis(),
+                p99.as_millis()
+            ),
+            _ => String::from("No recent requests"),
+        };
+        if let Circuit::Open(_) = status.circuit {
+            value.push_str("\nRequests are paused");
+        }
+        if let Some((at, error)) = &status.last_error {
+            let ago = at.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+            let error = discordhelpers::conform_external_str(error, 200, true);
+            write!(value, "\nLast error ({}s ago): `{}`", ago, error).unwrap();
+        }
+
+        emb = emb.field(format!("{} {}", icon, status.name), value, false);
+    }
+    emb
+}
+
 pub fn build_join_embed(guild: &Guild) -> CreateEmbed {
     let mut emb = CreateEmbed::default()
         .title("Guild joined")
@@ -7,18 +7,24 @@ use std::sync::Arc;
 
 use serenity::{builder::CreateEmbed, http::Http, model::prelude::*};
 
-use crate::cache::{ConfigCache, MessageCache};
+use crate::cache::{ConfigCache, MessageCache, SettingsCache};
+use crate::managers::compilation::CompilationDetails;
+use crate::managers::settings;
 use crate::utls::constants::*;
 use crate::utls::discordhelpers;
+use crate::utls::parser;
 use serenity::client::Context;
-use serenity::framework::standard::CommandResult;
-use tokio::sync::MutexGuard;
+use serenity::framework::standard::{CommandError, CommandResult};
+use tokio::sync::{mpsc, MutexGuard};
+use tokio::task::JoinHandle;
+
+use crate::utls::backend_queue::QueueUpdates;
 
 use crate::commands::compile;
 use crate::utls::discordhelpers::embeds::embed_message;
 use serenity::all::{
-    ActivityData, CreateActionRow, CreateAllowedMentions, CreateEmbedFooter, CreateMessage,
-    EditMessage, ShardManager,
+    ActivityData, CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateEmbedFooter,
+    CreateMessage, EditMessage, ShardManager,
 };
 use std::fmt::Write as _;
 
@@ -29,6 +35,24 @@ pub fn reply_to(msg: &Message, new_msg: CreateMessage) -> CreateMessage {
         .allowed_mentions(CreateAllowedMentions::new().replied_user(false))
 }
 
+/// Attach the compiler's full output when the embed only shows part of it
+pub fn attach_compiler_log(msg: CreateMessage, details: &CompilationDetails) -> CreateMessage {
+    match &details.compiler_log {
+        Some(log) => msg.add_file(CreateAttachment::bytes(log.as_bytes(), "compiler.log")),
+        None => msg,
+    }
+}
+
+/// Replace the attachments of an edited result with the compiler's new
+/// log, if it has one
+pub fn edit_compiler_log(msg: EditMessage, details: &CompilationDetails) -> EditMessage {
+    let msg = msg.remove_all_attachments();
+    match &details.compiler_log {
+        Some(log) => msg.new_attachment(CreateAttachment::bytes(log.as_bytes(), "compiler.log")),
+        None => msg,
+    }
+}
+
 pub fn build_menu_items(
     items: Vec<String>,
     items_per_page: usize,
@@ -103,12 +127,26 @@ pub async fn handle_edit(
     original_message: Message,
     was_executed: bool,
 ) -> serenity::Result<()> {
-    let prefix = {
+    let (prefixes, bot_id) = {
         let data = ctx.data.read().await;
         let info = data.get::<ConfigCache>().unwrap().read().await;
-        info.get("BOT_PREFIX").unwrap().to_owned()
+        let settings = data.get::<SettingsCache>().unwrap().read().await;
+        let guild = original_message.guild_id.map(|id| id.get());
+        (
+            settings.prefixes(guild, info.get("BOT_PREFIX").unwrap()),
+            info.get("BOT_ID").and_then(|id| id.parse::<u64>().ok()),
+        )
     };
 
+    // the command the edit invokes, after the bot's mention or one of the
+    // guild's prefixes
+    let command = match parser::split_mention(&content) {
+        Some((id, command)) if Some(id) == bot_id => Some(command),
+        _ => settings::find_prefix(&prefixes, &content).map(|prefix| &content[prefix.len()..]),
+    }
+    .unwrap_or_default()
+    .to_string();
+
     // try to clear reactions
     if let Ok(updated_message) = old.channel_id.message(&ctx.http, old.id.get()).await {
         for reaction in &updated_message.reactions {
@@ -140,7 +178,7 @@ pub async fn handle_edit(
         .edit(&ctx.http, EditMessage::new().components(Vec::new()))
         .await;
 
-    if content.starts_with(&format!("{}asm", prefix)) {
+    if command.starts_with("asm") {
         if let Err(e) = handle_edit_asm(
             ctx,
             content,
@@ -153,7 +191,7 @@ pub async fn handle_edit(
             let mut err = embeds::build_fail_embed(&author, &e.to_string());
             embeds::edit_message_embed(ctx, &mut old, &mut err, None).await?;
         }
-    } else if content.starts_with(&format!("{}compile", prefix)) {
+    } else if command.starts_with("compile") {
         if let Err(e) = handle_edit_compile(
             ctx,
             content,
@@ -168,7 +206,7 @@ pub async fn handle_edit(
             let mut err = embeds::build_fail_embed(&author, &e.to_string());
             embeds::edit_message_embed(ctx, &mut old, &mut err, None).await?;
         }
-    } else if content.starts_with(&format!("{}exe", prefix)) {
+    } else if command.starts_with("exe") {
         // matches the execute command and its exec/exe aliases
         if let Err(e) = handle_edit_compile(
             ctx,
@@ -184,7 +222,7 @@ pub async fn handle_edit(
             let mut err = embeds::build_fail_embed(&author, &e.to_string());
             embeds::edit_message_embed(ctx, &mut old, &mut err, None).await?;
         }
-    } else if content.starts_with(&format!("{}cpp", prefix)) {
+    } else if command.starts_with("cpp") {
         if let Err(e) = handle_edit_cpp(
             ctx,
             content,
@@ -197,7 +235,7 @@ pub async fn handle_edit(
             let mut err = embeds::build_fail_embed(&author, &e.to_string());
             embeds::edit_message_embed(ctx, &mut old, &mut err, None).await?;
         }
-    } else if content.starts_with(&format!("{}insights", prefix)) {
+    } else if command.starts_with("insights") {
         if let Err(e) = handle_edit_insights(
             ctx,
             content,
@@ -279,13 +317,11 @@ pub async fn handle_edit_compile(
         vec![CreateActionRow::Buttons(buttons)]
     };
 
-    old.edit(
-        &ctx.http,
-        EditMessage::new()
-            .embed(result.embed)
-            .components(components),
-    )
-    .await?;
+    let edit = EditMessage::new()
+        .embed(result.embed)
+        .components(components);
+    old.edit(&ctx.http, edit_compiler_log(edit, &result.details))
+        .await?;
 
     if offer_execute {
         compile::await_execute_button(
@@ -431,3 +467,80 @@ pub async fn delete_bot_reacts(ctx: &Context, msg: &Message, react: ReactionType
         .await?;
     Ok(())
 }
+
+/// The loading reaction on a request's message. While the request waits
+/// for a busy backend the reaction is swapped for its queue position
+/// (1️⃣ to 🔟, then 🔢 further back) and swapped back once it gets a
+/// slot.
+pub struct LoadingStatus {
+    updates: QueueUpdates,
+    task: JoinHandle<()>,
+}
+
+impl LoadingStatus {
+    /// React to `msg` with the loading reaction
+    pub async fn start(
+        ctx: &Context,
+        msg: &Message,
+        loading: ReactionType,
+    ) -> Result<LoadingStatus, CommandError> {
+        if msg.react(&ctx.http, loading.clone()).await.is_err() {
+            return Err(CommandError::from(
+                "Unable to react to message. Am I missing permissions to react or use external emoji?",
+            ));
+        }
+
+        let (updates, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(show_queue_position(
+            ctx.clone(),
+            msg.clone(),
+            loading,
+            receiver,
+        ));
+        Ok(LoadingStatus { updates, task })
+    }
+
+    /// Where the request's queue position should be reported
+    pub fn updates(&self) -> Option<&QueueUpdates> {
+        Some(&self.updates)
+    }
+
+    /// Remove the status from the message
+    pub async fn finish(self) {
+        drop(self.updates);
+        let _ = self.task.await;
+    }
+}
+
+async fn show_queue_position(
+    ctx: Context,
+    msg: Message,
+    loading: ReactionType,
+    mut updates: mpsc::UnboundedReceiver<usize>,
+) {
+    let mut shown = loading.clone();
+    while let Some(position) = updates.recv().await {
+        let status = match position {
+            0 => loading.clone(),
+            position => queue_reaction(position),
+        };
+        if status == shown {
+            continue;
+        }
+
+        let _ = delete_bot_reacts(&ctx, &msg, shown).await;
+        let _ = msg.react(&ctx.http, status.clone()).await;
+        shown = status;
+    }
+
+    let _ = delete_bot_reacts(&ctx, &msg, shown).await;
+}
+
+fn queue_reaction(position: usize) -> ReactionType {
+    let emoji = match position {
+        1..=9 => format!("{}\u{fe0f}\u{20e3}", position),
+        10 => String::from("\u{1f51f}"),
+        _ => String::from("\u{1f522}"),
+    };
+    ReactionType::Unicode(emoji)
+}
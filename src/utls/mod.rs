@@ -1,4 +1,14 @@
+pub mod backend_queue;
+pub mod bench;
+pub mod bisect;
 pub mod blocklist;
+pub mod cfg;
 pub mod constants;
+pub mod diagnostics;
 pub mod discordhelpers;
 pub mod parser;
+pub mod presets;
+pub mod result_cache;
+pub mod sandbox;
+pub mod test_cases;
+pub mod triage;
@@ -1,10 +1,15 @@
-use crate::utls::constants::{CODE_BLOCK_REGEX, C_LIKE_INCLUDE_REGEX, URL_ALLOW_LIST, USER_AGENT};
+use crate::utls::constants::{
+    CODE_BLOCK_REGEX, C_LIKE_INCLUDE_REGEX, MENTION_PREFIX_REGEX, URL_ALLOW_LIST, USER_AGENT,
+};
 
 use serenity::framework::standard::CommandError;
 use serenity::model::channel::{Attachment, Message};
 use serenity::model::user::User;
 
 use crate::managers::compilation::CompilationManager;
+use crate::utls::diagnostics::OutputMode;
+use crate::utls::presets::Preset;
+use regex::Captures;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -42,17 +47,74 @@ pub struct ParserResult {
     pub code: String,
     pub options: Vec<String>,
     pub args: Vec<String>,
+    /// Flags like `--asan` that `CompilationManager` expands into options
+    /// for the compiler handling the request
+    pub presets: Vec<Preset>,
+    /// How compiler output is shown, from the requester's settings
+    pub output: OutputMode,
+    /// Every file of a multi-file project, main file first. `code` holds
+    /// the main file's contents too.
+    pub files: Vec<SourceFile>,
+}
+
+impl ParserResult {
+    /// Whether the request is made of several files
+    pub fn is_project(&self) -> bool {
+        !self.files.is_empty()
+    }
+
+    /// Whether the main file is a CMakeLists.txt building the project
+    pub fn is_cmake(&self) -> bool {
+        self.files
+            .first()
+            .is_some_and(|file| file.name == "CMakeLists.txt")
+    }
+}
+
+/// One labelled code block of a multi-file project
+#[derive(Debug, Default, Clone, Hash, PartialEq)]
+pub struct SourceFile {
+    pub name: String,
+    pub code: String,
+}
+
+/// Split a leading mention off a message, giving the mentioned id and the
+/// command after it
+pub fn split_mention(content: &str) -> Option<(u64, &str)> {
+    let captures = MENTION_PREFIX_REGEX.captures(content)?;
+    let id = captures.name("id")?.as_str().parse().ok()?;
+    Some((id, &content[captures.get(0)?.end()..]))
+}
+
+/// A command without the mention it was invoked with, so mentioning the bot
+/// parses the same as using its prefix
+pub fn strip_mention(content: &str) -> &str {
+    split_mention(content).map_or(content, |(_, command)| command)
 }
 
-#[allow(clippy::while_let_on_iterator)]
 pub async fn get_components(
     input: &str,
     author: &User,
     compilation_manager: Option<&Arc<RwLock<CompilationManager>>>,
     reply: &Option<Box<Message>>,
     ignore_lang: bool,
+) -> Result<ParserResult, CommandError> {
+    get_components_with_language(input, author, compilation_manager, reply, ignore_lang, None).await
+}
+
+/// Like `get_components`, but requests that name no language and have no
+/// syntax highlighting get `language`, the requester's default
+#[allow(clippy::while_let_on_iterator)]
+pub async fn get_components_with_language(
+    input: &str,
+    author: &User,
+    compilation_manager: Option<&Arc<RwLock<CompilationManager>>>,
+    reply: &Option<Box<Message>>,
+    ignore_lang: bool,
+    language: Option<&str>,
 ) -> Result<ParserResult, CommandError> {
     let mut result = ParserResult::default();
+    let input = strip_mention(input);
 
     // Find the index for where we should stop parsing user input
     let mut end_point: usize = input.len();
@@ -122,6 +184,10 @@ pub async fn get_components(
             }
 
             result.stdin = input.trim().to_owned();
+        } else if let Some(preset) = Preset::from_flag(c.trim()) {
+            if !result.presets.contains(&preset) {
+                result.presets.push(preset);
+            }
         } else {
             result.options.push(c.trim().to_string());
         }
@@ -172,6 +238,11 @@ pub async fn get_components(
         ));
     }
 
+    if result.target.is_empty() {
+        if let Some(language) = language {
+            result.target = language.to_string();
+        }
+    }
     if result.target.is_empty() {
         return Err(CommandError::from("You must provide a valid language or compiler!\n\n;compile c++ \n\\`\\`\\`\nint main() {}\n\\`\\`\\`"));
     }
@@ -260,11 +331,23 @@ pub async fn find_code_block(
     let matches = CODE_BLOCK_REGEX.captures_iter(haystack);
 
     let mut captures = Vec::new();
-    let list = matches.enumerate();
-    for (_, cap) in list {
+    for cap in matches {
         captures.push(cap);
     }
 
+    // Several blocks labelled with a file name (```cpp main.cpp), or a
+    // CMakeLists.txt, form a project. A single labelled block is an ordinary
+    // request, with the label only naming its file.
+    let names: Vec<&str> = captures
+        .iter()
+        .filter_map(|cap| cap.name("filename"))
+        .map(|name| name.as_str())
+        .collect();
+    if names.len() > 1 || names.contains(&"CMakeLists.txt") {
+        find_project(result, &captures)?;
+        return Ok(true);
+    }
+
     // support for stdin codeblocks
     let code_index; // index into captures where we might find our target lang
     match captures.len() {
@@ -283,8 +366,8 @@ pub async fn find_code_block(
     }
 
     let code_copy = result.code.clone();
-    let matches = C_LIKE_INCLUDE_REGEX.captures_iter(&code_copy).enumerate();
-    for (_, cap) in matches {
+    let matches = C_LIKE_INCLUDE_REGEX.captures_iter(&code_copy);
+    for cap in matches {
         if let Some(statement) = cap.name("statement") {
             let include_stmt = statement.as_str();
             let url = cap.name("url").unwrap().as_str();
@@ -296,15 +379,102 @@ pub async fn find_code_block(
     }
 
     // if we still don't have our language target, lets try the language for syntax highlighting
+    // and then the extension of the block's file name
     if result.target.is_empty() {
-        if let Some(lang_match) = captures[code_index].name("language") {
-            result.target = shortname_to_qualified(lang_match.as_str()).to_owned();
+        let block = &captures[code_index];
+        let language = block
+            .name("language")
+            .map(|l| l.as_str().to_string())
+            .filter(|l| !l.is_empty())
+            .or_else(|| {
+                let name = block.name("filename")?;
+                let extension = Path::new(name.as_str()).extension()?;
+                Some(extension.to_string_lossy().to_lowercase())
+            });
+        if let Some(language) = language {
+            result.target = shortname_to_qualified(&language).to_owned();
         }
     }
 
     Ok(true)
 }
 
+/// Extensions of files that can't be the main source of a project
+const HEADER_EXTENSIONS: [&str; 7] = ["h", "hh", "hpp", "hxx", "inc", "ipp", "tpp"];
+
+/// Collect the labelled code blocks of a multi-file project. A
+/// CMakeLists.txt makes it a CMake project; otherwise the first file that
+/// isn't a header is the main source. An unlabelled block is used as stdin.
+fn find_project(result: &mut ParserResult, captures: &[Captures]) -> Result<(), CommandError> {
+    let mut files: Vec<(Option<&str>, SourceFile)> = Vec::new();
+    for cap in captures {
+        let code = cap.name("code").unwrap().as_str().to_string();
+        let name = match cap.name("filename") {
+            Some(name) => name.as_str().to_string(),
+            None if result.stdin.is_empty() => {
+                result.stdin = code;
+                continue;
+            }
+            None => {
+                return Err(CommandError::from(
+                    "Label every code block of a project with its file name, e.g. \\`\\`\\`cpp main.cpp",
+                ))
+            }
+        };
+
+        if files.iter().any(|(_, file)| file.name == name) {
+            return Err(CommandError::from(format!(
+                "The file '{}' appears more than once.",
+                name
+            )));
+        }
+        let language = cap.name("language").map(|l| l.as_str());
+        files.push((
+            language.filter(|l| !l.is_empty()),
+            SourceFile { name, code },
+        ));
+    }
+
+    let is_source = |file: &SourceFile| {
+        let extension = Path::new(&file.name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        file.name != "CMakeLists.txt" && !HEADER_EXTENSIONS.contains(&extension.as_str())
+    };
+
+    let source = files
+        .iter()
+        .position(|(_, file)| is_source(file))
+        .ok_or_else(|| CommandError::from("A project needs at least one source file."))?;
+
+    // Take the language from the first source's syntax highlighting or, failing
+    // that, its extension
+    if result.target.is_empty() {
+        let (language, file) = &files[source];
+        let language = language
+            .map(str::to_string)
+            .or_else(|| {
+                Path::new(&file.name)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_lowercase())
+            })
+            .unwrap_or_default();
+        result.target = shortname_to_qualified(&language).to_owned();
+    }
+
+    let main = files
+        .iter()
+        .position(|(_, file)| file.name == "CMakeLists.txt")
+        .unwrap_or(source);
+
+    let mut files: Vec<SourceFile> = files.into_iter().map(|(_, file)| file).collect();
+    files[..=main].rotate_right(1);
+    result.code = files[0].code.clone();
+    result.files = files;
+    Ok(())
+}
+
 pub async fn get_message_attachment(
     attachments: &[Attachment],
 ) -> Result<(String, String), CommandError> {
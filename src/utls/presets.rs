@@ -0,0 +1,199 @@
+//! High-level flags like `--asan` or `--hardened` that stand for a set of
+//! compiler options, so users don't have to remember each toolchain's
+//! spelling. The parser collects them and `CompilationManager` expands them
+//! once it knows which compiler will handle the request.
+
+use serenity::framework::standard::CommandError;
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq)]
+pub enum Preset {
+    Asan,
+    Ubsan,
+    Tsan,
+    Msan,
+    Hardened,
+    Debug,
+}
+
+/// Presets that can't be used in the same request
+const CONFLICTS: &[(Preset, Preset)] = &[
+    (Preset::Asan, Preset::Tsan),
+    (Preset::Asan, Preset::Msan),
+    (Preset::Tsan, Preset::Msan),
+    (Preset::Hardened, Preset::Debug),
+];
+
+impl Preset {
+    /// The preset a command line flag stands for, if any
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        match flag {
+            "--asan" => Some(Preset::Asan),
+            "--ubsan" => Some(Preset::Ubsan),
+            "--tsan" => Some(Preset::Tsan),
+            "--msan" => Some(Preset::Msan),
+            "--hardened" => Some(Preset::Hardened),
+            "--debug" => Some(Preset::Debug),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self) -> &'static str {
+        match self {
+            Preset::Asan => "--asan",
+            Preset::Ubsan => "--ubsan",
+            Preset::Tsan => "--tsan",
+            Preset::Msan => "--msan",
+            Preset::Hardened => "--hardened",
+            Preset::Debug => "--debug",
+        }
+    }
+
+    /// Whether the preset needs a sanitizer runtime to execute
+    pub fn is_sanitizer(&self) -> bool {
+        matches!(
+            self,
+            Preset::Asan | Preset::Ubsan | Preset::Tsan | Preset::Msan
+        )
+    }
+
+    /// Options for the preset, compile and link flags alike, or None if the
+    /// toolchain doesn't support it
+    fn options(&self, toolchain: Toolchain) -> Option<&'static [&'static str]> {
+        let options: &[&str] = match (toolchain, self) {
+            (Toolchain::Msvc, Preset::Asan) => &["/fsanitize=address", "/Zi"],
+            (Toolchain::Msvc, Preset::Hardened) => &["/GS", "/sdl", "/guard:cf"],
+            (Toolchain::Msvc, Preset::Debug) => &["/Od", "/Zi", "/RTC1"],
+            (Toolchain::Msvc, _) => return None,
+            (Toolchain::Gcc, Preset::Msan) => return None,
+            (_, Preset::Asan) => &["-fsanitize=address", "-fno-omit-frame-pointer", "-g"],
+            (_, Preset::Ubsan) => &[
+                "-fsanitize=undefined",
+                "-fno-sanitize-recover=undefined",
+                "-g",
+            ],
+            (_, Preset::Tsan) => &["-fsanitize=thread", "-g"],
+            (_, Preset::Msan) => &[
+                "-fsanitize=memory",
+                "-fsanitize-memory-track-origins",
+                "-fno-omit-frame-pointer",
+                "-g",
+            ],
+            (_, Preset::Hardened) => &[
+                "-O2",
+                "-D_FORTIFY_SOURCE=2",
+                "-fstack-protector-strong",
+                "-fstack-clash-protection",
+                "-fPIE",
+                "-pie",
+                "-Wl,-z,relro,-z,now",
+            ],
+            (_, Preset::Debug) => &["-O0", "-g", "-D_GLIBCXX_ASSERTIONS"],
+        };
+        Some(options)
+    }
+}
+
+/// Compiler families that spell options differently
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Toolchain {
+    Gcc,
+    Clang,
+    Msvc,
+}
+
+impl Toolchain {
+    /// Guess the family from a backend's compiler id (e.g., "g132",
+    /// "clang1600", "vcpp_v19_latest_x64", "gcc-13.2.0", "clang++")
+    pub fn detect(compiler: &str) -> Option<Self> {
+        let compiler = compiler.to_lowercase();
+        if compiler.starts_with("vcpp") || compiler.contains("msvc") {
+            return Some(Toolchain::Msvc);
+        }
+        if compiler.contains("clang") || compiler.starts_with("icx") {
+            return Some(Toolchain::Clang);
+        }
+
+        // Compiler Explorer names GCC builds g132, or cg132 for C
+        let short = compiler.strip_prefix('c').unwrap_or(&compiler);
+        let numbered = short
+            .strip_prefix('g')
+            .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()));
+        if numbered || compiler.contains("gcc") || compiler.starts_with("g++") {
+            return Some(Toolchain::Gcc);
+        }
+        None
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Toolchain::Gcc => "GCC",
+            Toolchain::Clang => "Clang",
+            Toolchain::Msvc => "MSVC",
+        }
+    }
+}
+
+/// Expand presets into compiler options for `compiler` on a backend shown to
+/// users as `service`. `sanitizers` is false for backends that can't run
+/// sanitized programs.
+pub fn expand(
+    presets: &[Preset],
+    compiler: &str,
+    service: &str,
+    sanitizers: bool,
+) -> Result<Vec<String>, CommandError> {
+    for (first, second) in CONFLICTS {
+        if presets.contains(first) && presets.contains(second) {
+            return Err(CommandError::from(format!(
+                "`{}` and `{}` can't be used together.",
+                first.flag(),
+                second.flag()
+            )));
+        }
+    }
+
+    if !sanitizers {
+        if let Some(preset) = presets.iter().find(|preset| preset.is_sanitizer()) {
+            return Err(CommandError::from(format!(
+                "`{}` isn't available on {}. Try a compiler hosted on godbolt.org instead.",
+                preset.flag(),
+                service
+            )));
+        }
+    }
+
+    let toolchain = match (Toolchain::detect(compiler), presets.first()) {
+        (Some(toolchain), _) => toolchain,
+        (None, Some(preset)) => {
+            return Err(CommandError::from(format!(
+                "`{}` only works with GCC, Clang and MSVC compilers, not '{}'.",
+                preset.flag(),
+                compiler
+            )))
+        }
+        (None, None) => return Ok(Vec::new()),
+    };
+
+    let mut options: Vec<String> = Vec::new();
+    for preset in presets {
+        let expanded = preset.options(toolchain).ok_or_else(|| {
+            let alternative = match preset {
+                Preset::Msan => "Try a Clang compiler instead.",
+                _ => "Try a GCC or Clang compiler instead.",
+            };
+            CommandError::from(format!(
+                "`{}` isn't supported by {}. {}",
+                preset.flag(),
+                toolchain.name(),
+                alternative
+            ))
+        })?;
+
+        for option in expanded {
+            if !options.iter().any(|existing| existing == option) {
+                options.push(option.to_string());
+            }
+        }
+    }
+    Ok(options)
+}
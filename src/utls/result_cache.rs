@@ -0,0 +1,128 @@
+//! Short-lived cache of finished compilations, so re-running an unchanged
+//! snippet (pressing Execute again, editing a message back and forth) doesn't
+//! hit the backend twice. Identical requests that arrive while the first one
+//! is still running wait for its result instead of making their own call.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lru_cache::LruCache;
+use serenity::builder::CreateEmbed;
+use tokio::sync::watch;
+
+use crate::managers::compilation::CompilationDetails;
+
+/// A stored result. The embed still carries the original requester's footer;
+/// others get one rebuilt from `details`.
+#[derive(Clone)]
+pub struct CachedResult {
+    pub details: CompilationDetails,
+    pub embed: CreateEmbed,
+}
+
+/// What a finished request tells the requests waiting on it
+type Outcome = Option<Result<CachedResult, String>>;
+
+pub enum Lookup<'a> {
+    /// A stored or coalesced result
+    Hit(Box<CachedResult>),
+    /// The in-flight request this one waited on failed with this message
+    Failed(String),
+    /// Nothing to reuse; the caller makes the upstream call and reports back
+    /// through the flight
+    Lead(Flight<'a>),
+}
+
+pub struct ResultCache {
+    ttl: Duration,
+    entries: Mutex<LruCache<u64, (Instant, CachedResult)>>,
+    in_flight: Mutex<HashMap<u64, watch::Receiver<Outcome>>>,
+}
+
+impl ResultCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        ResultCache {
+            ttl,
+            entries: Mutex::new(LruCache::new(capacity)),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn lookup(&self, key: u64) -> Lookup<'_> {
+        loop {
+            if let Some(result) = self.get(key) {
+                return Lookup::Hit(Box::new(result));
+            }
+
+            let mut receiver = {
+                let mut in_flight = self.in_flight.lock().unwrap();
+                match in_flight.get(&key) {
+                    Some(receiver) => receiver.clone(),
+                    None => {
+                        let (sender, receiver) = watch::channel(None);
+                        in_flight.insert(key, receiver);
+                        return Lookup::Lead(Flight {
+                            cache: self,
+                            key,
+                            sender,
+                        });
+                    }
+                }
+            };
+
+            // An error means the leading request was dropped before it
+            // finished, so start over and possibly lead in its place
+            let outcome = receiver
+                .wait_for(Option::is_some)
+                .await
+                .map(|outcome| outcome.clone());
+            if let Ok(Some(outcome)) = outcome {
+                return match outcome {
+                    Ok(result) => Lookup::Hit(Box::new(result)),
+                    Err(message) => Lookup::Failed(message),
+                };
+            }
+        }
+    }
+
+    fn get(&self, key: u64) -> Option<CachedResult> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get_mut(&key) {
+            Some((stored, result)) if stored.elapsed() < self.ttl => Some(result.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+/// The one request making the upstream call for a key. Dropping it without
+/// calling `finish` lets a waiting request take over.
+pub struct Flight<'a> {
+    cache: &'a ResultCache,
+    key: u64,
+    sender: watch::Sender<Outcome>,
+}
+
+impl Flight<'_> {
+    /// Hand the outcome to waiting requests, storing it if `store` is set
+    pub fn finish(self, outcome: Result<CachedResult, String>, store: bool) {
+        if let (Ok(result), true) = (&outcome, store) {
+            self.cache
+                .entries
+                .lock()
+                .unwrap()
+                .insert(self.key, (Instant::now(), result.clone()));
+        }
+        self.sender.send_replace(Some(outcome));
+    }
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        self.cache.in_flight.lock().unwrap().remove(&self.key);
+    }
+}
@@ -0,0 +1,668 @@
+//! Runs untrusted programs on the bot host inside a locked-down child process.
+//!
+//! Every process is started in fresh mount, pid, network, ipc and uts
+//! namespaces, as an unprivileged user reserved for the request, with rlimits
+//! for cpu time, memory, file size and process count. Its root is an empty
+//! read-only tmpfs holding only the request's directory (at WORKDIR), a few
+//! devices and read-only binds of the toolchain directories (DEFAULT_MOUNTS or
+//! LOCAL_SANDBOX_MOUNTS), so programs can't see the bot's files or processes
+//! whatever their permissions. The whole group is killed once the wall-clock
+//! timeout elapses, and the pid namespace takes anything left with it.
+//!
+//! Setting this up takes root, or CAP_SYS_ADMIN, CAP_SETUID, CAP_SETGID and
+//! CAP_CHOWN. The kernel is still shared with the bot, so a kernel exploit
+//! escapes the sandbox; see the README for what it does and doesn't protect.
+
+use std::ffi::CString;
+use std::io;
+use std::ops::RangeInclusive;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+
+/// Largest amount of stdout or stderr kept from a single process
+const MAX_CAPTURE: u64 = 64 * 1024;
+
+/// Host directories bound read-only into every sandbox when
+/// LOCAL_SANDBOX_MOUNTS is unset. Ones that don't exist are skipped.
+pub const DEFAULT_MOUNTS: &[&str] = &["/bin", "/sbin", "/usr", "/lib", "/lib32", "/lib64", "/etc"];
+
+/// Where the request's directory appears inside the sandbox
+pub const WORKDIR: &str = "/sandbox";
+
+/// Devices bound into the sandbox's /dev
+const DEVICES: &[&str] = &["null", "zero", "full", "random", "urandom"];
+
+/// Symlinks created in the sandbox's root, as (path, target)
+const LINKS: &[(&str, &str)] = &[
+    ("dev/fd", "/proc/self/fd"),
+    ("dev/stdin", "/proc/self/fd/0"),
+    ("dev/stdout", "/proc/self/fd/1"),
+    ("dev/stderr", "/proc/self/fd/2"),
+    ("tmp", WORKDIR),
+];
+
+/// Directories from LOCAL_SANDBOX_MOUNTS, separated by ':' like PATH, or
+/// DEFAULT_MOUNTS. Toolchains installed elsewhere, like ~/.cargo, must be
+/// listed there to be usable.
+pub fn mounts_from_env() -> Vec<PathBuf> {
+    match std::env::var("LOCAL_SANDBOX_MOUNTS") {
+        Ok(mounts) if !mounts.trim().is_empty() => std::env::split_paths(&mounts).collect(),
+        _ => DEFAULT_MOUNTS.iter().map(PathBuf::from).collect(),
+    }
+}
+
+/// Refuse to run programs on a host where the bot's own configuration is
+/// readable by everyone. The sandbox never mounts it, but a file that
+/// every account may read is one mistake away from leaking BOT_TOKEN.
+pub fn check_config_permissions() -> Result<(), String> {
+    let settings = std::env::var("SETTINGS_PATH").unwrap_or_else(|_| String::from("settings.db"));
+    let mut files = vec![String::from(".env"), settings];
+    files.extend(std::env::var("ROUTING_CONFIG").ok());
+
+    for file in files {
+        let Ok(metadata) = std::fs::metadata(&file) else {
+            continue;
+        };
+        if metadata.permissions().mode() & 0o004 != 0 {
+            return Err(format!(
+                "'{}' is readable by every user, run chmod 600 on it first",
+                file
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SandboxLimits {
+    pub cpu_seconds: u64,
+    pub memory_bytes: u64,
+    pub file_size_bytes: u64,
+    pub processes: u64,
+    pub timeout: Duration,
+}
+
+impl Default for SandboxLimits {
+    fn default() -> Self {
+        SandboxLimits {
+            cpu_seconds: 10,
+            memory_bytes: 1024 * 1024 * 1024,
+            file_size_bytes: 16 * 1024 * 1024,
+            processes: 64,
+            timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+impl SandboxLimits {
+    /// Default limits, overridden by any LOCAL_SANDBOX_CPU (seconds),
+    /// LOCAL_SANDBOX_MEMORY (MiB), LOCAL_SANDBOX_FSIZE (MiB),
+    /// LOCAL_SANDBOX_NPROC or LOCAL_SANDBOX_TIMEOUT (seconds) variables
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+
+        let mut limits = SandboxLimits::default();
+        if let Some(cpu) = var("LOCAL_SANDBOX_CPU") {
+            limits.cpu_seconds = cpu;
+        }
+        if let Some(memory) = var("LOCAL_SANDBOX_MEMORY") {
+            limits.memory_bytes = memory * 1024 * 1024;
+        }
+        if let Some(fsize) = var("LOCAL_SANDBOX_FSIZE") {
+            limits.file_size_bytes = fsize * 1024 * 1024;
+        }
+        if let Some(nproc) = var("LOCAL_SANDBOX_NPROC") {
+            limits.processes = nproc;
+        }
+        if let Some(timeout) = var("LOCAL_SANDBOX_TIMEOUT") {
+            limits.timeout = Duration::from_secs(timeout);
+        }
+        limits
+    }
+}
+
+/// Unprivileged users that sandboxed processes run as, e.g. uids
+/// 61000-61015. Each request has a user to itself until it finishes, so
+/// requests can't signal or trace each other, and RLIMIT_NPROC (which counts
+/// every process of a user) only counts the request's own processes. Each
+/// uid runs with the group id of the same number.
+pub struct SandboxUsers {
+    free: Mutex<Vec<u32>>,
+    available: Semaphore,
+}
+
+impl SandboxUsers {
+    /// Users from LOCAL_SANDBOX_UIDS, a range like 61000-61015
+    pub fn from_env() -> Result<Self, String> {
+        let range = std::env::var("LOCAL_SANDBOX_UIDS").map_err(|_| {
+            String::from("LOCAL_SANDBOX_UIDS must name the unprivileged uids to run programs as")
+        })?;
+        let (first, last) = range.split_once('-').unwrap_or((&range, &range));
+        let uid = |value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid LOCAL_SANDBOX_UIDS '{}'", range))
+        };
+        Self::new(uid(first)?..=uid(last)?)
+    }
+
+    pub fn new(uids: RangeInclusive<u32>) -> Result<Self, String> {
+        // SAFETY: getuid has no preconditions
+        let own = unsafe { libc::getuid() };
+        if uids.is_empty() {
+            return Err(String::from("LOCAL_SANDBOX_UIDS is empty"));
+        }
+        if uids.contains(&0) || uids.contains(&own) {
+            return Err(String::from(
+                "LOCAL_SANDBOX_UIDS can't include root or the bot's own uid",
+            ));
+        }
+
+        let free: Vec<u32> = uids.rev().collect();
+        Ok(SandboxUsers {
+            available: Semaphore::new(free.len()),
+            free: Mutex::new(free),
+        })
+    }
+
+    /// Reserve a user, waiting for one to be returned if all are in use
+    pub async fn acquire(&self) -> SandboxUser<'_> {
+        let permit = self
+            .available
+            .acquire()
+            .await
+            .expect("sandbox users are never closed");
+        permit.forget();
+
+        let uid = self.free.lock().unwrap().pop().expect("a user is free");
+        SandboxUser { uid, users: self }
+    }
+}
+
+/// A user reserved for one request, returned to the pool when dropped
+pub struct SandboxUser<'a> {
+    pub uid: u32,
+    users: &'a SandboxUsers,
+}
+
+impl Drop for SandboxUser<'_> {
+    fn drop(&mut self) {
+        self.users.free.lock().unwrap().push(self.uid);
+        self.users.available.add_permits(1);
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ProcessOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_code: Option<i32>,
+    /// Signal that terminated the process, if any
+    pub signal: Option<i32>,
+    pub timed_out: bool,
+    pub truncated: bool,
+}
+
+impl ProcessOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0) && !self.timed_out
+    }
+}
+
+/// Run `program` with `args` inside `dir` as `uid`, feeding it `stdin`.
+/// `dir` must be writable by `uid`, see `hand_over`. Only `mounts` and `dir`
+/// are visible to the program, `dir` as WORKDIR.
+///
+/// The environment is cleared except for PATH; HOME and TMPDIR point at
+/// WORKDIR so toolchains keep their caches inside the sandbox directory.
+pub async fn run(
+    program: &str,
+    args: &[String],
+    dir: &Path,
+    stdin: &str,
+    limits: &SandboxLimits,
+    uid: u32,
+    mounts: &[PathBuf],
+) -> io::Result<ProcessOutput> {
+    // The new root is built on top of an empty directory of our own
+    let root = tempfile::Builder::new().prefix("sandbox-root").tempdir()?;
+    let jail = Jail::new(root.path(), dir, mounts)?;
+
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .env_clear()
+        .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+        .env("HOME", WORKDIR)
+        .env("TMPDIR", WORKDIR)
+        .env("LANG", "C.UTF-8")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let limits = *limits;
+    // SAFETY: lock_down only performs async-signal-safe libc calls, on
+    // memory allocated before the fork
+    unsafe {
+        command.pre_exec(move || lock_down(&jail, &limits, uid));
+    }
+
+    let mut child = command.spawn()?;
+    let pgid = child.id().map(|pid| pid as libc::pid_t);
+
+    if let Some(mut pipe) = child.stdin.take() {
+        let input = stdin.as_bytes().to_vec();
+        tokio::spawn(async move {
+            // The program may exit without reading its input
+            let _ = pipe.write_all(&input).await;
+        });
+    }
+    let stdout = tokio::spawn(capture(child.stdout.take()));
+    let stderr = tokio::spawn(capture(child.stderr.take()));
+
+    let (status, timed_out) = match tokio::time::timeout(limits.timeout, child.wait()).await {
+        Ok(status) => (status?, false),
+        Err(_) => {
+            kill_group(pgid);
+            (child.wait().await?, true)
+        }
+    };
+    // Anything the program forked off would otherwise keep our pipes open,
+    // and could keep running as the user after it leaves the process group
+    kill_group(pgid);
+    tokio::task::spawn_blocking(move || kill_user(uid))
+        .await
+        .map_err(io::Error::other)?;
+
+    let (stdout, stdout_truncated) = stdout.await.unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr.await.unwrap_or_default();
+
+    Ok(ProcessOutput {
+        stdout,
+        stderr,
+        exit_code: status.code(),
+        signal: status.signal(),
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+/// Give `uid` the sandbox directory and the files already in it
+pub fn hand_over(dir: &Path, uid: u32) -> io::Result<()> {
+    std::os::unix::fs::chown(dir, Some(uid), Some(uid))?;
+    for entry in std::fs::read_dir(dir)? {
+        std::os::unix::fs::chown(entry?.path(), Some(uid), Some(uid))?;
+    }
+    Ok(())
+}
+
+/// Paths `lock_down` needs to build the sandbox's root, made ready before
+/// forking since the child must not allocate
+struct Jail {
+    /// Empty host directory that becomes the root
+    root: CString,
+    /// (source, target, is a directory) for every bind mount
+    binds: Vec<(CString, CString, bool)>,
+    /// (path, target) for every symlink
+    links: Vec<(CString, CString)>,
+    /// Directories created in the root before anything is mounted
+    dirs: Vec<CString>,
+    workdir: CString,
+    /// The workdir's host directory, the one bind that stays writable
+    workdir_source: CString,
+}
+
+impl Jail {
+    fn new(root: &Path, dir: &Path, mounts: &[PathBuf]) -> io::Result<Self> {
+        let c = |path: &Path| {
+            CString::new(path.as_os_str().as_bytes()).map_err(|e| io::Error::other(e.to_string()))
+        };
+        let inside = |path: &str| root.join(path.trim_start_matches('/'));
+
+        let mut jail = Jail {
+            root: c(root)?,
+            binds: Vec::new(),
+            links: Vec::new(),
+            dirs: vec![c(&inside("dev"))?, c(&inside("proc"))?, c(&inside(".old"))?],
+            workdir: c(&inside(WORKDIR))?,
+            workdir_source: c(dir)?,
+        };
+        jail.dirs.push(jail.workdir.clone());
+
+        for mount in mounts {
+            let Ok(metadata) = std::fs::symlink_metadata(mount) else {
+                continue;
+            };
+            let target = inside(&mount.to_string_lossy());
+            // Merged-usr systems link /bin to usr/bin, which is bound anyway
+            if metadata.file_type().is_symlink() {
+                let link = std::fs::read_link(mount)?;
+                jail.links.push((c(&target)?, c(&link)?));
+            } else if metadata.is_dir() {
+                jail.binds.push((c(mount)?, c(&target)?, true));
+            }
+        }
+        for device in DEVICES {
+            let source = Path::new("/dev").join(device);
+            if source.exists() {
+                jail.binds
+                    .push((c(&source)?, c(&inside(&format!("dev/{}", device)))?, false));
+            }
+        }
+        for (path, target) in LINKS {
+            jail.links.push((c(&inside(path))?, c(Path::new(target))?));
+        }
+        Ok(jail)
+    }
+}
+
+/// Runs in the forked child right before exec. The child is left outside
+/// the new pid namespace, so it forks the namespace's init, which builds the
+/// root and forks again for the program. The program isn't init itself
+/// since init ignores signals it has no handler for, like SIGABRT. Both
+/// wait for their child and pass its exit status back up.
+fn lock_down(jail: &Jail, limits: &SandboxLimits, uid: u32) -> io::Result<()> {
+    // SAFETY: plain syscalls on the calling process only
+    unsafe {
+        // Own process group so the whole tree can be killed on timeout
+        if libc::setpgid(0, 0) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let namespaces = libc::CLONE_NEWNS
+            | libc::CLONE_NEWPID
+            | libc::CLONE_NEWNET
+            | libc::CLONE_NEWIPC
+            | libc::CLONE_NEWUTS;
+        if libc::unshare(namespaces) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    // Passing a signal on means raising it, which must not dump core
+    set_limit(libc::RLIMIT_CORE, 0)?;
+
+    let mut status_pipe = [0; 2];
+    // SAFETY: status_pipe has room for both ends
+    if unsafe { libc::pipe2(status_pipe.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let [read_status, write_status] = status_pipe;
+
+    // SAFETY: the children only make async-signal-safe calls
+    unsafe {
+        match libc::fork() {
+            -1 => return Err(io::Error::last_os_error()),
+            0 => {}
+            init => {
+                // Holding on to Command's exec error pipe would keep spawn
+                // waiting until the program exits
+                close_other_fds(read_status);
+                let mut status = 0;
+                libc::waitpid(init, &mut status, 0);
+                let mut program = 0;
+                let size = std::mem::size_of::<libc::c_int>();
+                if libc::read(
+                    read_status,
+                    &mut program as *mut _ as *mut libc::c_void,
+                    size,
+                ) == size as isize
+                {
+                    status = program;
+                }
+                exit_like(status);
+            }
+        }
+
+        // The namespace's init
+        libc::close(read_status);
+        build_root(jail)?;
+        match libc::fork() {
+            -1 => return Err(io::Error::last_os_error()),
+            0 => {}
+            program => {
+                close_other_fds(write_status);
+                let mut status = 0;
+                // Reap whatever the program leaves behind until it exits
+                loop {
+                    let pid = libc::waitpid(-1, &mut status, 0);
+                    if pid == program || (pid == -1 && *libc::__errno_location() != libc::EINTR) {
+                        break;
+                    }
+                }
+                let size = std::mem::size_of::<libc::c_int>();
+                libc::write(
+                    write_status,
+                    &status as *const _ as *const libc::c_void,
+                    size,
+                );
+                libc::_exit(0);
+            }
+        }
+    }
+
+    set_limit(libc::RLIMIT_CPU, limits.cpu_seconds)?;
+    // RLIMIT_DATA rather than RLIMIT_AS: runtimes like V8 and Go reserve large
+    // address ranges up front without ever touching them
+    set_limit(libc::RLIMIT_DATA, limits.memory_bytes)?;
+    set_limit(libc::RLIMIT_FSIZE, limits.file_size_bytes)?;
+    set_limit(libc::RLIMIT_NPROC, limits.processes)?;
+
+    // SAFETY: plain syscalls on the calling process only
+    unsafe {
+        // Supplementary groups first, they can't be dropped after setuid
+        if libc::setgroups(0, std::ptr::null()) != 0
+            || libc::setgid(uid) != 0
+            || libc::setuid(uid) != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Setuid programs like su can't hand the privileges back
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Make the jail's root the process's root, with a fresh /proc for the new
+/// pid namespace, and enter WORKDIR
+///
+/// SAFETY: must run in a child in its own mount namespace
+unsafe fn build_root(jail: &Jail) -> io::Result<()> {
+    let check = |result: libc::c_int| {
+        if result != 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    };
+    let none = c"none".as_ptr();
+    let null = std::ptr::null();
+
+    // Keep our mounts from propagating back to the host
+    check(libc::mount(
+        none,
+        c"/".as_ptr(),
+        null,
+        libc::MS_REC | libc::MS_PRIVATE,
+        null as _,
+    ))?;
+    check(libc::mount(
+        c"tmpfs".as_ptr(),
+        jail.root.as_ptr(),
+        c"tmpfs".as_ptr(),
+        libc::MS_NOSUID | libc::MS_NODEV,
+        c"size=64k,mode=755".as_ptr() as _,
+    ))?;
+    for dir in &jail.dirs {
+        check(libc::mkdir(dir.as_ptr(), 0o755))?;
+    }
+
+    for (source, target, is_dir) in &jail.binds {
+        if *is_dir {
+            check(libc::mkdir(target.as_ptr(), 0o755))?;
+        } else {
+            let fd = libc::open(target.as_ptr(), libc::O_CREAT | libc::O_WRONLY, 0o644);
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            libc::close(fd);
+        }
+        check(libc::mount(
+            source.as_ptr(),
+            target.as_ptr(),
+            null,
+            libc::MS_BIND | libc::MS_REC,
+            null as _,
+        ))?;
+        let read_only = libc::MS_REMOUNT | libc::MS_BIND | libc::MS_RDONLY | libc::MS_NOSUID;
+        check(libc::mount(
+            none,
+            target.as_ptr(),
+            null,
+            read_only,
+            null as _,
+        ))?;
+    }
+    check(libc::mount(
+        jail.workdir_source.as_ptr(),
+        jail.workdir.as_ptr(),
+        null,
+        libc::MS_BIND,
+        null as _,
+    ))?;
+    let flags = libc::MS_REMOUNT | libc::MS_BIND | libc::MS_NOSUID | libc::MS_NODEV;
+    check(libc::mount(
+        none,
+        jail.workdir.as_ptr(),
+        null,
+        flags,
+        null as _,
+    ))?;
+    for (path, target) in &jail.links {
+        check(libc::symlink(target.as_ptr(), path.as_ptr()))?;
+    }
+
+    // Swap roots and drop the host's from view
+    check(libc::chdir(jail.root.as_ptr()))?;
+    check(libc::syscall(libc::SYS_pivot_root, c".".as_ptr(), c".old".as_ptr()) as libc::c_int)?;
+    check(libc::chdir(c"/".as_ptr()))?;
+    check(libc::umount2(c"/.old".as_ptr(), libc::MNT_DETACH))?;
+    check(libc::rmdir(c"/.old".as_ptr()))?;
+
+    // We're the new pid namespace's init, so /proc shows only the sandbox
+    let flags = libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC;
+    check(libc::mount(
+        c"proc".as_ptr(),
+        c"/proc".as_ptr(),
+        c"proc".as_ptr(),
+        flags,
+        null as _,
+    ))?;
+    let flags = libc::MS_REMOUNT | libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV;
+    check(libc::mount(none, c"/".as_ptr(), null, flags, null as _))?;
+    check(libc::chdir(c"/sandbox".as_ptr()))
+}
+
+/// Close every file descriptor besides stdio and `keep`
+///
+/// SAFETY: must run in a forked child that no longer needs them
+unsafe fn close_other_fds(keep: libc::c_int) {
+    let close_range = |first: libc::c_int, last: libc::c_uint| {
+        libc::syscall(libc::SYS_close_range, first as libc::c_uint, last, 0)
+    };
+    if keep > 3 {
+        close_range(3, keep as libc::c_uint - 1);
+    }
+    close_range(keep + 1, libc::c_uint::MAX);
+}
+
+/// Exit the way a child with wait `status` did, re-raising its signal
+///
+/// SAFETY: must run in a forked child
+unsafe fn exit_like(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        libc::signal(signal, libc::SIG_DFL);
+        let mut set = std::mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, signal);
+        libc::sigprocmask(libc::SIG_UNBLOCK, &set, std::ptr::null_mut());
+        libc::kill(libc::getpid(), signal);
+        libc::_exit(128 + signal);
+    }
+    libc::_exit(libc::WEXITSTATUS(status))
+}
+
+/// glibc takes rlimit resources as an enum, other libcs as an int
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+type Resource = libc::c_int;
+
+fn set_limit(resource: Resource, value: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    // SAFETY: limit is a valid rlimit for the duration of the call
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn kill_group(pgid: Option<libc::pid_t>) {
+    if let Some(pgid) = pgid {
+        // SAFETY: signals only the sandboxed process group
+        unsafe {
+            libc::kill(-pgid, libc::SIGKILL);
+        }
+    }
+}
+
+/// Kill every process running as `uid`, including those that left the
+/// sandbox's process group, so nothing outlives the request
+fn kill_user(uid: u32) {
+    // SAFETY: the child only makes async-signal-safe calls before _exit
+    unsafe {
+        match libc::fork() {
+            0 => {
+                // kill(-1) signals everything the caller may signal, which
+                // as `uid` is exactly that user's processes
+                if libc::setgid(uid) == 0 && libc::setuid(uid) == 0 {
+                    libc::kill(-1, libc::SIGKILL);
+                }
+                libc::_exit(0);
+            }
+            -1 => {}
+            pid => {
+                libc::waitpid(pid, std::ptr::null_mut(), 0);
+            }
+        }
+    }
+}
+
+/// Read a pipe until EOF, keeping at most MAX_CAPTURE bytes. Dropping the
+/// pipe early leaves chatty programs to die from SIGPIPE.
+async fn capture<R: AsyncRead + Unpin>(pipe: Option<R>) -> (String, bool) {
+    let mut buffer = Vec::new();
+    if let Some(pipe) = pipe {
+        let _ = pipe.take(MAX_CAPTURE + 1).read_to_end(&mut buffer).await;
+    }
+
+    let truncated = buffer.len() as u64 > MAX_CAPTURE;
+    buffer.truncate(MAX_CAPTURE as usize);
+    (String::from_utf8_lossy(&buffer).into_owned(), truncated)
+}
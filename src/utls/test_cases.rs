@@ -0,0 +1,116 @@
+//! Checks a program's output against what a ;test case expects.
+
+use serenity::framework::standard::CommandError;
+
+/// One stdin to run the program with and the output it should give
+#[derive(Debug, PartialEq)]
+pub struct TestCase {
+    pub input: String,
+    pub expected: String,
+}
+
+/// How strictly program output is compared with the expected output
+#[derive(Debug, Default, PartialEq)]
+pub struct Comparison {
+    /// Compare whitespace-separated tokens, ignoring spacing and line breaks
+    pub ignore_whitespace: bool,
+    /// Largest difference allowed between numbers, relative to the larger
+    /// one once it's above 1
+    pub tolerance: Option<f64>,
+}
+
+impl Comparison {
+    /// Remove `--ignore-whitespace` and `--tolerance=<n>` from the compiler
+    /// options
+    pub fn take(options: &mut Vec<String>) -> Result<Self, CommandError> {
+        let mut comparison = Comparison::default();
+        let mut remaining = Vec::new();
+        for opt in std::mem::take(options) {
+            if opt == "--ignore-whitespace" {
+                comparison.ignore_whitespace = true;
+            } else if let Some(tolerance) = opt.strip_prefix("--tolerance=") {
+                let tolerance = tolerance
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|tolerance| tolerance.is_finite() && *tolerance >= 0.0)
+                    .ok_or_else(|| {
+                        CommandError::from(format!(
+                            "Invalid tolerance '{}', expected something like 1e-6",
+                            tolerance
+                        ))
+                    })?;
+                comparison.tolerance = Some(tolerance);
+            } else {
+                remaining.push(opt);
+            }
+        }
+
+        *options = remaining;
+        Ok(comparison)
+    }
+
+    /// Whether the program's output counts as the expected one. Trailing
+    /// whitespace on each line and trailing blank lines never matter.
+    pub fn matches(&self, expected: &str, actual: &str) -> bool {
+        if self.ignore_whitespace || self.tolerance.is_some() {
+            let expected: Vec<&str> = expected.split_whitespace().collect();
+            let actual: Vec<&str> = actual.split_whitespace().collect();
+            return expected.len() == actual.len()
+                && expected
+                    .iter()
+                    .zip(&actual)
+                    .all(|(expected, actual)| self.tokens_match(expected, actual));
+        }
+
+        trim_lines(expected) == trim_lines(actual)
+    }
+
+    fn tokens_match(&self, expected: &str, actual: &str) -> bool {
+        if expected == actual {
+            return true;
+        }
+        match (
+            self.tolerance,
+            expected.parse::<f64>(),
+            actual.parse::<f64>(),
+        ) {
+            (Some(tolerance), Ok(expected), Ok(actual)) => {
+                let scale = expected.abs().max(actual.abs()).max(1.0);
+                (expected - actual).abs() <= tolerance * scale
+            }
+            _ => false,
+        }
+    }
+}
+
+fn trim_lines(output: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = output.lines().map(str::trim_end).collect();
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+/// How one test case went
+pub enum CaseOutcome {
+    Passed,
+    WrongAnswer {
+        diff: String,
+    },
+    /// The program didn't exit cleanly, or didn't build
+    Failed,
+}
+
+/// Unified diff from the expected output to what the program printed
+pub fn diff_output(expected: &str, actual: &str) -> String {
+    let with_newline = |text: &str| {
+        let mut text = text.trim_end().to_string();
+        text.push('\n');
+        text
+    };
+    let (expected, actual) = (with_newline(expected), with_newline(actual));
+    similar::TextDiff::from_lines(&expected, &actual)
+        .unified_diff()
+        .header("expected", "actual")
+        .to_string()
+}
@@ -0,0 +1,110 @@
+//! Recognizes programs that crashed, and reads the report of a rerun with
+//! AddressSanitizer and UBSan to point at the line that caused it.
+//!
+//! Backends describe crashes differently: WandBox gives a description like
+//! "Segmentation fault", Piston a signal name, the local sandbox a signal
+//! number and Compiler Explorer an exit status with the signal in the
+//! program's output.
+
+use crate::utls::constants::SANITIZER_LOCATION_REGEX;
+use crate::utls::diagnostics::{self, Diagnostic, Severity};
+use crate::utls::parser::SourceFile;
+
+/// Signals a sanitizer rerun can explain: name, number and description
+const CRASH_SIGNALS: &[(&str, i32, &str)] = &[
+    ("SIGILL", 4, "Illegal instruction"),
+    ("SIGABRT", 6, "Aborted"),
+    ("SIGBUS", 7, "Bus error"),
+    ("SIGFPE", 8, "Floating point exception"),
+    ("SIGSEGV", 11, "Segmentation fault"),
+];
+
+/// The crash signal a backend reported by name or description
+pub fn signal_name(signal: &str) -> Option<&'static str> {
+    let signal = signal.trim();
+    CRASH_SIGNALS
+        .iter()
+        .find(|(name, _, description)| {
+            signal.eq_ignore_ascii_case(name) || signal.eq_ignore_ascii_case(description)
+        })
+        .map(|(name, _, _)| *name)
+}
+
+/// The crash signal with the given number
+pub fn signal_number(number: i32) -> Option<&'static str> {
+    CRASH_SIGNALS
+        .iter()
+        .find(|(_, signal, _)| *signal == number)
+        .map(|(name, _, _)| *name)
+}
+
+/// The signal that killed a program on Compiler Explorer, from its exit
+/// status and output
+pub fn exit_signal(code: i32, output: &str) -> Option<&'static str> {
+    if code == 0 {
+        return None;
+    }
+
+    CRASH_SIGNALS
+        .iter()
+        .find(|(name, _, _)| output.contains(name))
+        .map(|(name, _, _)| *name)
+        .or_else(|| signal_number(code - 128))
+}
+
+/// The first problem in a sanitizer report, and where the user's code hit it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+/// Find the first sanitizer error in the report and the first frame of it in
+/// the user's source, or None if no sanitizer complained
+pub fn analyze(report: &str) -> Option<Finding> {
+    let lines: Vec<&str> = report.lines().collect();
+    let (start, message) = lines.iter().enumerate().find_map(|(index, line)| {
+        let message = match line.split_once("runtime error: ") {
+            Some((_, message)) => message,
+            None => line.split_once("ERROR: ")?.1,
+        };
+        Some((index, message.trim().to_string()))
+    })?;
+
+    let location = lines[start..]
+        .iter()
+        .find_map(|line| SANITIZER_LOCATION_REGEX.captures(line));
+    let number = |name| {
+        location
+            .as_ref()
+            .and_then(|captures| captures.name(name))
+            .and_then(|value| value.as_str().parse().ok())
+    };
+
+    Some(Finding {
+        message,
+        line: number("line"),
+        column: number("column"),
+    })
+}
+
+/// A finding shown against the user's source when it has a location
+pub fn render(finding: &Finding, source: &str) -> String {
+    match finding.line {
+        Some(line) => diagnostics::render(
+            &Diagnostic {
+                file: String::from("<source>"),
+                line,
+                column: finding.column,
+                severity: Severity::Error,
+                message: finding.message.clone(),
+            },
+            &[SourceFile {
+                name: String::from("<source>"),
+                code: source.to_string(),
+            }],
+        ),
+        None => format!("error: {}\n", finding.message),
+    }
+}